@@ -0,0 +1,3 @@
+fn main() -> std::process::ExitCode {
+    balance_proxy_desktop_lib::run_cli()
+}
@@ -0,0 +1,356 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use axum::http::HeaderMap;
+use serde::{Deserialize, Serialize};
+
+use crate::now_ts;
+
+const REDACTED: &str = "[REDACTED]";
+const SENSITIVE_HEADERS: [&str; 5] = [
+    "authorization",
+    "proxy-authorization",
+    "x-api-key",
+    "cookie",
+    "set-cookie",
+];
+/// Secrets shorter than this are not scrubbed from bodies; replacing tiny
+/// strings would mangle unrelated content.
+const MIN_SECRET_LEN: usize = 6;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct CaptureSettings {
+    pub(crate) max_requests: u32,
+    pub(crate) max_minutes: u64,
+    pub(crate) max_body_bytes: usize,
+}
+
+impl Default for CaptureSettings {
+    fn default() -> Self {
+        Self {
+            max_requests: 50,
+            max_minutes: 10,
+            max_body_bytes: 64 * 1024,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct CaptureStatus {
+    pub(crate) active: bool,
+    pub(crate) remaining_requests: u32,
+    pub(crate) expires_at: Option<u64>,
+    pub(crate) settings: CaptureSettings,
+    pub(crate) directory: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct CapturedHeader {
+    pub(crate) name: String,
+    pub(crate) value: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct CapturedBody {
+    pub(crate) text: String,
+    pub(crate) size: usize,
+    pub(crate) truncated: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct CaptureEntry {
+    pub(crate) id: String,
+    pub(crate) ts: u64,
+    pub(crate) provider: String,
+    pub(crate) request_id: String,
+    pub(crate) method: String,
+    pub(crate) path: String,
+    pub(crate) query: Option<String>,
+    pub(crate) upstream_url: String,
+    pub(crate) request_headers: Vec<CapturedHeader>,
+    pub(crate) request_body: CapturedBody,
+    pub(crate) status: u16,
+    pub(crate) key_index: Option<usize>,
+    pub(crate) retries: usize,
+    pub(crate) latency_ms: u64,
+    pub(crate) response_headers: Vec<CapturedHeader>,
+    pub(crate) response_body: CapturedBody,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct CaptureSummary {
+    pub(crate) id: String,
+    pub(crate) ts: u64,
+    pub(crate) provider: String,
+    pub(crate) method: String,
+    pub(crate) path: String,
+    pub(crate) status: u16,
+    pub(crate) latency_ms: u64,
+}
+
+struct ActiveCapture {
+    settings: CaptureSettings,
+    remaining: u32,
+    expires_at: u64,
+}
+
+/// Runtime-toggled debug capture. While active, up to `max_requests` proxied
+/// exchanges (or as many as arrive within `max_minutes`) are written as
+/// redacted JSON files to `dir`.
+#[derive(Clone)]
+pub(crate) struct DebugCapture {
+    dir: PathBuf,
+    active: Arc<Mutex<Option<ActiveCapture>>>,
+}
+
+impl DebugCapture {
+    pub(crate) fn new(dir: PathBuf) -> Self {
+        Self {
+            dir,
+            active: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    pub(crate) fn start(&self, settings: CaptureSettings) -> Result<CaptureStatus, String> {
+        if settings.max_requests == 0 || settings.max_minutes == 0 || settings.max_body_bytes == 0 {
+            return Err("Capture limits must all be greater than 0".to_string());
+        }
+        fs::create_dir_all(&self.dir)
+            .map_err(|e| format!("Failed to create capture dir: {}", e))?;
+        *self.lock() = Some(ActiveCapture {
+            settings,
+            remaining: settings.max_requests,
+            expires_at: now_ts() + settings.max_minutes * 60,
+        });
+        Ok(self.status())
+    }
+
+    pub(crate) fn stop(&self) -> CaptureStatus {
+        *self.lock() = None;
+        self.status()
+    }
+
+    pub(crate) fn status(&self) -> CaptureStatus {
+        let mut guard = self.lock();
+        expire_if_done(&mut guard, now_ts());
+        let directory = self.dir.to_string_lossy().to_string();
+        match guard.as_ref() {
+            Some(active) => CaptureStatus {
+                active: true,
+                remaining_requests: active.remaining,
+                expires_at: Some(active.expires_at),
+                settings: active.settings,
+                directory,
+            },
+            None => CaptureStatus {
+                active: false,
+                remaining_requests: 0,
+                expires_at: None,
+                settings: CaptureSettings::default(),
+                directory,
+            },
+        }
+    }
+
+    /// Claims one capture slot, returning the body size limit to apply, or
+    /// `None` when capture is off or its request/time budget is spent.
+    pub(crate) fn try_claim(&self) -> Option<usize> {
+        let mut guard = self.lock();
+        expire_if_done(&mut guard, now_ts());
+        let active = guard.as_mut()?;
+        active.remaining -= 1;
+        let max_body_bytes = active.settings.max_body_bytes;
+        expire_if_done(&mut guard, now_ts());
+        Some(max_body_bytes)
+    }
+
+    pub(crate) fn write(&self, entry: &CaptureEntry) -> Result<PathBuf, String> {
+        fs::create_dir_all(&self.dir)
+            .map_err(|e| format!("Failed to create capture dir: {}", e))?;
+        let path = self.entry_path(&entry.id)?;
+        let text = serde_json::to_string_pretty(entry)
+            .map_err(|e| format!("Failed to serialize capture: {}", e))?;
+        fs::write(&path, text).map_err(|e| format!("Failed to write capture: {}", e))?;
+        Ok(path)
+    }
+
+    pub(crate) fn list(&self) -> Result<Vec<CaptureSummary>, String> {
+        let mut summaries = Vec::new();
+        let entries = match fs::read_dir(&self.dir) {
+            Ok(entries) => entries,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(summaries),
+            Err(err) => return Err(format!("Failed to read capture dir: {}", err)),
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.extension().is_none_or(|ext| ext != "json") {
+                continue;
+            }
+            let Ok(capture) = read_entry(&path) else {
+                continue;
+            };
+            summaries.push(CaptureSummary {
+                id: capture.id,
+                ts: capture.ts,
+                provider: capture.provider,
+                method: capture.method,
+                path: capture.path,
+                status: capture.status,
+                latency_ms: capture.latency_ms,
+            });
+        }
+        summaries.sort_by(|a, b| b.ts.cmp(&a.ts).then_with(|| b.id.cmp(&a.id)));
+        Ok(summaries)
+    }
+
+    pub(crate) fn load(&self, id: &str) -> Result<CaptureEntry, String> {
+        read_entry(&self.entry_path(id)?)
+    }
+
+    pub(crate) fn clear(&self) -> Result<usize, String> {
+        let mut removed = 0;
+        let entries = match fs::read_dir(&self.dir) {
+            Ok(entries) => entries,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(0),
+            Err(err) => return Err(format!("Failed to read capture dir: {}", err)),
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.extension().is_some_and(|ext| ext == "json") && fs::remove_file(&path).is_ok() {
+                removed += 1;
+            }
+        }
+        Ok(removed)
+    }
+
+    fn entry_path(&self, id: &str) -> Result<PathBuf, String> {
+        if id.is_empty()
+            || !id
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+        {
+            return Err("Invalid capture id".to_string());
+        }
+        Ok(self.dir.join(format!("{}.json", id)))
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Option<ActiveCapture>> {
+        self.active.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+fn expire_if_done(active: &mut Option<ActiveCapture>, now: u64) {
+    if active
+        .as_ref()
+        .is_some_and(|a| a.remaining == 0 || now >= a.expires_at)
+    {
+        *active = None;
+    }
+}
+
+fn read_entry(path: &Path) -> Result<CaptureEntry, String> {
+    let text = fs::read_to_string(path).map_err(|e| format!("Failed to read capture: {}", e))?;
+    serde_json::from_str(&text).map_err(|e| format!("Failed to parse capture: {}", e))
+}
+
+/// Replaces every occurrence of a configured secret with a placeholder.
+pub(crate) fn redact_text(text: &str, secrets: &[String]) -> String {
+    let mut redacted = text.to_string();
+    for secret in secrets {
+        if secret.len() >= MIN_SECRET_LEN {
+            redacted = redacted.replace(secret.as_str(), REDACTED);
+        }
+    }
+    redacted
+}
+
+pub(crate) fn redact_headers(headers: &HeaderMap, secrets: &[String]) -> Vec<CapturedHeader> {
+    headers
+        .iter()
+        .map(|(name, value)| {
+            let name = name.as_str().to_ascii_lowercase();
+            let value = if SENSITIVE_HEADERS.contains(&name.as_str()) {
+                REDACTED.to_string()
+            } else {
+                redact_text(&String::from_utf8_lossy(value.as_bytes()), secrets)
+            };
+            CapturedHeader { name, value }
+        })
+        .collect()
+}
+
+pub(crate) fn capture_body(body: &[u8], max_bytes: usize, secrets: &[String]) -> CapturedBody {
+    let truncated = body.len() > max_bytes;
+    let slice = if truncated { &body[..max_bytes] } else { body };
+    CapturedBody {
+        text: redact_text(&String::from_utf8_lossy(slice), secrets),
+        size: body.len(),
+        truncated,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::HeaderValue;
+    use uuid::Uuid;
+
+    #[test]
+    fn redaction_covers_headers_and_bodies() {
+        let secrets = vec!["local-token".to_string(), "fc-secret-key".to_string()];
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            "authorization",
+            HeaderValue::from_static("Bearer local-token"),
+        );
+        headers.insert("x-echo", HeaderValue::from_static("fc-secret-key"));
+        headers.insert("content-type", HeaderValue::from_static("application/json"));
+
+        let captured = redact_headers(&headers, &secrets);
+        assert!(captured.iter().all(|h| !h.value.contains("local-token")));
+        assert!(captured.iter().all(|h| !h.value.contains("fc-secret-key")));
+        assert!(captured
+            .iter()
+            .any(|h| h.name == "content-type" && h.value == "application/json"));
+
+        let body = capture_body(br#"{"api_key":"fc-secret-key","q":"rust"}"#, 1024, &secrets);
+        assert_eq!(body.text, r#"{"api_key":"[REDACTED]","q":"rust"}"#);
+        assert!(!body.truncated);
+    }
+
+    #[test]
+    fn capture_body_truncates_to_limit() {
+        let body = capture_body(b"0123456789", 4, &[]);
+        assert_eq!(body.text, "0123");
+        assert_eq!(body.size, 10);
+        assert!(body.truncated);
+    }
+
+    #[test]
+    fn capture_stops_after_request_budget() {
+        let dir = std::env::temp_dir().join(format!("balance-proxy-capture-{}", Uuid::new_v4()));
+        let capture = DebugCapture::new(dir.clone());
+        assert_eq!(capture.try_claim(), None);
+
+        capture
+            .start(CaptureSettings {
+                max_requests: 2,
+                max_minutes: 5,
+                max_body_bytes: 128,
+            })
+            .expect("capture should start");
+        assert_eq!(capture.try_claim(), Some(128));
+        assert!(capture.status().active);
+        assert_eq!(capture.try_claim(), Some(128));
+        assert_eq!(capture.try_claim(), None);
+        assert!(!capture.status().active);
+        let _ = fs::remove_dir_all(dir);
+    }
+}
@@ -0,0 +1,653 @@
+//! Headless `balance-proxy` command line. Runs the same proxy core as the
+//! desktop app, configured from a JSON file, a `.env` file and environment
+//! variables (later sources win).
+
+use std::collections::HashMap;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use std::sync::Arc;
+use std::time::Duration;
+
+use reqwest::Client;
+use tokio::sync::{Mutex, RwLock};
+use tracing::{info, warn};
+
+use crate::capture::DebugCapture;
+use crate::config_schema::{
+    log_load_notes, recover_corrupt_config, upgrade_config_file, write_config_file, CONFIG_VERSION,
+};
+use crate::env_config::{
+    parse_dotenv, process_env_vars, resolve_config, ConfigSource, ResolvedConfig,
+};
+use crate::events::EventBus;
+use crate::key_import::KeyImport;
+use crate::key_probe::{probe_key_list, ProbeOutcome, DEFAULT_PROBE_CONCURRENCY};
+use crate::key_watch::{watch_key_folder, KEY_WATCH_STATE_FILE};
+use crate::keys::remember_removed_keys;
+use crate::logging::{init_tracing, parse_log_level, LogBuffer};
+use crate::profiles::active_profile;
+use crate::secrets::PASSPHRASE_ENV_VAR;
+use crate::{
+    build_http_client, idle_key_statuses, key_fingerprint, open_history_store, open_log_file,
+    open_tracer_provider, parse_provider, provider_display_name, read_config_file,
+    render_mcp_config, start_all_providers, stop_all_providers, warn_unavailable,
+    ActiveKeyManagers, AppState, KeyStatus, ProxyConfig, ProxyRuntime, CAPTURE_DIR_NAME, PROVIDERS,
+};
+
+const USAGE: &str = "\
+Usage: balance-proxy [OPTIONS] <COMMAND>
+
+Commands:
+  serve                  Run the configured proxies until interrupted
+  validate-config        Check the effective configuration and exit
+  keys status            Show key health (live when the proxy is running)
+  keys import FILE...    Add keys from key lists, account files, CSV or JSON
+                         to the --config file
+  keys check [PROVIDER]  Test-call each key against the upstream; with
+                         --remove-invalid, drop rejected keys from --config
+  mcp-config [TARGET]    Print MCP client config; TARGET is firecrawl, tavily or both
+
+Options:
+  -c, --config <PATH>    JSON config file, same format as the desktop app's
+  -e, --env-file <PATH>  .env file to read (default: ./.env when present)
+  -d, --data-dir <DIR>   Directory for logs, history and captures
+                         (default: the config file's directory, else .)
+  -h, --help             Print this help
+
+Environment variables named after config fields (PROXY_TOKEN, TAVILY_API_KEYS,
+PORT, ...) override the config file and the .env file.";
+
+const LIVE_STATUS_TIMEOUT: Duration = Duration::from_secs(2);
+
+#[derive(Debug, PartialEq, Eq)]
+enum Command {
+    Serve,
+    ValidateConfig,
+    KeysStatus,
+    KeysImport(Vec<PathBuf>),
+    KeysCheck {
+        provider: Option<String>,
+        remove_invalid: bool,
+    },
+    McpConfig(String),
+    Help,
+}
+
+#[derive(Debug)]
+struct CliOptions {
+    config_path: Option<PathBuf>,
+    env_file: Option<PathBuf>,
+    data_dir: Option<PathBuf>,
+    command: Command,
+}
+
+/// Entry point of the `balance-proxy` binary.
+pub fn run_cli() -> ExitCode {
+    let options = match parse_args(env::args().skip(1)) {
+        Ok(options) => options,
+        Err(err) => {
+            eprintln!("error: {}\n\n{}", err, USAGE);
+            return ExitCode::from(2);
+        }
+    };
+    if options.command == Command::Help {
+        println!("{}", USAGE);
+        return ExitCode::SUCCESS;
+    }
+
+    let runtime = match tokio::runtime::Runtime::new() {
+        Ok(runtime) => runtime,
+        Err(err) => {
+            eprintln!("error: Failed to start async runtime: {}", err);
+            return ExitCode::FAILURE;
+        }
+    };
+    match runtime.block_on(run_command(options)) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("error: {}", err);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn parse_args(args: impl IntoIterator<Item = String>) -> Result<CliOptions, String> {
+    let mut config_path = None;
+    let mut env_file = None;
+    let mut data_dir = None;
+    let mut remove_invalid = false;
+    let mut words = Vec::new();
+
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        let mut value_for = |flag: &str| {
+            args.next()
+                .map(PathBuf::from)
+                .ok_or_else(|| format!("{} expects a value", flag))
+        };
+        match arg.as_str() {
+            "-c" | "--config" => config_path = Some(value_for(&arg)?),
+            "-e" | "--env-file" => env_file = Some(value_for(&arg)?),
+            "-d" | "--data-dir" => data_dir = Some(value_for(&arg)?),
+            "--remove-invalid" => remove_invalid = true,
+            "-h" | "--help" => words = vec!["help".to_string()],
+            flag if flag.starts_with('-') => return Err(format!("Unknown option '{}'", flag)),
+            _ => words.push(arg),
+        }
+    }
+
+    let words: Vec<&str> = words.iter().map(String::as_str).collect();
+    let command = match words.as_slice() {
+        [] | ["help"] => Command::Help,
+        ["serve"] => Command::Serve,
+        ["validate-config"] => Command::ValidateConfig,
+        ["keys", "status"] => Command::KeysStatus,
+        ["keys", "import", files @ ..] if !files.is_empty() => {
+            Command::KeysImport(files.iter().map(PathBuf::from).collect())
+        }
+        ["keys", "check"] => Command::KeysCheck {
+            provider: None,
+            remove_invalid,
+        },
+        ["keys", "check", provider] => Command::KeysCheck {
+            provider: Some(provider.to_ascii_lowercase()),
+            remove_invalid,
+        },
+        ["mcp-config"] => Command::McpConfig("both".to_string()),
+        ["mcp-config", target] => Command::McpConfig(target.to_ascii_lowercase()),
+        _ => return Err(format!("Unknown command '{}'", words.join(" "))),
+    };
+    if remove_invalid && !matches!(command, Command::KeysCheck { .. }) {
+        return Err("--remove-invalid only applies to keys check".to_string());
+    }
+
+    Ok(CliOptions {
+        config_path,
+        env_file,
+        data_dir,
+        command,
+    })
+}
+
+impl CliOptions {
+    fn data_dir(&self) -> PathBuf {
+        self.data_dir
+            .clone()
+            .or_else(|| {
+                self.config_path
+                    .as_deref()
+                    .and_then(Path::parent)
+                    .filter(|dir| !dir.as_os_str().is_empty())
+                    .map(Path::to_path_buf)
+            })
+            .unwrap_or_else(|| PathBuf::from("."))
+    }
+
+    fn load_config(&self) -> Result<ResolvedConfig, String> {
+        let env_file = match &self.env_file {
+            Some(path) => Some(path.clone()),
+            None => Some(PathBuf::from(".env")).filter(|path| path.is_file()),
+        };
+        let dotenv = match env_file {
+            Some(path) => {
+                let text = fs::read_to_string(&path).map_err(|e| {
+                    format!("Failed to read env file {}: {}", path.to_string_lossy(), e)
+                })?;
+                parse_dotenv(&text)?
+            }
+            None => HashMap::new(),
+        };
+
+        let mut migrated_from = None;
+        if let Some(path) = &self.config_path {
+            if let Some(recovery) = recover_corrupt_config(path)? {
+                eprintln!(
+                    "warning: {} could not be loaded ({}); restored backup {} and moved the broken file to {}",
+                    path.to_string_lossy(),
+                    recovery.error,
+                    recovery.backup,
+                    recovery.corrupt_copy
+                );
+            }
+            migrated_from = upgrade_config_file(path)?;
+        }
+        let mut resolved = resolve_config(
+            self.config_path.as_deref(),
+            &[
+                (ConfigSource::DotEnv, &dotenv),
+                (ConfigSource::Env, &process_env_vars()),
+            ],
+        )?;
+        if resolved.locked {
+            return Err(format!(
+                "Config secrets are encrypted with a passphrase; set {} to unlock them",
+                PASSPHRASE_ENV_VAR
+            ));
+        }
+        // The file was stored in the current format before it was loaded.
+        resolved.migrated_from = migrated_from;
+        Ok(resolved)
+    }
+}
+
+async fn run_command(options: CliOptions) -> Result<(), String> {
+    let resolved = options.load_config()?;
+    let config = resolved.config.clone();
+    match &options.command {
+        Command::Serve => serve(resolved, options.config_path.clone(), &options.data_dir()).await,
+        Command::ValidateConfig => validate_config(&resolved),
+        Command::KeysStatus => keys_status(&config).await,
+        Command::KeysCheck {
+            provider,
+            remove_invalid,
+        } => {
+            let config_path = options.config_path.as_deref();
+            check_keys(&config, config_path, provider.as_deref(), *remove_invalid).await
+        }
+        Command::KeysImport(files) => import_key_files(options.config_path.as_deref(), files),
+        Command::McpConfig(target) => {
+            let data_dir = options.data_dir();
+            fs::create_dir_all(&data_dir)
+                .map_err(|e| format!("Failed to create data dir: {}", e))?;
+            // MCP clients launch the Tavily script from their own directory.
+            let data_dir = fs::canonicalize(&data_dir)
+                .map_err(|e| format!("Failed to resolve data dir: {}", e))?;
+            println!("{}", render_mcp_config(&data_dir, &config, target)?);
+            Ok(())
+        }
+        Command::Help => Ok(()),
+    }
+}
+
+async fn serve(
+    resolved: ResolvedConfig,
+    config_path: Option<PathBuf>,
+    data_dir: &Path,
+) -> Result<(), String> {
+    let ResolvedConfig {
+        config,
+        sources,
+        unknown_keys,
+        migrated_from,
+        ..
+    } = resolved;
+    config.validate()?;
+    fs::create_dir_all(data_dir).map_err(|e| format!("Failed to create data dir: {}", e))?;
+
+    let logs = LogBuffer::default();
+    let events = EventBus::default();
+    let log_file = open_log_file(data_dir, &config);
+    let tracer_provider = open_tracer_provider(&config);
+    let log_handle = init_tracing(
+        parse_log_level(&config.log_level)?,
+        config.log_json,
+        logs.clone(),
+        events.clone(),
+        log_file.clone().unwrap_or_default(),
+        tracer_provider.clone().unwrap_or_default(),
+    )?;
+    info!(data_dir = %data_dir.to_string_lossy(), "Headless proxy initialized");
+    warn_unavailable("Log file", &log_file);
+    warn_unavailable("OTLP export", &tracer_provider);
+    log_load_notes(&unknown_keys, migrated_from);
+    let profile = config_path.as_deref().map(active_profile);
+    if let Some(profile) = &profile {
+        info!(profile = %profile, "Using config profile");
+    }
+
+    let state = AppState {
+        history: open_history_store(data_dir, &config),
+        capture: DebugCapture::new(data_dir.join(CAPTURE_DIR_NAME)),
+        config: Arc::new(RwLock::new(config)),
+        config_sources: Arc::new(RwLock::new(sources)),
+        runtime: Arc::new(Mutex::new(ProxyRuntime {
+            active_profile: profile,
+            ..ProxyRuntime::default()
+        })),
+        logs,
+        log_handle: log_handle.clone(),
+        events,
+        active_key_managers: Arc::new(Mutex::new(ActiveKeyManagers::default())),
+    };
+
+    match config_path {
+        Some(config_path) => {
+            tokio::spawn(watch_key_folder(
+                state.clone(),
+                config_path,
+                data_dir.join(KEY_WATCH_STATE_FILE),
+                || {},
+            ));
+        }
+        None if !state.config.read().await.key_watch_dir.is_empty() => {
+            warn!("KEY_WATCH_DIR needs --config to save ingested keys; not watching");
+        }
+        None => {}
+    }
+
+    let result = match start_all_providers(&state).await {
+        Ok(_) => {
+            shutdown_signal().await;
+            info!("Shutdown requested");
+            stop_all_providers(&state).await;
+            Ok(())
+        }
+        Err(err) => Err(err),
+    };
+    log_handle.shutdown();
+    result
+}
+
+async fn shutdown_signal() {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{signal, SignalKind};
+        match signal(SignalKind::terminate()) {
+            Ok(mut terminate) => {
+                tokio::select! {
+                    _ = tokio::signal::ctrl_c() => {}
+                    _ = terminate.recv() => {}
+                }
+            }
+            Err(_) => {
+                let _ = tokio::signal::ctrl_c().await;
+            }
+        }
+    }
+    #[cfg(not(unix))]
+    let _ = tokio::signal::ctrl_c().await;
+}
+
+/// Merges keys from `files` into the config file; the desktop app and a
+/// running `serve` pick them up on their next config load.
+fn import_key_files(config_path: Option<&Path>, files: &[PathBuf]) -> Result<(), String> {
+    let config_path = config_path.ok_or("keys import needs --config to know where to save")?;
+    let files = files
+        .iter()
+        .map(|path| {
+            fs::read_to_string(path)
+                .map(|text| (path.to_string_lossy().to_string(), text))
+                .map_err(|e| format!("Failed to read {}: {}", path.display(), e))
+        })
+        .collect::<Result<Vec<_>, String>>()?;
+    let mut config = read_config_file(config_path)?;
+    let mut import = KeyImport::scan(&files, None);
+    for provider in import.providers() {
+        import.merge_into(provider, config.provider_keys_mut(provider));
+    }
+    write_config_file(config_path, &config)?;
+
+    let report = import.finish();
+    for (heading, entries) in [("Skipped", &report.skipped), ("Invalid", &report.invalid)] {
+        for entry in entries {
+            println!(
+                "{} {}:{} {}: {}",
+                heading, entry.file, entry.line, entry.key, entry.reason
+            );
+        }
+    }
+    println!(
+        "Added {} keys, skipped {}, invalid {}",
+        report.added.len(),
+        report.skipped.len(),
+        report.invalid.len()
+    );
+    Ok(())
+}
+
+/// Probes every enabled key of the configured providers (or just
+/// `provider`) and prints one line per key.
+async fn check_keys(
+    config: &ProxyConfig,
+    config_path: Option<&Path>,
+    provider: Option<&str>,
+    remove_invalid: bool,
+) -> Result<(), String> {
+    if remove_invalid && config_path.is_none() {
+        return Err("--remove-invalid needs --config to know where to save".to_string());
+    }
+    let providers = match provider {
+        Some(provider) => vec![parse_provider(provider)?],
+        None => PROVIDERS
+            .into_iter()
+            .filter(|p| config.provider_enabled(p))
+            .collect(),
+    };
+    let client = build_http_client(config)?;
+    let mut rejected = Vec::new();
+    for provider in providers {
+        let keys = config.enabled_keys(provider);
+        let upstream = config.provider_upstream(provider);
+        if keys.is_empty() || upstream.is_empty() {
+            println!("{}: not configured\n", provider_display_name(provider));
+            continue;
+        }
+        println!("{}: {}", provider_display_name(provider), upstream);
+        let results = probe_key_list(
+            &client,
+            provider,
+            upstream,
+            &keys,
+            DEFAULT_PROBE_CONCURRENCY,
+        )
+        .await;
+        for result in &results {
+            println!(
+                "  {} {:<12} {}{}",
+                result.key,
+                result.outcome.label(),
+                result.label,
+                if result.detail.is_empty() {
+                    String::new()
+                } else {
+                    format!(" ({})", result.detail)
+                }
+            );
+        }
+        println!();
+        rejected.extend(
+            results
+                .into_iter()
+                .filter(|result| result.outcome == ProbeOutcome::Invalid)
+                .map(|result| (provider, result.fingerprint)),
+        );
+    }
+
+    if let Some(config_path) = config_path.filter(|_| remove_invalid && !rejected.is_empty()) {
+        let mut on_disk = read_config_file(config_path)?;
+        for (provider, fingerprint) in &rejected {
+            let before = on_disk.provider_keys(provider).to_vec();
+            on_disk
+                .provider_keys_mut(provider)
+                .retain(|key| key_fingerprint(&key.value) != *fingerprint);
+            remember_removed_keys(config_path, &before, on_disk.provider_keys(provider))?;
+        }
+        write_config_file(config_path, &on_disk)?;
+        println!("Removed {} invalid keys", rejected.len());
+    }
+    Ok(())
+}
+
+fn validate_config(resolved: &ResolvedConfig) -> Result<(), String> {
+    let config = &resolved.config;
+    if let Some(from) = resolved.migrated_from {
+        println!(
+            "Migrated config file from version {} to {}",
+            from, CONFIG_VERSION
+        );
+    }
+    if !resolved.unknown_keys.is_empty() {
+        println!("Unknown keys ignored: {}", resolved.unknown_keys.join(", "));
+    }
+    let overridden: Vec<String> = resolved
+        .sources
+        .iter()
+        .filter(|(_, source)| !matches!(source, ConfigSource::Default | ConfigSource::File))
+        .map(|(name, source)| format!("{} ({})", name, source.label()))
+        .collect();
+    if !overridden.is_empty() {
+        println!("Overrides: {}", overridden.join(", "));
+    }
+    config.validate()?;
+    if !PROVIDERS.iter().any(|p| config.provider_enabled(p)) {
+        return Err("No provider is fully configured".to_string());
+    }
+    for provider in PROVIDERS {
+        let name = provider_display_name(provider);
+        if config.provider_enabled(provider) {
+            println!(
+                "{}: http://{} -> {} ({} keys)",
+                name,
+                config.provider_bind_addr(provider),
+                config.provider_upstream(provider),
+                config.enabled_keys(provider).len()
+            );
+        } else {
+            println!("{}: not configured", name);
+        }
+    }
+    println!("Config is valid");
+    Ok(())
+}
+
+async fn keys_status(config: &ProxyConfig) -> Result<(), String> {
+    let client = Client::builder()
+        .timeout(LIVE_STATUS_TIMEOUT)
+        .build()
+        .map_err(|e| format!("Failed to build HTTP client: {}", e))?;
+
+    for provider in PROVIDERS {
+        let name = provider_display_name(provider);
+        if !config.provider_enabled(provider) {
+            println!("{}: not configured\n", name);
+            continue;
+        }
+        let (statuses, live) = match fetch_live_statuses(&client, config, provider).await {
+            Some(statuses) => (statuses, true),
+            None => (idle_key_statuses(config.provider_keys(provider)), false),
+        };
+        println!("{}: {}", name, if live { "running" } else { "not running" });
+        for status in &statuses {
+            println!("  {}", format_key_status(status, live));
+        }
+        println!();
+    }
+    Ok(())
+}
+
+async fn fetch_live_statuses(
+    client: &Client,
+    config: &ProxyConfig,
+    provider: &str,
+) -> Option<Vec<KeyStatus>> {
+    let url = format!("http://{}/_admin/keys", config.provider_bind_addr(provider));
+    let response = client
+        .get(url)
+        .bearer_auth(&config.proxy_token)
+        .send()
+        .await
+        .ok()?
+        .error_for_status()
+        .ok()?;
+    response.json().await.ok()
+}
+
+fn format_key_status(status: &KeyStatus, live: bool) -> String {
+    let state = if status.is_dead {
+        "dead".to_string()
+    } else if status.is_expired {
+        "expired".to_string()
+    } else if status.is_exhausted {
+        format!(
+            "exhausted ({}d)",
+            status.days_until_reset.unwrap_or_default()
+        )
+    } else if status.is_cooling_down {
+        format!("cooling down ({}s)", status.cooldown_remaining_secs)
+    } else if live {
+        "active".to_string()
+    } else {
+        "idle".to_string()
+    };
+    let mut schedule = Vec::new();
+    if !status.tier.is_empty() {
+        schedule.push(format!("tier {}", status.tier));
+    }
+    if !status.pool.is_empty() {
+        schedule.push(format!("pool {}", status.pool));
+    }
+    if let Some(limit) = status.max_concurrency {
+        schedule.push(format!("in flight {}/{}", status.in_flight, limit));
+    } else if status.in_flight > 0 {
+        schedule.push(format!("in flight {}", status.in_flight));
+    }
+    if let Some(remaining) = status.rate_remaining {
+        schedule.push(format!("{} left this minute", remaining));
+    }
+    if let Some(days) = status.days_until_reset {
+        schedule.push(format!("resets in {}d", days));
+    }
+    if let (Some(days), false) = (status.days_until_expiry, status.is_expired) {
+        schedule.push(format!("expires in {}d", days));
+    }
+    format!(
+        "#{:<3} {:<16} {:<20} {} failures{}",
+        status.index + 1,
+        status.key_preview,
+        state,
+        status.fail_count,
+        if schedule.is_empty() {
+            String::new()
+        } else {
+            format!(", {}", schedule.join(", "))
+        }
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(raw: &[&str]) -> Vec<String> {
+        raw.iter().map(|a| a.to_string()).collect()
+    }
+
+    #[test]
+    fn parses_commands_and_options() {
+        let options = parse_args(args(&["--config", "/etc/bp/config.json", "keys", "status"]))
+            .expect("valid args");
+        assert_eq!(options.command, Command::KeysStatus);
+        assert_eq!(options.data_dir(), PathBuf::from("/etc/bp"));
+
+        let options = parse_args(args(&["mcp-config", "Tavily", "-d", "/data"])).unwrap();
+        assert_eq!(options.command, Command::McpConfig("tavily".to_string()));
+        assert_eq!(options.data_dir(), PathBuf::from("/data"));
+
+        assert_eq!(parse_args(args(&[])).unwrap().command, Command::Help);
+        assert_eq!(
+            parse_args(args(&["keys", "import", "a.txt", "b.csv"]))
+                .unwrap()
+                .command,
+            Command::KeysImport(vec![PathBuf::from("a.txt"), PathBuf::from("b.csv")])
+        );
+        assert_eq!(
+            parse_args(args(&["keys", "check", "Tavily", "--remove-invalid"]))
+                .unwrap()
+                .command,
+            Command::KeysCheck {
+                provider: Some("tavily".to_string()),
+                remove_invalid: true
+            }
+        );
+    }
+
+    #[test]
+    fn rejects_unknown_input() {
+        assert!(parse_args(args(&["keys"])).is_err());
+        assert!(parse_args(args(&["serve", "--remove-invalid"])).is_err());
+        assert!(parse_args(args(&["serve", "--verbose"])).is_err());
+        assert!(parse_args(args(&["serve", "--config"])).is_err());
+    }
+}
@@ -0,0 +1,651 @@
+//! Versioned on-disk format of `proxy-config.json`. Files are upgraded through
+//! the migration chain when loaded. Loading never writes; at startup
+//! `upgrade_config_file` stores the upgraded file, keeping the original as a
+//! backup next to the config.
+//!
+//! Every write goes through a temporary file and a rename, and each save first
+//! copies the previous valid file into `config-backups/`, so a crash mid-write
+//! never leaves a truncated config behind and a bad save can be rolled back.
+//!
+//! With `secretsEncryption` on, the secret fields are sealed on write and
+//! opened on load (see `secrets`); backups and recovery work on the sealed
+//! file as stored.
+
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::Serialize;
+use serde_json::{Map, Value};
+use tracing::{info, warn};
+
+use crate::secrets::{
+    clear_fields, new_cipher, open_cipher, open_fields, seal_fields, SecretsHeader,
+};
+use crate::ProxyConfig;
+
+pub(crate) const CONFIG_VERSION: u64 = 2;
+const VERSION_KEY: &str = "version";
+const SECRETS_KEY: &str = "secrets";
+const BACKUP_DIR_NAME: &str = "config-backups";
+const BACKUP_LIMIT: usize = 10;
+
+/// `MIGRATIONS[n]` upgrades the fields of a version `n` file to version `n + 1`.
+const MIGRATIONS: [fn(&mut Map<String, Value>); CONFIG_VERSION as usize] = [v0_to_v1, v1_to_v2];
+
+/// Files written before the version field existed. Every field they can hold
+/// is still read under the same name, so only the version stamp is new.
+fn v0_to_v1(_fields: &mut Map<String, Value>) {}
+
+/// Key lists became lists of key records; each plain key string becomes a
+/// record holding just its value (sealed values stay sealed).
+fn v1_to_v2(fields: &mut Map<String, Value>) {
+    for field in ["firecrawlApiKeys", "tavilyApiKeys"] {
+        if let Some(Value::Array(items)) = fields.get_mut(field) {
+            for item in items.iter_mut() {
+                if let Value::String(key) = item {
+                    *item = serde_json::json!({ "value": key });
+                }
+            }
+        }
+    }
+}
+
+/// A config file upgraded to `CONFIG_VERSION`.
+#[derive(Debug)]
+pub(crate) struct ConfigFile {
+    pub(crate) fields: Map<String, Value>,
+    /// Keys no config field reads; they are dropped on the next save.
+    pub(crate) unknown_keys: Vec<String>,
+    /// Version the file had on disk when it needed an upgrade.
+    pub(crate) migrated_from: Option<u64>,
+    /// How the secret fields are sealed; `None` for a plaintext file.
+    pub(crate) secrets: Option<SecretsHeader>,
+    /// The secrets need a passphrase that has not been provided, so the
+    /// secret fields were left empty.
+    pub(crate) locked: bool,
+}
+
+impl ConfigFile {
+    pub(crate) fn config(&self) -> Result<ProxyConfig, String> {
+        let config: ProxyConfig = serde_json::from_value(Value::Object(self.fields.clone()))
+            .map_err(|e| format!("Failed to parse config: {}", e))?;
+        Ok(config.normalized())
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ConfigBackup {
+    pub(crate) name: String,
+    pub(crate) created_at_ms: u64,
+    /// Whether the backup parses as a config this build can load.
+    pub(crate) valid: bool,
+}
+
+/// Startup replaced a config that failed to load with its newest valid backup.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ConfigRecovery {
+    pub(crate) error: String,
+    pub(crate) backup: String,
+    /// Where the unreadable file was moved, for manual inspection.
+    pub(crate) corrupt_copy: String,
+}
+
+/// Reads the config at `path`, upgraded to the current format in memory, and
+/// decrypts its secrets. The file itself is left as it is.
+pub(crate) fn load_config_file(path: &Path) -> Result<ConfigFile, String> {
+    let mut file = read_upgraded(path)?;
+    if let Some(header) = &file.secrets {
+        match open_cipher(header, path)? {
+            Some(cipher) => open_fields(&mut file.fields, &cipher)?,
+            None => {
+                clear_fields(&mut file.fields);
+                file.locked = true;
+            }
+        }
+    }
+    Ok(file)
+}
+
+/// Rewrites the config at `path` in the current format when an older version
+/// wrote it, after saving a backup of the original. Returns the version it
+/// was upgraded from. Run once at startup, before the config is loaded.
+pub(crate) fn upgrade_config_file(path: &Path) -> Result<Option<u64>, String> {
+    if !path.exists() {
+        return Ok(None);
+    }
+    let file = read_upgraded(path)?;
+    if let Some(from) = file.migrated_from {
+        fs::copy(path, backup_path(path, from))
+            .map_err(|e| format!("Failed to back up config: {}", e))?;
+        write_atomic(path, &render(&file.fields, file.secrets.as_ref())?)
+            .map_err(|e| format!("Failed to write migrated config: {}", e))?;
+    }
+    if file.secrets.is_none() {
+        // A plaintext file that asks for encryption, e.g. edited by hand:
+        // seal it now when the key is at hand, otherwise on the next save.
+        let config = file.config()?;
+        if config.secrets_encryption != "off" {
+            let _ = write_config_file(path, &config);
+        }
+    }
+    Ok(file.migrated_from)
+}
+
+/// Logs what loading the file changed or ignored, once tracing is set up.
+pub(crate) fn log_load_notes(unknown_keys: &[String], migrated_from: Option<u64>) {
+    if let Some(from) = migrated_from {
+        info!(
+            from,
+            to = CONFIG_VERSION,
+            "Config file migrated; original kept as backup"
+        );
+    }
+    if !unknown_keys.is_empty() {
+        warn!(keys = %unknown_keys.join(","), "Config file has unknown keys; they are ignored");
+    }
+}
+
+/// Writes `config` to `path` stamped with the current version, backing up the
+/// file it replaces. Secrets are sealed when `secrets_encryption` asks for it,
+/// and plaintext copies left in the backups are removed.
+pub(crate) fn write_config_file(path: &Path, config: &ProxyConfig) -> Result<(), String> {
+    let mut fields = match serde_json::to_value(config) {
+        Ok(Value::Object(fields)) => fields,
+        Ok(_) => return Err("Config is not an object".to_string()),
+        Err(e) => return Err(format!("Failed to serialize config: {}", e)),
+    };
+    let header = if config.secrets_encryption == "off" {
+        None
+    } else {
+        let (header, cipher) = new_cipher(&config.secrets_encryption, path)?;
+        seal_fields(&mut fields, &cipher)?;
+        Some(header)
+    };
+    let text = render(&fields, header.as_ref())?;
+    backup_current(path)?;
+    write_atomic(path, &text).map_err(|e| format!("Failed to write config: {}", e))?;
+    if header.is_some() {
+        remove_plaintext_copies(path)?;
+    }
+    Ok(())
+}
+
+/// Backups of the config at `path`, newest first.
+pub(crate) fn list_config_backups(path: &Path) -> Result<Vec<ConfigBackup>, String> {
+    let dir = backup_dir(path);
+    let entries = match fs::read_dir(&dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(format!("Failed to read config backups: {}", e)),
+    };
+    let prefix = backup_prefix(path);
+    let mut backups: Vec<ConfigBackup> = entries
+        .filter_map(Result::ok)
+        .filter_map(|entry| {
+            let name = entry.file_name().to_string_lossy().to_string();
+            let (created_at_ms, _) = backup_stamp(&name, &prefix)?;
+            let valid = read_upgraded(&entry.path()).is_ok();
+            Some(ConfigBackup {
+                name,
+                created_at_ms,
+                valid,
+            })
+        })
+        .collect();
+    backups.sort_by_key(|backup| std::cmp::Reverse(backup_stamp(&backup.name, &prefix)));
+    Ok(backups)
+}
+
+/// Replaces the config at `path` with the backup called `name`. The current
+/// file is backed up first, so a restore can itself be undone.
+pub(crate) fn restore_config_backup(path: &Path, name: &str) -> Result<(), String> {
+    if !list_config_backups(path)?.iter().any(|b| b.name == name) {
+        return Err(format!("Config backup '{}' not found", name));
+    }
+    let backup = read_upgraded(&backup_dir(path).join(name))?;
+    let text = render(&backup.fields, backup.secrets.as_ref())?;
+    backup_current(path)?;
+    write_atomic(path, &text).map_err(|e| format!("Failed to restore config: {}", e))
+}
+
+/// Swaps a config that does not parse as a config this build can load for
+/// its newest valid backup. Returns `None` when the file is missing or
+/// parses, and the parse error when no backup helps. Other load failures,
+/// such as secrets that do not decrypt, are left for the load to report.
+pub(crate) fn recover_corrupt_config(path: &Path) -> Result<Option<ConfigRecovery>, String> {
+    let text = match fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(format!("Failed to read config: {}", e)),
+    };
+    let error = match parse_upgraded(&text) {
+        Ok(_) => return Ok(None),
+        Err(error) => error,
+    };
+    let Some(backup) = list_config_backups(path)?.into_iter().find(|b| b.valid) else {
+        return Err(error);
+    };
+    let text = fs::read_to_string(backup_dir(path).join(&backup.name))
+        .map_err(|e| format!("Failed to read config backup: {}", e))?;
+
+    let mut corrupt_name = path.file_name().unwrap_or_default().to_os_string();
+    corrupt_name.push(format!(".corrupt-{}", unix_millis()));
+    let corrupt_copy = path.with_file_name(corrupt_name);
+    fs::rename(path, &corrupt_copy)
+        .map_err(|e| format!("Failed to move corrupt config aside: {}", e))?;
+    write_atomic(path, &text).map_err(|e| format!("Failed to restore config: {}", e))?;
+
+    Ok(Some(ConfigRecovery {
+        error,
+        backup: backup.name,
+        corrupt_copy: corrupt_copy.to_string_lossy().to_string(),
+    }))
+}
+
+/// Copies the current file into the backup directory if it still loads, then
+/// drops the oldest backups beyond `BACKUP_LIMIT`.
+fn backup_current(path: &Path) -> Result<(), String> {
+    if read_upgraded(path).is_err() {
+        return Ok(());
+    }
+    let dir = backup_dir(path);
+    fs::create_dir_all(&dir).map_err(|e| format!("Failed to create backup dir: {}", e))?;
+    let text = fs::read_to_string(path).map_err(|e| format!("Failed to read config: {}", e))?;
+    write_atomic(&unique_backup_path(path), &text)
+        .map_err(|e| format!("Failed to back up config: {}", e))?;
+
+    for stale in list_config_backups(path)?.iter().skip(BACKUP_LIMIT) {
+        let _ = fs::remove_file(dir.join(&stale.name));
+    }
+    Ok(())
+}
+
+/// Writes `text` to a sibling temp file, syncs it and renames it over `path`.
+pub(crate) fn write_atomic(path: &Path, text: &str) -> std::io::Result<()> {
+    let mut tmp_name = path.file_name().unwrap_or_default().to_os_string();
+    tmp_name.push(".tmp");
+    let tmp = path.with_file_name(tmp_name);
+    let mut file = File::create(&tmp)?;
+    file.write_all(text.as_bytes())?;
+    file.sync_all()?;
+    fs::rename(&tmp, path)
+}
+
+fn read_upgraded(path: &Path) -> Result<ConfigFile, String> {
+    let text = fs::read_to_string(path).map_err(|e| format!("Failed to read config: {}", e))?;
+    parse_upgraded(&text)
+}
+
+fn parse_upgraded(text: &str) -> Result<ConfigFile, String> {
+    let raw: Value =
+        serde_json::from_str(text).map_err(|e| format!("Failed to parse config: {}", e))?;
+    let file = upgrade(raw)?;
+    file.config()?;
+    Ok(file)
+}
+
+fn backup_dir(path: &Path) -> PathBuf {
+    path.with_file_name(BACKUP_DIR_NAME)
+}
+
+/// `proxy-config.json` -> `proxy-config-`
+fn backup_prefix(path: &Path) -> String {
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+    format!("{}-", stem)
+}
+
+/// `config-backups/proxy-config-<millis>.json`, with a `-<n>` suffix after
+/// the timestamp when several backups are taken within one millisecond.
+fn unique_backup_path(path: &Path) -> PathBuf {
+    let dir = backup_dir(path);
+    let prefix = backup_prefix(path);
+    let ts = unix_millis();
+    let mut suffix = 0;
+    loop {
+        let name = if suffix == 0 {
+            format!("{}{}.json", prefix, ts)
+        } else {
+            format!("{}{}-{}.json", prefix, ts, suffix)
+        };
+        let candidate = dir.join(name);
+        if !candidate.exists() {
+            return candidate;
+        }
+        suffix += 1;
+    }
+}
+
+/// `proxy-config-<millis>.json` or `proxy-config-<millis>-<n>.json` ->
+/// `(millis, n)`, the order backups were taken in.
+fn backup_stamp(name: &str, prefix: &str) -> Option<(u64, u64)> {
+    let stamp = name.strip_prefix(prefix)?.strip_suffix(".json")?;
+    match stamp.split_once('-') {
+        Some((ms, n)) => Some((ms.parse().ok()?, n.parse().ok()?)),
+        None => Some((stamp.parse().ok()?, 0)),
+    }
+}
+
+fn unix_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |v| v.as_millis() as u64)
+}
+
+/// The file contents `write_config_file` would produce for a plaintext config.
+pub(crate) fn render_plain_config(config: &ProxyConfig) -> Result<String, String> {
+    match serde_json::to_value(config) {
+        Ok(Value::Object(fields)) => render(&fields, None),
+        Ok(_) => Err("Config is not an object".to_string()),
+        Err(e) => Err(format!("Failed to serialize config: {}", e)),
+    }
+}
+
+/// Drops backups written before encryption was enabled, along with the
+/// pre-migration copies, so no plaintext secret outlives the switch.
+fn remove_plaintext_copies(path: &Path) -> Result<(), String> {
+    let dir = backup_dir(path);
+    for backup in list_config_backups(path)? {
+        let backup_path = dir.join(&backup.name);
+        if read_upgraded(&backup_path).map_or(true, |file| file.secrets.is_none()) {
+            let _ = fs::remove_file(backup_path);
+        }
+    }
+    for version in 0..CONFIG_VERSION {
+        let _ = fs::remove_file(backup_path(path, version));
+    }
+    Ok(())
+}
+
+fn render(fields: &Map<String, Value>, secrets: Option<&SecretsHeader>) -> Result<String, String> {
+    let mut stamped = fields.clone();
+    stamped.insert(VERSION_KEY.to_string(), Value::from(CONFIG_VERSION));
+    if let Some(header) = secrets {
+        let header = serde_json::to_value(header)
+            .map_err(|e| format!("Failed to serialize config: {}", e))?;
+        stamped.insert(SECRETS_KEY.to_string(), header);
+    }
+    serde_json::to_string_pretty(&stamped).map_err(|e| format!("Failed to serialize config: {}", e))
+}
+
+/// `proxy-config.json` -> `proxy-config.json.v0.bak`
+fn backup_path(path: &Path, version: u64) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(format!(".v{}.bak", version));
+    path.with_file_name(name)
+}
+
+fn upgrade(raw: Value) -> Result<ConfigFile, String> {
+    let Value::Object(mut fields) = raw else {
+        return Err("Failed to parse config: expected a JSON object".to_string());
+    };
+    let version = match fields.remove(VERSION_KEY) {
+        None => 0,
+        Some(value) => value
+            .as_u64()
+            .ok_or_else(|| "Config version must be a non-negative integer".to_string())?,
+    };
+    if version > CONFIG_VERSION {
+        return Err(format!(
+            "Config version {} is newer than this build supports ({})",
+            version, CONFIG_VERSION
+        ));
+    }
+    let secrets = match fields.remove(SECRETS_KEY) {
+        None => None,
+        Some(header) => Some(
+            serde_json::from_value(header)
+                .map_err(|e| format!("Failed to parse config secrets header: {}", e))?,
+        ),
+    };
+    for migrate in &MIGRATIONS[version as usize..] {
+        migrate(&mut fields);
+    }
+
+    let known = match serde_json::to_value(ProxyConfig::default()) {
+        Ok(Value::Object(known)) => known,
+        _ => Map::new(),
+    };
+    let unknown_keys = fields
+        .keys()
+        .filter(|key| !known.contains_key(*key))
+        .cloned()
+        .collect();
+
+    Ok(ConfigFile {
+        fields,
+        unknown_keys,
+        migrated_from: (version < CONFIG_VERSION).then_some(version),
+        secrets,
+        locked: false,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::keys::key_values;
+    use uuid::Uuid;
+
+    const FIXTURES: [(&str, &str); 4] = [
+        (
+            "v0-baseline",
+            include_str!("../tests/fixtures/config/v0-baseline.json"),
+        ),
+        (
+            "v0-unversioned",
+            include_str!("../tests/fixtures/config/v0-unversioned.json"),
+        ),
+        ("v1", include_str!("../tests/fixtures/config/v1.json")),
+        ("v2", include_str!("../tests/fixtures/config/v2.json")),
+    ];
+
+    fn upgrade_fixture(name: &str) -> ConfigFile {
+        let (_, text) = FIXTURES.iter().find(|(n, _)| *n == name).unwrap();
+        upgrade(serde_json::from_str(text).unwrap()).expect("fixture upgrades")
+    }
+
+    #[test]
+    fn every_fixture_upgrades_to_the_current_version() {
+        for (name, _) in FIXTURES {
+            let file = upgrade_fixture(name);
+            assert!(file.unknown_keys.is_empty(), "{} has unknown keys", name);
+            let config = file.config().expect("fixture parses");
+            assert!(config.validate_common().is_ok(), "{} is invalid", name);
+        }
+
+        let baseline = upgrade_fixture("v0-baseline");
+        assert_eq!(baseline.migrated_from, Some(0));
+        let config = baseline.config().unwrap();
+        assert_eq!(config.proxy_token, "baseline-token");
+        assert_eq!(
+            key_values(&config.firecrawl_api_keys),
+            vec!["fc-key-1", "fc-key-2"]
+        );
+        assert_eq!(config.key_cooldown_seconds, 90);
+        assert_eq!(config.drain_timeout_seconds, 30);
+
+        let unversioned = upgrade_fixture("v0-unversioned").config().unwrap();
+        assert_eq!(unversioned.port, 9787);
+        assert_eq!(unversioned.drain_timeout_seconds, 45);
+        assert!(unversioned.auto_start_proxy);
+
+        let v1 = upgrade_fixture("v1");
+        assert_eq!(v1.migrated_from, Some(1));
+        assert_eq!(v1.fields["tavilyApiKeys"][0]["value"], "tvly-key-1");
+
+        let v2 = upgrade_fixture("v2");
+        assert_eq!(v2.migrated_from, None);
+        let keys = v2.config().unwrap().tavily_api_keys;
+        assert_eq!(key_values(&keys), vec!["tvly-key-1", "tvly-key-2"]);
+        assert_eq!(keys[1].label, "team");
+        assert!(!keys[1].enabled);
+    }
+
+    #[test]
+    fn rejects_newer_versions_and_reports_unknown_keys() {
+        let err = upgrade(serde_json::json!({ "version": CONFIG_VERSION + 1 })).unwrap_err();
+        assert!(err.contains("newer than this build supports"));
+        assert!(upgrade(serde_json::json!({ "version": "one" })).is_err());
+
+        let file = upgrade(serde_json::json!({
+            "version": CONFIG_VERSION,
+            "proxyToken": "t",
+            "tavilyKeys": ["tvly-typo"],
+        }))
+        .unwrap();
+        assert_eq!(file.unknown_keys, vec!["tavilyKeys"]);
+    }
+
+    #[test]
+    fn migrating_a_file_keeps_a_backup_of_the_original() {
+        let dir = std::env::temp_dir().join(format!("balance-proxy-config-{}", Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("proxy-config.json");
+        let original = FIXTURES[0].1;
+        fs::write(&path, original).unwrap();
+
+        // Loading upgrades in memory only.
+        assert_eq!(load_config_file(&path).unwrap().migrated_from, Some(0));
+        assert_eq!(fs::read_to_string(&path).unwrap(), original);
+
+        assert_eq!(upgrade_config_file(&path), Ok(Some(0)));
+        assert_eq!(
+            fs::read_to_string(dir.join("proxy-config.json.v0.bak")).unwrap(),
+            original
+        );
+        let rewritten: Value = serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(rewritten[VERSION_KEY], CONFIG_VERSION);
+
+        // The upgraded file loads without another migration.
+        assert_eq!(load_config_file(&path).unwrap().migrated_from, None);
+        assert_eq!(upgrade_config_file(&path), Ok(None));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn saves_keep_backups_that_restore_and_recover_a_corrupt_file() {
+        let dir = std::env::temp_dir().join(format!("balance-proxy-config-{}", Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("proxy-config.json");
+        let with_port = |port| ProxyConfig {
+            port,
+            ..ProxyConfig::default()
+        };
+
+        write_config_file(&path, &with_port(9001)).unwrap();
+        assert!(list_config_backups(&path).unwrap().is_empty());
+        write_config_file(&path, &with_port(9002)).unwrap();
+        let backups = list_config_backups(&path).unwrap();
+        assert_eq!(backups.len(), 1);
+        assert!(backups[0].valid);
+
+        restore_config_backup(&path, &backups[0].name).unwrap();
+        assert_eq!(
+            load_config_file(&path).unwrap().config().unwrap().port,
+            9001
+        );
+        assert!(restore_config_backup(&path, "../proxy-config.json").is_err());
+
+        // A truncated write is swapped for the newest valid backup, which
+        // holds the 9002 config the restore replaced.
+        fs::write(&path, "{\"port\": 90").unwrap();
+        let recovery = recover_corrupt_config(&path).unwrap().expect("recovered");
+        assert!(recovery.error.contains("Failed to parse config"));
+        assert_eq!(
+            fs::read_to_string(&recovery.corrupt_copy).unwrap(),
+            "{\"port\": 90"
+        );
+        assert_eq!(
+            load_config_file(&path).unwrap().config().unwrap().port,
+            9002
+        );
+        assert!(recover_corrupt_config(&path).unwrap().is_none());
+
+        // Backups taken within the same millisecond keep distinct names.
+        for port in 9003..9006 {
+            write_config_file(&path, &with_port(port)).unwrap();
+        }
+        let ports: Vec<u16> = list_config_backups(&path)
+            .unwrap()
+            .iter()
+            .take(3)
+            .map(|b| {
+                let backup = load_config_file(&dir.join(BACKUP_DIR_NAME).join(&b.name));
+                backup.unwrap().config().unwrap().port
+            })
+            .collect();
+        assert_eq!(ports, vec![9004, 9003, 9002]);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn configs_that_parse_but_fail_to_decrypt_are_not_recovered() {
+        let dir = std::env::temp_dir().join(format!("balance-proxy-config-{}", Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("proxy-config.json");
+        let mut config = ProxyConfig {
+            tavily_api_keys: vec!["tvly-secret-1".into()],
+            secrets_encryption: "keyfile".to_string(),
+            ..ProxyConfig::default()
+        };
+        write_config_file(&path, &config).unwrap();
+        config.port = 9001;
+        write_config_file(&path, &config).unwrap();
+        fs::remove_file(dir.join("secret.key")).unwrap();
+
+        assert!(load_config_file(&path).is_err());
+        assert!(recover_corrupt_config(&path).unwrap().is_none());
+        assert_eq!(read_upgraded(&path).unwrap().config().unwrap().port, 9001);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn unreadable_config_files_are_not_replaced_by_defaults() {
+        let dir = std::env::temp_dir().join(format!("balance-proxy-config-{}", Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("proxy-config.json");
+        assert!(crate::read_config_file_or_default(&path).is_ok());
+        fs::write(&path, "{ not json").unwrap();
+        assert!(crate::read_config_file_or_default(&path).is_err());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn enabling_key_file_encryption_seals_the_file_and_drops_plaintext_backups() {
+        let dir = std::env::temp_dir().join(format!("balance-proxy-config-{}", Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("proxy-config.json");
+        let mut config = ProxyConfig {
+            tavily_api_keys: vec!["tvly-secret-1".into()],
+            ..ProxyConfig::default()
+        };
+        write_config_file(&path, &config).unwrap();
+        std::thread::sleep(std::time::Duration::from_millis(2));
+
+        config.secrets_encryption = "keyfile".to_string();
+        write_config_file(&path, &config).unwrap();
+        assert!(!fs::read_to_string(&path).unwrap().contains("tvly-secret-1"));
+        assert!(list_config_backups(&path).unwrap().is_empty());
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = fs::metadata(dir.join("secret.key"))
+                .unwrap()
+                .permissions()
+                .mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+
+        let file = load_config_file(&path).unwrap();
+        assert_eq!(file.secrets, Some(SecretsHeader::KeyFile));
+        assert_eq!(
+            key_values(&file.config().unwrap().tavily_api_keys),
+            vec!["tvly-secret-1"]
+        );
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
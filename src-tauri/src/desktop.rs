@@ -0,0 +1,1105 @@
+//! Tauri desktop shell: window, tray and the commands the UI invokes. The
+//! proxy itself lives in the crate root and runs without any of this.
+
+use std::fs;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use axum::http::{HeaderMap, HeaderValue, Method};
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use tauri::{Emitter, Manager, WindowEvent};
+use tauri_plugin_autostart::MacosLauncher;
+use tauri_plugin_autostart::ManagerExt as _;
+use tauri_plugin_notification::NotificationExt;
+use tokio::sync::{broadcast, Mutex, RwLock};
+use tracing::{info, warn};
+use tracing_subscriber::filter::LevelFilter;
+
+use crate::capture::{
+    capture_body, redact_headers, CaptureEntry, CaptureSettings, CaptureStatus, CaptureSummary,
+    CapturedBody, CapturedHeader, DebugCapture,
+};
+use crate::config_schema::{
+    self, log_load_notes, recover_corrupt_config, write_config_file, ConfigBackup, ConfigRecovery,
+};
+use crate::env_config::{
+    copy_fields, import_dotenv, process_env_vars, resolve_config, to_dotenv, ConfigSource,
+    ConfigSources, EnvImport, ResolvedConfig,
+};
+use crate::events::{EventBus, ProxyEvent};
+use crate::history::{HistoryPage, HistoryQuery, HistoryStats};
+use crate::key_import::{self, KeyImportReport};
+use crate::key_probe::{self, KeyProbeReport, DEFAULT_PROBE_CONCURRENCY};
+use crate::key_watch::{self, KEY_WATCH_STATE_FILE};
+use crate::keys::{self, stamp_new_keys, KeyDetails, KeyRecord, KeyView};
+use crate::log_files::export_log_range;
+use crate::logging::{init_tracing, parse_log_level, LogBuffer};
+use crate::notifications::{AlertPolicy, AlertSettings};
+use crate::profiles::{self, ProfileSummary};
+use crate::secrets::{mask_secrets, set_session_passphrase, unmask_secrets};
+use crate::tray;
+use crate::{
+    build_key_status_snapshot_inner, compose_proxy_status, current_proxy_status, init_config_file,
+    now_ts, open_history_store, open_log_file, open_tracer_provider, parse_provider,
+    read_config_file_or_default, render_mcp_config, restart_single_provider, start_all_providers,
+    start_single_provider, stop_all_providers, stop_provider, warn_unavailable, ActiveKeyManagers,
+    AppState, KeyStatus, KeyStatusSnapshot, ProxyConfig, ProxyRuntime, ProxyStatus,
+    CAPTURE_DIR_NAME, LOG_DIR_NAME, PROVIDERS, REQUEST_HEADER_BLOCKLIST,
+};
+
+/// Set when startup had to replace an unreadable config with a backup.
+struct StartupRecovery(Option<ConfigRecovery>);
+
+/// Whether the config's secrets are still waiting for their passphrase.
+struct SecretsLock(AtomicBool);
+
+pub(crate) fn app_data_dir(app: &tauri::AppHandle) -> Result<PathBuf, String> {
+    let app_data_dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("Failed to resolve app data dir: {}", e))?;
+    fs::create_dir_all(&app_data_dir)
+        .map_err(|e| format!("Failed to create app data dir: {}", e))?;
+    Ok(app_data_dir)
+}
+
+pub(crate) fn config_path(app: &tauri::AppHandle) -> Result<PathBuf, String> {
+    Ok(app_data_dir(app)?.join("proxy-config.json"))
+}
+
+fn log_dir(app: &tauri::AppHandle) -> Result<PathBuf, String> {
+    Ok(app_data_dir(app)?.join(LOG_DIR_NAME))
+}
+
+#[tauri::command]
+async fn load_proxy_config(state: tauri::State<'_, AppState>) -> Result<ProxyConfig, String> {
+    Ok(mask_secrets(&*state.config.read().await))
+}
+
+/// The config with its secrets in full, for an explicit "show secrets" action.
+#[tauri::command]
+async fn reveal_secrets(state: tauri::State<'_, AppState>) -> Result<ProxyConfig, String> {
+    info!("Config secrets revealed in the UI");
+    Ok(state.config.read().await.clone())
+}
+
+#[tauri::command]
+async fn get_secrets_locked(lock: tauri::State<'_, SecretsLock>) -> Result<bool, String> {
+    Ok(lock.0.load(Ordering::SeqCst))
+}
+
+/// Decrypts a passphrase-protected config and starts the proxy if it is set
+/// to auto-start, which startup skipped while the keys were unavailable.
+#[tauri::command]
+async fn unlock_secrets(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
+    passphrase: String,
+) -> Result<ProxyConfig, String> {
+    set_session_passphrase(Some(passphrase));
+    let config = match reload_config_file(&app, state.inner()).await {
+        Ok(config) => config,
+        Err(err) => {
+            set_session_passphrase(None);
+            return Err(err);
+        }
+    };
+    info!("Config secrets unlocked");
+    if config.auto_start_proxy {
+        let state = state.inner().clone();
+        tauri::async_runtime::spawn(async move {
+            if let Err(err) = start_all_providers(&state).await {
+                warn!(error = %err, "Failed to auto-start proxy after unlock");
+            }
+        });
+    }
+    Ok(mask_secrets(&config))
+}
+
+/// Re-reads the config file with the current environment overrides and makes
+/// it the running config, as a save would.
+async fn reload_config_file(
+    app: &tauri::AppHandle,
+    state: &AppState,
+) -> Result<ProxyConfig, String> {
+    let resolved = resolve_config(
+        Some(&config_path(app)?),
+        &[(ConfigSource::Env, &process_env_vars())],
+    )?;
+    state
+        .log_handle
+        .set_level(parse_log_level(&resolved.config.log_level)?)?;
+    *state.config.write().await = resolved.config.clone();
+    *state.config_sources.write().await = resolved.sources;
+    app.state::<SecretsLock>()
+        .0
+        .store(resolved.locked, Ordering::SeqCst);
+    Ok(resolved.config)
+}
+
+#[tauri::command]
+async fn list_config_backups(app: tauri::AppHandle) -> Result<Vec<ConfigBackup>, String> {
+    config_schema::list_config_backups(&config_path(&app)?)
+}
+
+/// Rolls the config file back to a backup and applies it like a save, keeping
+/// environment overrides in effect.
+#[tauri::command]
+async fn restore_config_backup(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
+    name: String,
+) -> Result<ProxyConfig, String> {
+    config_schema::restore_config_backup(&config_path(&app)?, &name)?;
+    let config = reload_config_file(&app, state.inner()).await?;
+    info!(backup = %name, "Config restored from backup");
+    Ok(mask_secrets(&config))
+}
+
+#[tauri::command]
+async fn get_config_recovery(
+    recovery: tauri::State<'_, StartupRecovery>,
+) -> Result<Option<ConfigRecovery>, String> {
+    Ok(recovery.0.clone())
+}
+
+#[tauri::command]
+async fn get_config_sources(state: tauri::State<'_, AppState>) -> Result<ConfigSources, String> {
+    Ok(state.config_sources.read().await.clone())
+}
+
+/// Applies a `.env` file's contents onto `base` (the unsaved form values)
+/// without saving, so the user can review the result first.
+#[tauri::command]
+async fn import_env_config(base: ProxyConfig, text: String) -> Result<EnvImport, String> {
+    let imported = import_dotenv(&base, &text)?;
+    info!(
+        applied = imported.applied.len(),
+        ignored = imported.ignored.len(),
+        ".env imported"
+    );
+    Ok(imported)
+}
+
+/// Writes the effective config as a `.env` file and returns its path.
+#[tauri::command]
+async fn export_env_config(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
+) -> Result<String, String> {
+    let text = to_dotenv(&*state.config.read().await)?;
+    let export_dir = app_data_dir(&app)?.join("exports");
+    fs::create_dir_all(&export_dir).map_err(|e| format!("Failed to create export dir: {}", e))?;
+    let destination = export_dir.join(format!("balance-proxy-{}.env", now_ts()));
+    fs::write(&destination, text).map_err(|e| format!("Failed to write .env export: {}", e))?;
+
+    info!(path = %destination.to_string_lossy(), "Config exported");
+    Ok(destination.to_string_lossy().to_string())
+}
+
+#[tauri::command]
+async fn save_proxy_config(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
+    lock: tauri::State<'_, SecretsLock>,
+    config: ProxyConfig,
+    passphrase: Option<String>,
+) -> Result<String, String> {
+    if lock.0.load(Ordering::SeqCst) {
+        return Err("Unlock the config with its passphrase before saving".to_string());
+    }
+    if passphrase.is_some() {
+        set_session_passphrase(passphrase);
+    }
+    let path = config_path(&app)?;
+    let mut sources = state.config_sources.write().await;
+    let env_names: Vec<&str> = sources
+        .iter()
+        .filter(|(_, source)| **source == ConfigSource::Env)
+        .map(|(name, _)| name.as_str())
+        .collect();
+
+    // Environment overrides stay in effect but are never written to the file,
+    // which keeps whatever it had for those fields.
+    // Secrets the UI left masked keep their stored values.
+    let current = state.config.read().await.clone();
+    let mut effective = copy_fields(
+        &unmask_secrets(config, &current).normalized(),
+        &current,
+        env_names.iter().copied(),
+    )?;
+    stamp_new_keys(
+        &mut effective.firecrawl_api_keys,
+        &current.firecrawl_api_keys,
+    );
+    stamp_new_keys(&mut effective.tavily_api_keys, &current.tavily_api_keys);
+    effective.validate()?;
+    let on_disk = read_config_file_or_default(&path)?;
+    let to_file = copy_fields(&effective, &on_disk, env_names.iter().copied())?;
+    // Keys deleted here must not come back from the key drop folder.
+    for provider in PROVIDERS {
+        keys::remember_removed_keys(
+            &path,
+            on_disk.provider_keys(provider),
+            to_file.provider_keys(provider),
+        )?;
+    }
+
+    write_config_file(&path, &to_file)?;
+
+    state
+        .log_handle
+        .set_level(parse_log_level(&effective.log_level)?)?;
+    *state.config.write().await = effective;
+    for source in sources.values_mut() {
+        if *source != ConfigSource::Env {
+            *source = ConfigSource::File;
+        }
+    }
+    drop(sources);
+    info!(path = %path.to_string_lossy(), "Config saved");
+
+    Ok(path.to_string_lossy().to_string())
+}
+
+#[tauri::command]
+async fn list_keys(
+    state: tauri::State<'_, AppState>,
+    provider: String,
+) -> Result<Vec<KeyView>, String> {
+    let provider = parse_provider(&provider)?;
+    Ok(keys::key_views(
+        state.config.read().await.provider_keys(provider),
+    ))
+}
+
+#[tauri::command]
+async fn add_key(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
+    lock: tauri::State<'_, SecretsLock>,
+    provider: String,
+    value: String,
+    details: KeyDetails,
+) -> Result<Vec<KeyView>, String> {
+    edit_keys(&app, &state, &lock, &provider, |keys| {
+        keys::add_key(keys, &value, details)
+    })
+    .await
+}
+
+#[tauri::command]
+async fn remove_key(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
+    lock: tauri::State<'_, SecretsLock>,
+    provider: String,
+    fingerprint: String,
+) -> Result<Vec<KeyView>, String> {
+    edit_keys(&app, &state, &lock, &provider, |keys| {
+        keys::remove_key(keys, &fingerprint)
+    })
+    .await
+}
+
+#[tauri::command]
+async fn set_key_enabled(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
+    lock: tauri::State<'_, SecretsLock>,
+    provider: String,
+    fingerprint: String,
+    enabled: bool,
+) -> Result<Vec<KeyView>, String> {
+    edit_keys(&app, &state, &lock, &provider, |keys| {
+        keys::set_key_enabled(keys, &fingerprint, enabled)
+    })
+    .await
+}
+
+#[tauri::command]
+async fn update_key(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
+    lock: tauri::State<'_, SecretsLock>,
+    provider: String,
+    fingerprint: String,
+    details: KeyDetails,
+) -> Result<Vec<KeyView>, String> {
+    edit_keys(&app, &state, &lock, &provider, |keys| {
+        keys::update_key(keys, &fingerprint, details)
+    })
+    .await
+}
+
+#[tauri::command]
+async fn move_key(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
+    lock: tauri::State<'_, SecretsLock>,
+    provider: String,
+    fingerprint: String,
+    position: usize,
+) -> Result<Vec<KeyView>, String> {
+    edit_keys(&app, &state, &lock, &provider, |keys| {
+        keys::move_key(keys, &fingerprint, position)
+    })
+    .await
+}
+
+#[derive(Deserialize)]
+struct KeyFile {
+    name: String,
+    text: String,
+}
+
+/// Imports keys from files the UI has read; `provider` applies to keys
+/// without a recognizable prefix.
+#[tauri::command]
+async fn import_keys(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
+    lock: tauri::State<'_, SecretsLock>,
+    files: Vec<KeyFile>,
+    provider: Option<String>,
+) -> Result<KeyImportReport, String> {
+    if lock.0.load(Ordering::SeqCst) {
+        return Err("Unlock the config with its passphrase before editing keys".to_string());
+    }
+    let provider = provider.as_deref().map(parse_provider).transpose()?;
+    let files: Vec<(String, String)> = files.into_iter().map(|f| (f.name, f.text)).collect();
+    let report = key_import::import_keys(&state, &config_path(&app)?, &files, provider).await;
+    info!(
+        added = report.added.len(),
+        skipped = report.skipped.len(),
+        invalid = report.invalid.len(),
+        "Keys imported"
+    );
+    tray::refresh_tray(&app).await;
+    Ok(report)
+}
+
+/// Test-calls each enabled key of `provider` and updates its live state;
+/// with `remove_invalid`, keys the upstream rejects leave the config.
+#[tauri::command]
+async fn validate_keys(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
+    lock: tauri::State<'_, SecretsLock>,
+    provider: String,
+    remove_invalid: bool,
+    concurrency: Option<usize>,
+) -> Result<KeyProbeReport, String> {
+    if lock.0.load(Ordering::SeqCst) {
+        return Err("Unlock the config with its passphrase before validating keys".to_string());
+    }
+    let provider = parse_provider(&provider)?;
+    let report = key_probe::probe_provider_keys(
+        &state,
+        &config_path(&app)?,
+        provider,
+        concurrency.unwrap_or(DEFAULT_PROBE_CONCURRENCY),
+        remove_invalid,
+    )
+    .await?;
+    tray::refresh_tray(&app).await;
+    Ok(report)
+}
+
+/// Shared by the key commands: edits one provider's keys in the config file
+/// and the running key manager, and returns the masked result.
+async fn edit_keys(
+    app: &tauri::AppHandle,
+    state: &AppState,
+    lock: &SecretsLock,
+    provider: &str,
+    edit: impl FnOnce(&mut Vec<KeyRecord>) -> Result<(), String>,
+) -> Result<Vec<KeyView>, String> {
+    if lock.0.load(Ordering::SeqCst) {
+        return Err("Unlock the config with its passphrase before editing keys".to_string());
+    }
+    let provider = parse_provider(provider)?;
+    let keys = keys::edit_provider_keys(state, &config_path(app)?, provider, edit).await?;
+    tray::refresh_tray(app).await;
+    Ok(keys::key_views(&keys))
+}
+
+#[tauri::command]
+async fn list_profiles(app: tauri::AppHandle) -> Result<Vec<ProfileSummary>, String> {
+    profiles::list_profiles(&config_path(&app)?)
+}
+
+#[tauri::command]
+async fn create_profile(app: tauri::AppHandle, name: String) -> Result<(), String> {
+    profiles::create_profile(&config_path(&app)?, &name)?;
+    info!(profile = %name, "Profile created");
+    tray::refresh_tray(&app).await;
+    Ok(())
+}
+
+#[tauri::command]
+async fn clone_profile(app: tauri::AppHandle, source: String, name: String) -> Result<(), String> {
+    profiles::clone_profile(&config_path(&app)?, &source, &name)?;
+    info!(source = %source, profile = %name, "Profile cloned");
+    tray::refresh_tray(&app).await;
+    Ok(())
+}
+
+#[tauri::command]
+async fn rename_profile(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
+    name: String,
+    new_name: String,
+) -> Result<(), String> {
+    let path = config_path(&app)?;
+    profiles::rename_profile(&path, &name, &new_name)?;
+    {
+        let mut runtime = state.runtime.lock().await;
+        if runtime.active_profile.as_deref() == Some(name.as_str()) {
+            runtime.active_profile = Some(new_name.clone());
+        }
+    }
+    info!(from = %name, to = %new_name, "Profile renamed");
+    tray::refresh_tray(&app).await;
+    Ok(())
+}
+
+#[tauri::command]
+async fn delete_profile(app: tauri::AppHandle, name: String) -> Result<(), String> {
+    profiles::delete_profile(&config_path(&app)?, &name)?;
+    info!(profile = %name, "Profile deleted");
+    tray::refresh_tray(&app).await;
+    Ok(())
+}
+
+#[tauri::command]
+async fn activate_profile(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
+    name: String,
+) -> Result<ProxyStatus, String> {
+    switch_profile(&app, state.inner(), &name).await
+}
+
+/// Makes `name` the active profile and hot-swaps the running providers onto
+/// it: whatever was running before is restarted with the new settings. A
+/// profile that fails to load is swapped back out.
+pub(crate) async fn switch_profile(
+    app: &tauri::AppHandle,
+    state: &AppState,
+    name: &str,
+) -> Result<ProxyStatus, String> {
+    let path = config_path(app)?;
+    let previous = profiles::active_profile(&path);
+    if previous == name {
+        return Ok(current_proxy_status(state).await);
+    }
+    let status = current_proxy_status(state).await;
+    let was_running: Vec<&'static str> = PROVIDERS
+        .into_iter()
+        .filter(|provider| {
+            if *provider == "tavily" {
+                status.tavily_running
+            } else {
+                status.firecrawl_running
+            }
+        })
+        .collect();
+
+    profiles::activate_profile(&path, name)?;
+    let config = match reload_config_file(app, state).await {
+        Ok(config) => config,
+        Err(err) => {
+            profiles::activate_profile(&path, &previous)?;
+            reload_config_file(app, state).await?;
+            return Err(format!("Failed to load profile '{}': {}", name, err));
+        }
+    };
+    state.runtime.lock().await.active_profile = Some(name.to_string());
+    info!(from = %previous, to = %name, "Profile activated");
+
+    stop_all_providers(state).await;
+    let locked = app.state::<SecretsLock>().0.load(Ordering::SeqCst);
+    for provider in was_running {
+        if locked || !config.provider_enabled(provider) {
+            continue;
+        }
+        if let Err(err) = start_single_provider(state, provider).await {
+            warn!(provider, error = %err, "Failed to restart provider on the new profile");
+        }
+    }
+    tray::refresh_tray(app).await;
+    Ok(current_proxy_status(state).await)
+}
+
+#[tauri::command]
+async fn get_proxy_status(state: tauri::State<'_, AppState>) -> Result<ProxyStatus, String> {
+    let config = state.config.read().await.clone();
+    let runtime = state.runtime.lock().await;
+    Ok(compose_proxy_status(&runtime, &config))
+}
+
+#[tauri::command]
+async fn start_proxy(state: tauri::State<'_, AppState>) -> Result<ProxyStatus, String> {
+    start_all_providers(state.inner()).await
+}
+
+#[tauri::command]
+async fn stop_proxy(state: tauri::State<'_, AppState>) -> Result<ProxyStatus, String> {
+    Ok(stop_all_providers(state.inner()).await)
+}
+
+#[tauri::command]
+async fn start_provider_proxy(
+    state: tauri::State<'_, AppState>,
+    provider: String,
+) -> Result<ProxyStatus, String> {
+    start_single_provider(state.inner(), parse_provider(&provider)?).await
+}
+
+#[tauri::command]
+async fn stop_provider_proxy(
+    state: tauri::State<'_, AppState>,
+    provider: String,
+) -> Result<ProxyStatus, String> {
+    let provider = parse_provider(&provider)?;
+    stop_provider(state.inner(), provider).await;
+    Ok(current_proxy_status(state.inner()).await)
+}
+
+#[tauri::command]
+async fn restart_provider_proxy(
+    state: tauri::State<'_, AppState>,
+    provider: String,
+) -> Result<ProxyStatus, String> {
+    restart_single_provider(state.inner(), parse_provider(&provider)?).await
+}
+
+#[tauri::command]
+async fn get_recent_logs(state: tauri::State<'_, AppState>) -> Result<Vec<String>, String> {
+    Ok(state.logs.snapshot())
+}
+
+#[tauri::command]
+async fn query_request_history(
+    state: tauri::State<'_, AppState>,
+    query: Option<HistoryQuery>,
+) -> Result<HistoryPage, String> {
+    let history = state
+        .history
+        .clone()
+        .ok_or_else(|| "Request history is disabled".to_string())?;
+    let query = query.unwrap_or_default();
+    tokio::task::spawn_blocking(move || history.query(&query))
+        .await
+        .map_err(|e| format!("History query task failed: {}", e))?
+}
+
+#[tauri::command]
+async fn get_request_history_stats(
+    state: tauri::State<'_, AppState>,
+    query: Option<HistoryQuery>,
+) -> Result<HistoryStats, String> {
+    let history = state
+        .history
+        .clone()
+        .ok_or_else(|| "Request history is disabled".to_string())?;
+    let query = query.unwrap_or_default();
+    tokio::task::spawn_blocking(move || history.stats(&query))
+        .await
+        .map_err(|e| format!("History query task failed: {}", e))?
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ReplayResult {
+    url: String,
+    status: u16,
+    latency_ms: u64,
+    headers: Vec<CapturedHeader>,
+    body: CapturedBody,
+}
+
+#[tauri::command]
+async fn get_debug_capture_status(
+    state: tauri::State<'_, AppState>,
+) -> Result<CaptureStatus, String> {
+    Ok(state.capture.status())
+}
+
+#[tauri::command]
+async fn start_debug_capture(
+    state: tauri::State<'_, AppState>,
+    settings: Option<CaptureSettings>,
+) -> Result<CaptureStatus, String> {
+    let status = state.capture.start(settings.unwrap_or_default())?;
+    info!(
+        max_requests = status.settings.max_requests,
+        max_minutes = status.settings.max_minutes,
+        "Debug capture started"
+    );
+    Ok(status)
+}
+
+#[tauri::command]
+async fn stop_debug_capture(state: tauri::State<'_, AppState>) -> Result<CaptureStatus, String> {
+    info!("Debug capture stopped");
+    Ok(state.capture.stop())
+}
+
+#[tauri::command]
+async fn list_captures(state: tauri::State<'_, AppState>) -> Result<Vec<CaptureSummary>, String> {
+    let capture = state.capture.clone();
+    tokio::task::spawn_blocking(move || capture.list())
+        .await
+        .map_err(|e| format!("Capture listing task failed: {}", e))?
+}
+
+#[tauri::command]
+async fn get_capture(
+    state: tauri::State<'_, AppState>,
+    id: String,
+) -> Result<CaptureEntry, String> {
+    state.capture.load(&id)
+}
+
+#[tauri::command]
+async fn clear_captures(state: tauri::State<'_, AppState>) -> Result<usize, String> {
+    let capture = state.capture.clone();
+    tokio::task::spawn_blocking(move || capture.clear())
+        .await
+        .map_err(|e| format!("Capture cleanup task failed: {}", e))?
+}
+
+/// Re-sends a captured request through the running local proxy so it goes
+/// through key selection again. Redacted values in the body are replayed as
+/// the proxy token, the only secret a client is expected to send.
+#[tauri::command]
+async fn replay_capture(
+    state: tauri::State<'_, AppState>,
+    id: String,
+) -> Result<ReplayResult, String> {
+    let entry = state.capture.load(&id)?;
+    if entry.request_body.truncated {
+        return Err("Captured request body was truncated and cannot be replayed".to_string());
+    }
+
+    let config = state.config.read().await.clone();
+    let listen_url = {
+        let runtime = state.runtime.lock().await;
+        let handle = match entry.provider.as_str() {
+            "firecrawl" => runtime.firecrawl_handle.as_ref(),
+            "tavily" => runtime.tavily_handle.as_ref(),
+            _ => None,
+        };
+        handle
+            .map(|h| h.listen_url.clone())
+            .ok_or_else(|| format!("The {} proxy is not running", entry.provider))?
+    };
+
+    let mut url = format!("{}{}", listen_url, entry.path);
+    if let Some(query) = &entry.query {
+        url.push('?');
+        url.push_str(query);
+    }
+    let method = Method::from_bytes(entry.method.as_bytes())
+        .map_err(|_| format!("Invalid captured method: {}", entry.method))?;
+
+    let mut headers = HeaderMap::new();
+    for header in &entry.request_headers {
+        if REQUEST_HEADER_BLOCKLIST.contains(&header.name.as_str())
+            || header.value.contains("[REDACTED]")
+        {
+            continue;
+        }
+        if let (Ok(name), Ok(value)) = (
+            axum::http::HeaderName::from_bytes(header.name.as_bytes()),
+            HeaderValue::from_str(&header.value),
+        ) {
+            headers.insert(name, value);
+        }
+    }
+    let auth = HeaderValue::from_str(&format!("Bearer {}", config.proxy_token))
+        .map_err(|_| "Invalid proxy token".to_string())?;
+    headers.insert("authorization", auth);
+
+    let client = Client::builder()
+        .timeout(Duration::from_millis(config.request_timeout_ms))
+        .build()
+        .map_err(|e| format!("Failed to build HTTP client: {}", e))?;
+    let body = entry
+        .request_body
+        .text
+        .replace("[REDACTED]", &config.proxy_token);
+
+    let started = Instant::now();
+    let response = client
+        .request(method, &url)
+        .headers(headers)
+        .body(body)
+        .send()
+        .await
+        .map_err(|e| format!("Replay request failed: {}", e))?;
+    let status = response.status().as_u16();
+    let secrets = vec![config.proxy_token.clone()];
+    let response_headers = redact_headers(response.headers(), &secrets);
+    let payload = response
+        .bytes()
+        .await
+        .map_err(|e| format!("Failed to read replay response: {}", e))?;
+
+    info!(capture_id = %id, status, "Debug capture replayed");
+    Ok(ReplayResult {
+        url,
+        status,
+        latency_ms: started.elapsed().as_millis() as u64,
+        headers: response_headers,
+        body: capture_body(
+            &payload,
+            CaptureSettings::default().max_body_bytes,
+            &secrets,
+        ),
+    })
+}
+
+#[tauri::command]
+async fn get_log_directory(app: tauri::AppHandle) -> Result<String, String> {
+    let dir = log_dir(&app)?;
+    fs::create_dir_all(&dir).map_err(|e| format!("Failed to create log dir: {}", e))?;
+    Ok(dir.to_string_lossy().to_string())
+}
+
+#[tauri::command]
+async fn export_logs(
+    app: tauri::AppHandle,
+    start_ts: Option<u64>,
+    end_ts: Option<u64>,
+) -> Result<String, String> {
+    let dir = log_dir(&app)?;
+    let export_dir = dir.join("exports");
+    fs::create_dir_all(&export_dir).map_err(|e| format!("Failed to create export dir: {}", e))?;
+    let destination = export_dir.join(format!(
+        "balance-proxy-{}-{}.log",
+        start_ts.unwrap_or(0),
+        end_ts.unwrap_or_else(now_ts)
+    ));
+
+    let output = destination.clone();
+    let count =
+        tokio::task::spawn_blocking(move || export_log_range(&dir, start_ts, end_ts, &output))
+            .await
+            .map_err(|e| format!("Log export task failed: {}", e))?
+            .map_err(|e| format!("Failed to export logs: {}", e))?;
+
+    info!(path = %destination.to_string_lossy(), lines = count, "Logs exported");
+    Ok(destination.to_string_lossy().to_string())
+}
+
+#[tauri::command]
+async fn get_key_status(state: tauri::State<'_, AppState>) -> Result<Vec<KeyStatus>, String> {
+    let snapshot = build_key_status_snapshot_inner(state.inner()).await;
+    Ok(snapshot.firecrawl.keys)
+}
+
+#[tauri::command]
+async fn get_key_status_snapshot(
+    state: tauri::State<'_, AppState>,
+) -> Result<KeyStatusSnapshot, String> {
+    Ok(build_key_status_snapshot_inner(state.inner()).await)
+}
+
+#[tauri::command]
+async fn build_mcp_config(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
+    target: Option<String>,
+) -> Result<String, String> {
+    let target = target
+        .unwrap_or_else(|| "both".to_string())
+        .to_ascii_lowercase();
+    let config = state.config.read().await.clone();
+    render_mcp_config(&app_data_dir(&app)?, &config, &target)
+}
+
+#[tauri::command]
+async fn get_launch_on_login_enabled(app: tauri::AppHandle) -> Result<bool, String> {
+    app.autolaunch()
+        .is_enabled()
+        .map_err(|e| format!("Failed to read launch-on-login state: {}", e))
+}
+
+#[tauri::command]
+async fn set_launch_on_login_enabled(app: tauri::AppHandle, enabled: bool) -> Result<bool, String> {
+    let manager = app.autolaunch();
+    if enabled {
+        manager
+            .enable()
+            .map_err(|e| format!("Failed to enable launch-on-login: {}", e))?;
+    } else {
+        manager
+            .disable()
+            .map_err(|e| format!("Failed to disable launch-on-login: {}", e))?;
+    }
+    manager
+        .is_enabled()
+        .map_err(|e| format!("Failed to verify launch-on-login state: {}", e))
+}
+
+/// Relays bus events to the webview as Tauri events.
+fn forward_events_to_frontend(app: tauri::AppHandle, mut rx: broadcast::Receiver<ProxyEvent>) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            match rx.recv().await {
+                Ok(event) => {
+                    let (name, payload) = event.to_wire();
+                    let _ = app.emit(name, payload);
+                }
+                Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                Err(broadcast::error::RecvError::Closed) => break,
+            }
+        }
+    });
+}
+
+/// Shows native notifications for pool exhaustion, dead keys and crashed
+/// listeners, using the current notification settings for each event.
+fn forward_alerts_to_desktop(
+    app: tauri::AppHandle,
+    mut rx: broadcast::Receiver<ProxyEvent>,
+    config: Arc<RwLock<ProxyConfig>>,
+) {
+    tauri::async_runtime::spawn(async move {
+        let mut policy = AlertPolicy::default();
+        loop {
+            let event = match rx.recv().await {
+                Ok(event) => event,
+                Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                Err(broadcast::error::RecvError::Closed) => break,
+            };
+            if matches!(
+                event,
+                ProxyEvent::LogLine(_) | ProxyEvent::RequestCompleted(_)
+            ) {
+                continue;
+            }
+            let settings = {
+                let config = config.read().await;
+                if !config.notifications_enabled {
+                    continue;
+                }
+                AlertSettings {
+                    min_healthy_keys: config.notify_min_healthy_keys,
+                    cooldown: Duration::from_secs(config.notify_cooldown_minutes * 60),
+                }
+            };
+            let Some(alert) = policy.evaluate(&event, settings, Instant::now()) else {
+                continue;
+            };
+            if let Err(err) = app
+                .notification()
+                .builder()
+                .title(&alert.title)
+                .body(&alert.body)
+                .show()
+            {
+                warn!(error = %err, title = %alert.title, "Failed to show notification");
+            }
+        }
+    });
+}
+
+pub(crate) fn show_main_window<R: tauri::Runtime, M: Manager<R>>(manager: &M) {
+    #[cfg(target_os = "macos")]
+    let _ = manager.app_handle().set_dock_visibility(true);
+
+    if let Some(window) = manager.get_webview_window("main") {
+        let _ = window.show();
+        let _ = window.unminimize();
+        let _ = window.set_focus();
+    }
+}
+
+#[cfg_attr(mobile, tauri::mobile_entry_point)]
+pub fn run() {
+    tauri::Builder::default()
+        .on_window_event(|window, event| {
+            if let WindowEvent::CloseRequested { api, .. } = event {
+                api.prevent_close();
+                let _ = window.hide();
+
+                #[cfg(target_os = "macos")]
+                let _ = window.app_handle().set_dock_visibility(false);
+            }
+        })
+        .on_menu_event(|app, event| tray::handle_menu_event(app, event.id().as_ref()))
+        .plugin(tauri_plugin_autostart::init(
+            MacosLauncher::LaunchAgent,
+            None,
+        ))
+        .plugin(tauri_plugin_clipboard_manager::init())
+        .plugin(tauri_plugin_notification::init())
+        .plugin(tauri_plugin_opener::init())
+        .setup(|app| {
+            let data_dir = app_data_dir(app.handle())?;
+            let config_path = config_path(app.handle())?;
+            let recovery = recover_corrupt_config(&config_path)?;
+            let migrated_from = init_config_file(&config_path)?;
+            let env_vars = process_env_vars();
+            let ResolvedConfig {
+                config,
+                sources,
+                unknown_keys,
+                locked,
+                ..
+            } = resolve_config(Some(&config_path), &[(ConfigSource::Env, &env_vars)])?;
+            let logs = LogBuffer::default();
+            let events = EventBus::default();
+            forward_events_to_frontend(app.handle().clone(), events.subscribe());
+            let log_level = parse_log_level(&config.log_level).unwrap_or(LevelFilter::INFO);
+            let log_file = open_log_file(&data_dir, &config);
+            let tracer_provider = open_tracer_provider(&config);
+            let log_handle = init_tracing(
+                log_level,
+                config.log_json,
+                logs.clone(),
+                events.clone(),
+                log_file.clone().unwrap_or_default(),
+                tracer_provider.clone().unwrap_or_default(),
+            )?;
+            info!("App initialized. Config path is in app data directory.");
+            warn_unavailable("Log file", &log_file);
+            warn_unavailable("OTLP export", &tracer_provider);
+            log_load_notes(&unknown_keys, migrated_from);
+            if let Some(recovery) = &recovery {
+                warn!(
+                    error = %recovery.error,
+                    backup = %recovery.backup,
+                    corrupt_copy = %recovery.corrupt_copy,
+                    "Config file could not be loaded; restored the latest valid backup"
+                );
+                let _ = app
+                    .notification()
+                    .builder()
+                    .title("Config restored from backup")
+                    .body(format!(
+                        "proxy-config.json could not be loaded, so {} was used instead.",
+                        recovery.backup
+                    ))
+                    .show();
+            }
+            if !env_vars.is_empty() {
+                let mut names: Vec<&str> = env_vars.keys().map(String::as_str).collect();
+                names.sort_unstable();
+                info!(vars = %names.join(","), "Config overridden by environment variables");
+            }
+            let history = open_history_store(&data_dir, &config);
+            let capture = DebugCapture::new(data_dir.join(CAPTURE_DIR_NAME));
+
+            let config = Arc::new(RwLock::new(config));
+            forward_alerts_to_desktop(app.handle().clone(), events.subscribe(), config.clone());
+            let tray_events = events.subscribe();
+
+            app.manage(AppState {
+                config,
+                config_sources: Arc::new(RwLock::new(sources)),
+                runtime: Arc::new(Mutex::new(ProxyRuntime {
+                    active_profile: Some(profiles::active_profile(&config_path)),
+                    ..ProxyRuntime::default()
+                })),
+                logs,
+                log_handle,
+                history,
+                capture,
+                events,
+                active_key_managers: Arc::new(Mutex::new(ActiveKeyManagers::default())),
+            });
+
+            app.manage(StartupRecovery(recovery));
+            app.manage(SecretsLock(AtomicBool::new(locked)));
+            if locked {
+                warn!("Config secrets are encrypted with a passphrase; unlock them in the app");
+            }
+
+            tray::create_tray(app.handle())?;
+            tray::watch_tray_status(app.handle().clone(), tray_events);
+
+            let tray_app = app.handle().clone();
+            tauri::async_runtime::spawn(key_watch::watch_key_folder(
+                app.state::<AppState>().inner().clone(),
+                config_path.clone(),
+                data_dir.join(KEY_WATCH_STATE_FILE),
+                move || {
+                    let app = tray_app.clone();
+                    tauri::async_runtime::spawn(async move { tray::refresh_tray(&app).await });
+                },
+            ));
+
+            let state = app.state::<AppState>().inner().clone();
+            tauri::async_runtime::spawn(async move {
+                if locked || !state.config.read().await.auto_start_proxy {
+                    return;
+                }
+                match start_all_providers(&state).await {
+                    Ok(_) => info!("Proxy auto-started on launch"),
+                    Err(err) => warn!(error = %err, "Failed to auto-start proxy on launch"),
+                }
+            });
+            Ok(())
+        })
+        .invoke_handler(tauri::generate_handler![
+            load_proxy_config,
+            save_proxy_config,
+            get_config_sources,
+            import_env_config,
+            export_env_config,
+            list_config_backups,
+            restore_config_backup,
+            get_config_recovery,
+            reveal_secrets,
+            get_secrets_locked,
+            unlock_secrets,
+            list_keys,
+            add_key,
+            remove_key,
+            set_key_enabled,
+            update_key,
+            move_key,
+            import_keys,
+            validate_keys,
+            list_profiles,
+            create_profile,
+            clone_profile,
+            rename_profile,
+            delete_profile,
+            activate_profile,
+            get_proxy_status,
+            start_proxy,
+            stop_proxy,
+            start_provider_proxy,
+            stop_provider_proxy,
+            restart_provider_proxy,
+            get_recent_logs,
+            get_log_directory,
+            export_logs,
+            query_request_history,
+            get_request_history_stats,
+            get_debug_capture_status,
+            start_debug_capture,
+            stop_debug_capture,
+            list_captures,
+            get_capture,
+            clear_captures,
+            replay_capture,
+            get_key_status,
+            get_key_status_snapshot,
+            build_mcp_config,
+            get_launch_on_login_enabled,
+            set_launch_on_login_enabled
+        ])
+        .run(tauri::generate_context!())
+        .expect("error while running tauri application");
+}
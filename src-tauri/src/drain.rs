@@ -0,0 +1,106 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use tokio::sync::{watch, Notify};
+
+/// Counts the requests a listener is serving and lets a shutdown that ran out
+/// of drain time cut the stragglers short.
+#[derive(Clone)]
+pub(crate) struct RequestTracker {
+    in_flight: Arc<AtomicUsize>,
+    abort_tx: Arc<watch::Sender<bool>>,
+}
+
+impl Default for RequestTracker {
+    fn default() -> Self {
+        let (abort_tx, _) = watch::channel(false);
+        Self {
+            in_flight: Arc::new(AtomicUsize::new(0)),
+            abort_tx: Arc::new(abort_tx),
+        }
+    }
+}
+
+impl RequestTracker {
+    /// Marks a request as in flight until the returned guard is dropped.
+    pub(crate) fn track(&self) -> InFlightGuard {
+        InFlightGuard::new(self.in_flight.clone())
+    }
+
+    pub(crate) fn in_flight(&self) -> usize {
+        self.in_flight.load(Ordering::SeqCst)
+    }
+
+    /// Resolves once `abort_all` has been called; pending forever otherwise.
+    pub(crate) async fn aborted(&self) {
+        let mut abort_rx = self.abort_tx.subscribe();
+        let _ = abort_rx.wait_for(|aborted| *aborted).await;
+    }
+
+    pub(crate) fn abort_all(&self) {
+        self.abort_tx.send_replace(true);
+    }
+}
+
+pub(crate) struct InFlightGuard {
+    in_flight: Arc<AtomicUsize>,
+    released: Option<Arc<Notify>>,
+}
+
+impl InFlightGuard {
+    /// Counts one more in flight on `in_flight` until the guard is dropped.
+    pub(crate) fn new(in_flight: Arc<AtomicUsize>) -> Self {
+        in_flight.fetch_add(1, Ordering::SeqCst);
+        Self {
+            in_flight,
+            released: None,
+        }
+    }
+
+    /// Like `new`, and wakes everyone waiting on `released` once dropped.
+    pub(crate) fn notifying(in_flight: Arc<AtomicUsize>, released: Arc<Notify>) -> Self {
+        let mut guard = Self::new(in_flight);
+        guard.released = Some(released);
+        guard
+    }
+}
+
+impl Drop for InFlightGuard {
+    fn drop(&mut self) {
+        self.in_flight.fetch_sub(1, Ordering::SeqCst);
+        if let Some(released) = &self.released {
+            released.notify_waiters();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn guards_track_in_flight_requests() {
+        let tracker = RequestTracker::default();
+        let first = tracker.track();
+        let second = tracker.clone().track();
+        assert_eq!(tracker.in_flight(), 2);
+        drop(first);
+        assert_eq!(tracker.in_flight(), 1);
+        drop(second);
+        assert_eq!(tracker.in_flight(), 0);
+    }
+
+    #[tokio::test]
+    async fn abort_wakes_current_and_later_waiters() {
+        let tracker = RequestTracker::default();
+        let waiter = tokio::spawn({
+            let tracker = tracker.clone();
+            async move { tracker.aborted().await }
+        });
+        tokio::task::yield_now().await;
+        tracker.abort_all();
+        waiter.await.expect("waiter should finish");
+        // A request that checks after the abort does not hang either.
+        tracker.aborted().await;
+    }
+}
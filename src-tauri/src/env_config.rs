@@ -0,0 +1,353 @@
+//! Maps `.env` files and environment variables onto `ProxyConfig`. Variable
+//! names are the config field names in SCREAMING_SNAKE_CASE, so
+//! `tavilyApiKeys` is read from `TAVILY_API_KEYS`.
+
+use std::collections::{BTreeMap, HashMap};
+use std::env;
+use std::path::Path;
+
+use serde::Serialize;
+use serde_json::{Map, Value};
+
+use crate::config_schema::load_config_file;
+use crate::ProxyConfig;
+
+/// Where the effective value of a config field came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) enum ConfigSource {
+    Default,
+    File,
+    DotEnv,
+    Env,
+}
+
+impl ConfigSource {
+    pub(crate) fn label(self) -> &'static str {
+        match self {
+            Self::Default => "default",
+            Self::File => "config file",
+            Self::DotEnv => ".env",
+            Self::Env => "environment",
+        }
+    }
+}
+
+/// Source of every config field, keyed by variable name.
+pub(crate) type ConfigSources = BTreeMap<String, ConfigSource>;
+
+pub(crate) struct ResolvedConfig {
+    pub(crate) config: ProxyConfig,
+    pub(crate) sources: ConfigSources,
+    pub(crate) unknown_keys: Vec<String>,
+    pub(crate) migrated_from: Option<u64>,
+    /// Secrets in the file await a passphrase; see `ConfigFile::locked`.
+    pub(crate) locked: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct EnvImport {
+    pub(crate) config: ProxyConfig,
+    pub(crate) applied: Vec<String>,
+    pub(crate) ignored: Vec<String>,
+}
+
+/// Parses `KEY=value` lines. Blank lines, `#` comments and a leading
+/// `export ` are ignored; values may be wrapped in single or double quotes.
+pub(crate) fn parse_dotenv(text: &str) -> Result<HashMap<String, String>, String> {
+    let mut vars = HashMap::new();
+    for (index, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let line = line.strip_prefix("export ").unwrap_or(line);
+        let (name, value) = line
+            .split_once('=')
+            .ok_or_else(|| format!("Invalid .env line {}: expected KEY=value", index + 1))?;
+        vars.insert(name.trim().to_string(), unquote(value.trim()).to_string());
+    }
+    Ok(vars)
+}
+
+fn unquote(value: &str) -> &str {
+    for quote in ['"', '\''] {
+        if let Some(inner) = value
+            .strip_prefix(quote)
+            .and_then(|v| v.strip_suffix(quote))
+        {
+            return inner;
+        }
+    }
+    value
+}
+
+pub(crate) fn env_var_name(field: &str) -> String {
+    let mut name = String::with_capacity(field.len() + 4);
+    for ch in field.chars() {
+        if ch.is_ascii_uppercase() {
+            name.push('_');
+        }
+        name.push(ch.to_ascii_uppercase());
+    }
+    name
+}
+
+fn config_fields(config: &ProxyConfig) -> Result<Map<String, Value>, String> {
+    match serde_json::to_value(config) {
+        Ok(Value::Object(fields)) => Ok(fields),
+        Ok(_) => Err("Config is not an object".to_string()),
+        Err(e) => Err(format!("Failed to serialize config: {}", e)),
+    }
+}
+
+fn config_from_fields(fields: Map<String, Value>) -> Result<ProxyConfig, String> {
+    let config: ProxyConfig = serde_json::from_value(Value::Object(fields))
+        .map_err(|e| format!("Failed to apply env vars: {}", e))?;
+    Ok(config.normalized())
+}
+
+/// Variable names of all config fields, sorted.
+pub(crate) fn config_var_names() -> Vec<String> {
+    config_fields(&ProxyConfig::default())
+        .map(|fields| fields.keys().map(|field| env_var_name(field)).collect())
+        .unwrap_or_default()
+}
+
+/// Returns `config` with every field whose variable `lookup` knows replaced by
+/// the parsed value. Lists are comma or newline separated.
+pub(crate) fn apply_env_vars(
+    config: &ProxyConfig,
+    lookup: impl Fn(&str) -> Option<String>,
+) -> Result<ProxyConfig, String> {
+    let mut fields = config_fields(config)?;
+    for (field, current) in fields.iter_mut() {
+        let name = env_var_name(field);
+        if let Some(raw) = lookup(&name) {
+            *current = parse_env_value(&name, &raw, current)?;
+        }
+    }
+    config_from_fields(fields)
+}
+
+/// Config variables set in the process environment.
+pub(crate) fn process_env_vars() -> HashMap<String, String> {
+    config_var_names()
+        .into_iter()
+        .filter_map(|name| env::var(&name).ok().map(|value| (name, value)))
+        .collect()
+}
+
+/// Loads the JSON file (when given) and applies each variable layer on top,
+/// later layers winning, while recording where every field came from.
+pub(crate) fn resolve_config(
+    file: Option<&Path>,
+    layers: &[(ConfigSource, &HashMap<String, String>)],
+) -> Result<ResolvedConfig, String> {
+    let mut sources: ConfigSources = config_var_names()
+        .into_iter()
+        .map(|name| (name, ConfigSource::Default))
+        .collect();
+
+    let mut config = ProxyConfig::default();
+    let mut unknown_keys = Vec::new();
+    let mut migrated_from = None;
+    let mut locked = false;
+    if let Some(path) = file {
+        let file = load_config_file(path)?;
+        for field in file.fields.keys() {
+            if let Some(source) = sources.get_mut(&env_var_name(field)) {
+                *source = ConfigSource::File;
+            }
+        }
+        config = file.config()?;
+        unknown_keys = file.unknown_keys;
+        migrated_from = file.migrated_from;
+        locked = file.locked;
+    }
+
+    for (source, vars) in layers {
+        config = apply_env_vars(&config, |name| vars.get(name).cloned())?;
+        for name in vars.keys() {
+            if let Some(entry) = sources.get_mut(name) {
+                *entry = *source;
+            }
+        }
+    }
+
+    Ok(ResolvedConfig {
+        config: config.normalized(),
+        sources,
+        unknown_keys,
+        migrated_from,
+        locked,
+    })
+}
+
+/// Returns `into` with the fields named by `names` taken from `from`.
+pub(crate) fn copy_fields<'a>(
+    into: &ProxyConfig,
+    from: &ProxyConfig,
+    names: impl IntoIterator<Item = &'a str>,
+) -> Result<ProxyConfig, String> {
+    let mut fields = config_fields(into)?;
+    let source = config_fields(from)?;
+    let names: Vec<&str> = names.into_iter().collect();
+    for (field, value) in fields.iter_mut() {
+        if names.contains(&env_var_name(field).as_str()) {
+            if let Some(replacement) = source.get(field) {
+                *value = replacement.clone();
+            }
+        }
+    }
+    config_from_fields(fields)
+}
+
+/// Applies the variables of a `.env` file onto `base`, reporting which names
+/// were used and which are not config variables.
+pub(crate) fn import_dotenv(base: &ProxyConfig, text: &str) -> Result<EnvImport, String> {
+    let vars = parse_dotenv(text)?;
+    let known = config_var_names();
+    let (mut applied, mut ignored): (Vec<String>, Vec<String>) =
+        vars.keys().cloned().partition(|name| known.contains(name));
+    applied.sort();
+    ignored.sort();
+    Ok(EnvImport {
+        config: apply_env_vars(base, |name| vars.get(name).cloned())?,
+        applied,
+        ignored,
+    })
+}
+
+/// Renders `config` as a `.env` file that `import_dotenv` reads back.
+pub(crate) fn to_dotenv(config: &ProxyConfig) -> Result<String, String> {
+    let mut out = String::from("# Balance Proxy configuration\n");
+    for (field, value) in config_fields(config)? {
+        let rendered = match value {
+            // Key records export as their values; labels and the rest stay
+            // in the config file.
+            Value::Array(items) => items
+                .iter()
+                .filter_map(|item| item.as_str().or_else(|| item.get("value")?.as_str()))
+                .collect::<Vec<_>>()
+                .join(","),
+            Value::String(text) => text,
+            other => other.to_string(),
+        };
+        let needs_quotes = rendered.contains(char::is_whitespace) || rendered.contains('#');
+        if needs_quotes {
+            out.push_str(&format!("{}=\"{}\"\n", env_var_name(&field), rendered));
+        } else {
+            out.push_str(&format!("{}={}\n", env_var_name(&field), rendered));
+        }
+    }
+    Ok(out)
+}
+
+fn parse_env_value(name: &str, raw: &str, current: &Value) -> Result<Value, String> {
+    let raw = raw.trim();
+    match current {
+        Value::Bool(_) => match raw.to_ascii_lowercase().as_str() {
+            "true" | "1" | "yes" | "on" => Ok(Value::Bool(true)),
+            "false" | "0" | "no" | "off" => Ok(Value::Bool(false)),
+            _ => Err(format!("{} must be true or false", name)),
+        },
+        Value::Number(_) => raw
+            .parse::<u64>()
+            .map(Value::from)
+            .map_err(|_| format!("{} must be a non-negative integer", name)),
+        Value::Array(_) => Ok(Value::Array(
+            raw.split([',', '\n'])
+                .map(str::trim)
+                .filter(|item| !item.is_empty())
+                .map(|item| Value::String(item.to_string()))
+                .collect(),
+        )),
+        _ => Ok(Value::String(raw.to_string())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::keys::key_values;
+
+    #[test]
+    fn dotenv_parsing_skips_comments_and_unquotes() {
+        let vars = parse_dotenv(
+            "# proxy\nexport PROXY_TOKEN=\"secret\"\n\nTAVILY_API_KEYS='a, b'\nPORT = 9000\n",
+        )
+        .expect("valid .env");
+        assert_eq!(vars["PROXY_TOKEN"], "secret");
+        assert_eq!(vars["TAVILY_API_KEYS"], "a, b");
+        assert_eq!(vars["PORT"], "9000");
+        assert!(parse_dotenv("NOT_AN_ASSIGNMENT").is_err());
+    }
+
+    #[test]
+    fn dotenv_export_round_trips() {
+        let config = ProxyConfig {
+            tavily_api_keys: vec!["tvly-1".into(), "tvly-2".into()],
+            otel_service_name: "balance proxy".to_string(),
+            port: 9100,
+            ..ProxyConfig::default()
+        };
+        let text = to_dotenv(&config).expect("config renders");
+        assert!(text.contains("TAVILY_API_KEYS=tvly-1,tvly-2\n"));
+        assert!(text.contains("OTEL_SERVICE_NAME=\"balance proxy\"\n"));
+
+        let imported = import_dotenv(&ProxyConfig::default(), &format!("{}EXTRA=1\n", text))
+            .expect("export imports");
+        assert_eq!(imported.ignored, vec!["EXTRA"]);
+        assert_eq!(imported.config.tavily_api_keys, config.tavily_api_keys);
+        assert_eq!(imported.config.otel_service_name, "balance proxy");
+        assert_eq!(imported.config.port, 9100);
+    }
+
+    #[test]
+    fn later_layers_win_and_report_their_source() {
+        let dotenv = HashMap::from([
+            ("PORT".to_string(), "9000".to_string()),
+            ("PROXY_TOKEN".to_string(), "from-file".to_string()),
+        ]);
+        let env = HashMap::from([("PROXY_TOKEN".to_string(), "from-env".to_string())]);
+        let resolved = resolve_config(
+            None,
+            &[(ConfigSource::DotEnv, &dotenv), (ConfigSource::Env, &env)],
+        )
+        .expect("valid layers");
+        assert_eq!(resolved.config.port, 9000);
+        assert_eq!(resolved.config.proxy_token, "from-env");
+        assert_eq!(resolved.sources["PORT"], ConfigSource::DotEnv);
+        assert_eq!(resolved.sources["PROXY_TOKEN"], ConfigSource::Env);
+        assert_eq!(resolved.sources["LOG_LEVEL"], ConfigSource::Default);
+    }
+
+    #[test]
+    fn env_vars_override_typed_fields() {
+        let vars = HashMap::from([
+            ("TAVILY_API_KEYS", "tvly-1,tvly-2\ntvly-1"),
+            ("LOG_FILE_MAX_SIZE_MB", "25"),
+            ("HISTORY_ENABLED", "off"),
+            ("TAVILY_UPSTREAM_BASE_URL", "https://example.test/"),
+        ]);
+        let config = apply_env_vars(&ProxyConfig::default(), |name| {
+            vars.get(name).map(|v| v.to_string())
+        })
+        .expect("valid overrides");
+        assert_eq!(
+            key_values(&config.tavily_api_keys),
+            vec!["tvly-1", "tvly-2"]
+        );
+        assert_eq!(config.log_file_max_size_mb, 25);
+        assert!(!config.history_enabled);
+        assert_eq!(config.tavily_upstream_base_url, "https://example.test");
+
+        let err = apply_env_vars(&ProxyConfig::default(), |name| {
+            (name == "PORT").then(|| "eighty".to_string())
+        })
+        .unwrap_err();
+        assert_eq!(err, "PORT must be a non-negative integer");
+    }
+}
@@ -0,0 +1,149 @@
+use serde::Serialize;
+use tokio::sync::broadcast;
+
+use crate::KeyStatus;
+
+/// Buffered events per subscriber; a UI that falls further behind than this
+/// misses events and resyncs from the snapshot commands.
+const EVENT_CHANNEL_CAPACITY: usize = 1024;
+
+pub(crate) const LOG_LINE_EVENT: &str = "log-line";
+pub(crate) const PROXY_LIFECYCLE_EVENT: &str = "proxy-lifecycle";
+pub(crate) const KEY_STATE_EVENT: &str = "key-state";
+pub(crate) const REQUEST_COMPLETED_EVENT: &str = "request-completed";
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) enum LifecycleState {
+    Started,
+    Draining,
+    Stopped,
+    Crashed,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ProxyLifecycle {
+    pub(crate) provider: &'static str,
+    pub(crate) state: LifecycleState,
+    pub(crate) listen_url: Option<String>,
+    pub(crate) error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct KeyStateChange {
+    pub(crate) provider: &'static str,
+    pub(crate) key: KeyStatus,
+    /// Identifies the key across list edits, unlike its index.
+    pub(crate) key_fingerprint: String,
+    pub(crate) healthy_keys: usize,
+    pub(crate) total_keys: usize,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct RequestCompleted {
+    pub(crate) ts: u64,
+    pub(crate) request_id: String,
+    pub(crate) provider: &'static str,
+    pub(crate) method: String,
+    pub(crate) path: String,
+    pub(crate) status: u16,
+    pub(crate) key_index: Option<usize>,
+    pub(crate) retries: usize,
+    pub(crate) latency_ms: u64,
+}
+
+#[derive(Debug, Clone)]
+pub(crate) enum ProxyEvent {
+    LogLine(String),
+    Lifecycle(ProxyLifecycle),
+    KeyState(KeyStateChange),
+    RequestCompleted(RequestCompleted),
+}
+
+impl ProxyEvent {
+    /// Event name and JSON payload as delivered to the frontend.
+    pub(crate) fn to_wire(&self) -> (&'static str, serde_json::Value) {
+        let payload = match self {
+            Self::LogLine(line) => Ok(serde_json::Value::String(line.clone())),
+            Self::Lifecycle(event) => serde_json::to_value(event),
+            Self::KeyState(event) => serde_json::to_value(event),
+            Self::RequestCompleted(event) => serde_json::to_value(event),
+        }
+        .unwrap_or(serde_json::Value::Null);
+        (self.name(), payload)
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            Self::LogLine(_) => LOG_LINE_EVENT,
+            Self::Lifecycle(_) => PROXY_LIFECYCLE_EVENT,
+            Self::KeyState(_) => KEY_STATE_EVENT,
+            Self::RequestCompleted(_) => REQUEST_COMPLETED_EVENT,
+        }
+    }
+}
+
+/// Fan-out of live proxy events. Publishing never blocks and is a no-op when
+/// nobody is subscribed.
+#[derive(Clone)]
+pub(crate) struct EventBus {
+    sender: broadcast::Sender<ProxyEvent>,
+}
+
+impl Default for EventBus {
+    fn default() -> Self {
+        let (sender, _) = broadcast::channel(EVENT_CHANNEL_CAPACITY);
+        Self { sender }
+    }
+}
+
+impl EventBus {
+    pub(crate) fn publish(&self, event: ProxyEvent) {
+        let _ = self.sender.send(event);
+    }
+
+    pub(crate) fn subscribe(&self) -> broadcast::Receiver<ProxyEvent> {
+        self.sender.subscribe()
+    }
+
+    pub(crate) fn lifecycle(
+        &self,
+        provider: &'static str,
+        state: LifecycleState,
+        listen_url: Option<String>,
+        error: Option<String>,
+    ) {
+        self.publish(ProxyEvent::Lifecycle(ProxyLifecycle {
+            provider,
+            state,
+            listen_url,
+            error,
+        }));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn wire_format_uses_event_names_and_camel_case() {
+        let bus = EventBus::default();
+        let mut rx = bus.subscribe();
+        bus.lifecycle(
+            "tavily",
+            LifecycleState::Crashed,
+            None,
+            Some("bind failed".to_string()),
+        );
+
+        let (name, payload) = rx.try_recv().expect("event should be buffered").to_wire();
+        assert_eq!(name, PROXY_LIFECYCLE_EVENT);
+        assert_eq!(payload["state"], "crashed");
+        assert_eq!(payload["listenUrl"], serde_json::Value::Null);
+        assert_eq!(payload["error"], "bind failed");
+    }
+}
@@ -0,0 +1,533 @@
+use std::path::Path;
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+use rusqlite::types::Value;
+use rusqlite::{params, params_from_iter, Connection};
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+use crate::now_ts;
+
+const MAX_PAGE_SIZE: u32 = 500;
+const DEFAULT_PAGE_SIZE: u32 = 50;
+const PRUNE_EVERY_INSERTS: u64 = 500;
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS requests (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    ts INTEGER NOT NULL,
+    request_id TEXT NOT NULL,
+    provider TEXT NOT NULL,
+    client TEXT NOT NULL,
+    method TEXT NOT NULL,
+    path TEXT NOT NULL,
+    status INTEGER NOT NULL,
+    key_index INTEGER,
+    key_fingerprint TEXT,
+    retries INTEGER NOT NULL,
+    latency_ms INTEGER NOT NULL,
+    request_bytes INTEGER NOT NULL,
+    response_bytes INTEGER NOT NULL
+);
+CREATE INDEX IF NOT EXISTS idx_requests_ts ON requests (ts);
+CREATE INDEX IF NOT EXISTS idx_requests_provider_ts ON requests (provider, ts);
+CREATE INDEX IF NOT EXISTS idx_requests_key_ts ON requests (key_fingerprint, ts);
+";
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct RequestRecord {
+    pub(crate) ts: u64,
+    pub(crate) request_id: String,
+    pub(crate) provider: String,
+    pub(crate) client: String,
+    pub(crate) method: String,
+    pub(crate) path: String,
+    pub(crate) status: u16,
+    pub(crate) key_index: Option<usize>,
+    pub(crate) key_fingerprint: Option<String>,
+    pub(crate) retries: usize,
+    pub(crate) latency_ms: u64,
+    pub(crate) request_bytes: u64,
+    pub(crate) response_bytes: u64,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct HistoryRetention {
+    pub(crate) max_age_days: u64,
+    pub(crate) max_rows: u64,
+}
+
+/// Filters shared by history paging and aggregate queries. Every field is
+/// optional; timestamps are unix seconds and the range is inclusive.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub(crate) struct HistoryQuery {
+    pub(crate) provider: Option<String>,
+    pub(crate) client: Option<String>,
+    pub(crate) method: Option<String>,
+    pub(crate) path_prefix: Option<String>,
+    pub(crate) key_fingerprint: Option<String>,
+    pub(crate) status: Option<u16>,
+    pub(crate) errors_only: bool,
+    pub(crate) since_ts: Option<u64>,
+    pub(crate) until_ts: Option<u64>,
+    pub(crate) limit: Option<u32>,
+    pub(crate) offset: Option<u32>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct HistoryPage {
+    pub(crate) total: u64,
+    pub(crate) limit: u32,
+    pub(crate) offset: u32,
+    pub(crate) items: Vec<RequestRecord>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct StatusCount {
+    pub(crate) status: u16,
+    pub(crate) count: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct KeyUsage {
+    pub(crate) provider: String,
+    pub(crate) key_fingerprint: String,
+    pub(crate) requests: u64,
+    pub(crate) errors: u64,
+    pub(crate) retries: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct HistoryStats {
+    pub(crate) total: u64,
+    pub(crate) errors: u64,
+    pub(crate) retries: u64,
+    pub(crate) avg_latency_ms: f64,
+    pub(crate) max_latency_ms: u64,
+    pub(crate) request_bytes: u64,
+    pub(crate) response_bytes: u64,
+    pub(crate) by_status: Vec<StatusCount>,
+    pub(crate) by_key: Vec<KeyUsage>,
+}
+
+/// SQLite-backed request journal. Inserts are queued to a writer thread so
+/// the proxy hot path never waits on disk; queries use a separate connection.
+#[derive(Clone)]
+pub(crate) struct HistoryStore {
+    writer: Sender<RequestRecord>,
+    reader: Arc<Mutex<Connection>>,
+}
+
+impl HistoryStore {
+    pub(crate) fn open(path: &Path, retention: HistoryRetention) -> Result<Self, String> {
+        let writer_conn = open_connection(path)?;
+        writer_conn
+            .execute_batch(SCHEMA)
+            .map_err(|e| format!("Failed to initialize history schema: {}", e))?;
+        prune(&writer_conn, retention, now_ts())
+            .map_err(|e| format!("Failed to prune request history: {}", e))?;
+        let reader = open_connection(path)?;
+
+        let (writer, rx) = mpsc::channel();
+        thread::Builder::new()
+            .name("request-history".to_string())
+            .spawn(move || run_writer(writer_conn, rx, retention))
+            .map_err(|e| format!("Failed to start history writer: {}", e))?;
+
+        Ok(Self {
+            writer,
+            reader: Arc::new(Mutex::new(reader)),
+        })
+    }
+
+    pub(crate) fn record(&self, record: RequestRecord) {
+        // The writer only goes away with the process; dropping a record then
+        // is harmless.
+        let _ = self.writer.send(record);
+    }
+
+    pub(crate) fn query(&self, query: &HistoryQuery) -> Result<HistoryPage, String> {
+        let conn = self.reader.lock().unwrap_or_else(|e| e.into_inner());
+        query_page(&conn, query).map_err(|e| format!("Failed to query request history: {}", e))
+    }
+
+    pub(crate) fn stats(&self, query: &HistoryQuery) -> Result<HistoryStats, String> {
+        let conn = self.reader.lock().unwrap_or_else(|e| e.into_inner());
+        query_stats(&conn, query).map_err(|e| format!("Failed to aggregate request history: {}", e))
+    }
+}
+
+fn open_connection(path: &Path) -> Result<Connection, String> {
+    let conn = Connection::open(path).map_err(|e| {
+        format!(
+            "Failed to open request history {}: {}",
+            path.to_string_lossy(),
+            e
+        )
+    })?;
+    conn.busy_timeout(std::time::Duration::from_secs(5))
+        .and_then(|_| conn.pragma_update_and_check(None, "journal_mode", "WAL", |_| Ok(())))
+        .map_err(|e| format!("Failed to configure request history: {}", e))?;
+    Ok(conn)
+}
+
+fn run_writer(conn: Connection, rx: Receiver<RequestRecord>, retention: HistoryRetention) {
+    let mut inserted = 0u64;
+    while let Ok(record) = rx.recv() {
+        if let Err(err) = insert(&conn, &record) {
+            warn!(error = %err, "Failed to record request history");
+            continue;
+        }
+        inserted += 1;
+        if inserted.is_multiple_of(PRUNE_EVERY_INSERTS) {
+            if let Err(err) = prune(&conn, retention, now_ts()) {
+                warn!(error = %err, "Failed to prune request history");
+            }
+        }
+    }
+}
+
+fn insert(conn: &Connection, record: &RequestRecord) -> rusqlite::Result<()> {
+    conn.execute(
+        "INSERT INTO requests (ts, request_id, provider, client, method, path, status, key_index,
+            key_fingerprint, retries, latency_ms, request_bytes, response_bytes)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13)",
+        params![
+            record.ts as i64,
+            record.request_id,
+            record.provider,
+            record.client,
+            record.method,
+            record.path,
+            record.status,
+            record.key_index.map(|v| v as i64),
+            record.key_fingerprint,
+            record.retries as i64,
+            record.latency_ms as i64,
+            record.request_bytes as i64,
+            record.response_bytes as i64,
+        ],
+    )?;
+    Ok(())
+}
+
+fn prune(conn: &Connection, retention: HistoryRetention, now: u64) -> rusqlite::Result<()> {
+    let cutoff = now.saturating_sub(retention.max_age_days.saturating_mul(86_400));
+    conn.execute("DELETE FROM requests WHERE ts < ?1", params![cutoff as i64])?;
+    conn.execute(
+        "DELETE FROM requests WHERE id <= (
+            SELECT id FROM requests ORDER BY id DESC LIMIT 1 OFFSET ?1
+        )",
+        params![retention.max_rows as i64],
+    )?;
+    Ok(())
+}
+
+fn build_filter(query: &HistoryQuery) -> (String, Vec<Value>) {
+    let mut clauses: Vec<&str> = Vec::new();
+    let mut values: Vec<Value> = Vec::new();
+
+    if let Some(provider) = query.provider.as_ref().filter(|v| !v.is_empty()) {
+        clauses.push("provider = ?");
+        values.push(Value::Text(provider.to_ascii_lowercase()));
+    }
+    if let Some(client) = query.client.as_ref().filter(|v| !v.is_empty()) {
+        clauses.push("client = ?");
+        values.push(Value::Text(client.clone()));
+    }
+    if let Some(method) = query.method.as_ref().filter(|v| !v.is_empty()) {
+        clauses.push("method = ?");
+        values.push(Value::Text(method.to_ascii_uppercase()));
+    }
+    if let Some(prefix) = query.path_prefix.as_ref().filter(|v| !v.is_empty()) {
+        clauses.push("substr(path, 1, length(?)) = ?");
+        values.push(Value::Text(prefix.clone()));
+        values.push(Value::Text(prefix.clone()));
+    }
+    if let Some(fingerprint) = query.key_fingerprint.as_ref().filter(|v| !v.is_empty()) {
+        clauses.push("key_fingerprint = ?");
+        values.push(Value::Text(fingerprint.clone()));
+    }
+    if let Some(status) = query.status {
+        clauses.push("status = ?");
+        values.push(Value::Integer(status as i64));
+    }
+    if query.errors_only {
+        clauses.push("status >= 400");
+    }
+    if let Some(since) = query.since_ts {
+        clauses.push("ts >= ?");
+        values.push(Value::Integer(since as i64));
+    }
+    if let Some(until) = query.until_ts {
+        clauses.push("ts <= ?");
+        values.push(Value::Integer(until as i64));
+    }
+
+    if clauses.is_empty() {
+        return (String::new(), values);
+    }
+    (format!(" WHERE {}", clauses.join(" AND ")), values)
+}
+
+fn query_page(conn: &Connection, query: &HistoryQuery) -> rusqlite::Result<HistoryPage> {
+    let (filter, values) = build_filter(query);
+    let limit = query
+        .limit
+        .unwrap_or(DEFAULT_PAGE_SIZE)
+        .clamp(1, MAX_PAGE_SIZE);
+    let offset = query.offset.unwrap_or(0);
+
+    let total: i64 = conn.query_row(
+        &format!("SELECT COUNT(*) FROM requests{}", filter),
+        params_from_iter(values.iter()),
+        |row| row.get(0),
+    )?;
+
+    let mut page_values = values.clone();
+    page_values.push(Value::Integer(limit as i64));
+    page_values.push(Value::Integer(offset as i64));
+    let mut stmt = conn.prepare(&format!(
+        "SELECT ts, request_id, provider, client, method, path, status, key_index,
+            key_fingerprint, retries, latency_ms, request_bytes, response_bytes
+         FROM requests{} ORDER BY ts DESC, id DESC LIMIT ? OFFSET ?",
+        filter
+    ))?;
+    let items = stmt
+        .query_map(params_from_iter(page_values.iter()), |row| {
+            Ok(RequestRecord {
+                ts: row.get::<_, i64>(0)? as u64,
+                request_id: row.get(1)?,
+                provider: row.get(2)?,
+                client: row.get(3)?,
+                method: row.get(4)?,
+                path: row.get(5)?,
+                status: row.get(6)?,
+                key_index: row.get::<_, Option<i64>>(7)?.map(|v| v as usize),
+                key_fingerprint: row.get(8)?,
+                retries: row.get::<_, i64>(9)? as usize,
+                latency_ms: row.get::<_, i64>(10)? as u64,
+                request_bytes: row.get::<_, i64>(11)? as u64,
+                response_bytes: row.get::<_, i64>(12)? as u64,
+            })
+        })?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+
+    Ok(HistoryPage {
+        total: total as u64,
+        limit,
+        offset,
+        items,
+    })
+}
+
+fn query_stats(conn: &Connection, query: &HistoryQuery) -> rusqlite::Result<HistoryStats> {
+    let (filter, values) = build_filter(query);
+
+    let (total, errors, retries, avg_latency_ms, max_latency_ms, request_bytes, response_bytes) =
+        conn.query_row(
+            &format!(
+                "SELECT COUNT(*),
+                    COALESCE(SUM(CASE WHEN status >= 400 THEN 1 ELSE 0 END), 0),
+                    COALESCE(SUM(retries), 0),
+                    COALESCE(AVG(latency_ms), 0.0),
+                    COALESCE(MAX(latency_ms), 0),
+                    COALESCE(SUM(request_bytes), 0),
+                    COALESCE(SUM(response_bytes), 0)
+                 FROM requests{}",
+                filter
+            ),
+            params_from_iter(values.iter()),
+            |row| {
+                Ok((
+                    row.get::<_, i64>(0)? as u64,
+                    row.get::<_, i64>(1)? as u64,
+                    row.get::<_, i64>(2)? as u64,
+                    row.get::<_, f64>(3)?,
+                    row.get::<_, i64>(4)? as u64,
+                    row.get::<_, i64>(5)? as u64,
+                    row.get::<_, i64>(6)? as u64,
+                ))
+            },
+        )?;
+
+    let mut stmt = conn.prepare(&format!(
+        "SELECT status, COUNT(*) FROM requests{} GROUP BY status ORDER BY status",
+        filter
+    ))?;
+    let by_status = stmt
+        .query_map(params_from_iter(values.iter()), |row| {
+            Ok(StatusCount {
+                status: row.get(0)?,
+                count: row.get::<_, i64>(1)? as u64,
+            })
+        })?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+
+    let key_filter = if filter.is_empty() {
+        " WHERE key_fingerprint IS NOT NULL".to_string()
+    } else {
+        format!("{} AND key_fingerprint IS NOT NULL", filter)
+    };
+    let mut stmt = conn.prepare(&format!(
+        "SELECT provider, key_fingerprint, COUNT(*),
+            SUM(CASE WHEN status >= 400 THEN 1 ELSE 0 END), SUM(retries)
+         FROM requests{} GROUP BY provider, key_fingerprint ORDER BY COUNT(*) DESC",
+        key_filter
+    ))?;
+    let by_key = stmt
+        .query_map(params_from_iter(values.iter()), |row| {
+            Ok(KeyUsage {
+                provider: row.get(0)?,
+                key_fingerprint: row.get(1)?,
+                requests: row.get::<_, i64>(2)? as u64,
+                errors: row.get::<_, i64>(3)? as u64,
+                retries: row.get::<_, i64>(4)? as u64,
+            })
+        })?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+
+    Ok(HistoryStats {
+        total,
+        errors,
+        retries,
+        avg_latency_ms,
+        max_latency_ms,
+        request_bytes,
+        response_bytes,
+        by_status,
+        by_key,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn memory_db() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(SCHEMA).unwrap();
+        conn
+    }
+
+    fn record(
+        ts: u64,
+        provider: &str,
+        path: &str,
+        status: u16,
+        key: Option<&str>,
+    ) -> RequestRecord {
+        RequestRecord {
+            ts,
+            request_id: format!("req-{}", ts),
+            provider: provider.to_string(),
+            client: "127.0.0.1:50000".to_string(),
+            method: "POST".to_string(),
+            path: path.to_string(),
+            status,
+            key_index: key.map(|_| 1),
+            key_fingerprint: key.map(str::to_string),
+            retries: if status == 429 { 1 } else { 0 },
+            latency_ms: 100,
+            request_bytes: 10,
+            response_bytes: 20,
+        }
+    }
+
+    #[test]
+    fn query_filters_and_pages_newest_first() {
+        let conn = memory_db();
+        insert(
+            &conn,
+            &record(100, "firecrawl", "/v1/scrape", 200, Some("aaa")),
+        )
+        .unwrap();
+        insert(
+            &conn,
+            &record(200, "firecrawl", "/v1/crawl", 429, Some("bbb")),
+        )
+        .unwrap();
+        insert(&conn, &record(300, "tavily", "/search", 502, Some("ccc"))).unwrap();
+        insert(
+            &conn,
+            &record(400, "firecrawl", "/v2/scrape", 500, Some("aaa")),
+        )
+        .unwrap();
+
+        let page = query_page(
+            &conn,
+            &HistoryQuery {
+                provider: Some("Firecrawl".to_string()),
+                errors_only: true,
+                limit: Some(1),
+                ..HistoryQuery::default()
+            },
+        )
+        .unwrap();
+        assert_eq!(page.total, 2);
+        assert_eq!(page.items.len(), 1);
+        assert_eq!(page.items[0].ts, 400);
+
+        let page = query_page(
+            &conn,
+            &HistoryQuery {
+                path_prefix: Some("/v1/".to_string()),
+                since_ts: Some(150),
+                ..HistoryQuery::default()
+            },
+        )
+        .unwrap();
+        assert_eq!(page.total, 1);
+        assert_eq!(page.items[0].path, "/v1/crawl");
+    }
+
+    #[test]
+    fn stats_group_by_status_and_key() {
+        let conn = memory_db();
+        insert(
+            &conn,
+            &record(100, "firecrawl", "/v1/scrape", 200, Some("aaa")),
+        )
+        .unwrap();
+        insert(
+            &conn,
+            &record(200, "firecrawl", "/v1/scrape", 429, Some("aaa")),
+        )
+        .unwrap();
+        insert(&conn, &record(300, "firecrawl", "/v1/scrape", 401, None)).unwrap();
+
+        let stats = query_stats(&conn, &HistoryQuery::default()).unwrap();
+        assert_eq!(stats.total, 3);
+        assert_eq!(stats.errors, 2);
+        assert_eq!(stats.retries, 1);
+        assert_eq!(stats.by_status.len(), 3);
+        assert_eq!(stats.by_key.len(), 1);
+        assert_eq!(stats.by_key[0].requests, 2);
+        assert_eq!(stats.by_key[0].errors, 1);
+    }
+
+    #[test]
+    fn prune_applies_age_and_row_limits() {
+        let conn = memory_db();
+        for ts in [10, 1_000_000, 1_000_001, 1_000_002] {
+            insert(&conn, &record(ts, "tavily", "/search", 200, None)).unwrap();
+        }
+        let retention = HistoryRetention {
+            max_age_days: 1,
+            max_rows: 2,
+        };
+        prune(&conn, retention, 1_000_010).unwrap();
+
+        let page = query_page(&conn, &HistoryQuery::default()).unwrap();
+        let kept: Vec<u64> = page.items.iter().map(|r| r.ts).collect();
+        assert_eq!(kept, vec![1_000_002, 1_000_001]);
+    }
+}
@@ -0,0 +1,451 @@
+//! Bulk key import from the files the registration bots write: plain key
+//! lists, `email:password:key` / `email|password|key` account lines, CSV and
+//! JSON. Providers are told apart by key prefix, falling back to the provider
+//! named in the file name or picked by the user.
+
+use std::path::Path;
+
+use serde::Serialize;
+use serde_json::Value;
+use tracing::warn;
+
+use crate::keys::{edit_provider_keys, KeyRecord, KeySource};
+use crate::secrets::mask;
+use crate::{now_ts, AppState, PROVIDERS};
+
+const KEY_PREFIXES: [(&str, &str); 2] = [("fc-", "firecrawl"), ("tvly-", "tavily")];
+/// Field names that hold the key in CSV headers and JSON objects.
+const KEY_FIELDS: [&str; 5] = ["key", "apikey", "api_key", "api key", "value"];
+/// Field names used as the key's label, in order of preference.
+const LABEL_FIELDS: [&str; 4] = ["label", "email", "account", "name"];
+const MIN_KEY_LEN: usize = 8;
+const MAX_KEY_LEN: usize = 256;
+
+/// One entry of an import report. `key` is masked.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ImportedKey {
+    pub(crate) file: String,
+    /// 1-based line of the entry; 0 when the whole file was rejected.
+    pub(crate) line: usize,
+    pub(crate) provider: Option<&'static str>,
+    pub(crate) key: String,
+    pub(crate) reason: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct KeyImportReport {
+    pub(crate) added: Vec<ImportedKey>,
+    /// Well-formed keys that were not added, e.g. duplicates.
+    pub(crate) skipped: Vec<ImportedKey>,
+    /// Entries that did not hold a usable key.
+    pub(crate) invalid: Vec<ImportedKey>,
+}
+
+struct FoundKey {
+    file: String,
+    line: usize,
+    provider: &'static str,
+    value: String,
+    label: String,
+}
+
+/// Keys found in a set of files, waiting to be merged into the config.
+pub(crate) struct KeyImport {
+    found: Vec<FoundKey>,
+    report: KeyImportReport,
+}
+
+impl KeyImport {
+    /// Parses `(file name, contents)` pairs. `provider` is used for keys
+    /// without a known prefix in files whose name names no provider.
+    pub(crate) fn scan(files: &[(String, String)], provider: Option<&'static str>) -> Self {
+        let mut import = Self {
+            found: Vec::new(),
+            report: KeyImportReport::default(),
+        };
+        for (name, text) in files {
+            let hint = provider_from_file_name(name).or(provider);
+            for entry in parse_entries(text) {
+                import.classify(name, entry, hint);
+            }
+        }
+        import
+    }
+
+    /// Providers that have keys waiting to be merged.
+    pub(crate) fn providers(&self) -> Vec<&'static str> {
+        PROVIDERS
+            .into_iter()
+            .filter(|provider| self.found.iter().any(|key| key.provider == *provider))
+            .collect()
+    }
+
+    /// Appends the provider's new keys to `keys`; keys it already has, or
+    /// that appear twice in the import, are reported as skipped.
+    pub(crate) fn merge_into(&mut self, provider: &'static str, keys: &mut Vec<KeyRecord>) {
+        let added_at = now_ts();
+        let (found, rest): (Vec<FoundKey>, Vec<FoundKey>) = std::mem::take(&mut self.found)
+            .into_iter()
+            .partition(|key| key.provider == provider);
+        self.found = rest;
+        for key in found {
+            if keys.iter().any(|existing| existing.value == key.value) {
+                self.report.skipped.push(key.entry("already configured"));
+                continue;
+            }
+            keys.push(KeyRecord {
+                value: key.value.clone(),
+                label: key.label.clone(),
+                added_at,
+                source: KeySource::Import,
+                ..KeyRecord::default()
+            });
+            self.report.added.push(key.entry(""));
+        }
+    }
+
+    /// Reports the provider's keys as skipped because they could not be saved.
+    pub(crate) fn reject(&mut self, provider: &'static str, reason: &str) {
+        let (found, rest): (Vec<FoundKey>, Vec<FoundKey>) = std::mem::take(&mut self.found)
+            .into_iter()
+            .partition(|key| key.provider == provider);
+        self.found = rest;
+        self.report
+            .skipped
+            .extend(found.into_iter().map(|key| key.entry(reason)));
+    }
+
+    /// Reports keys `skip` matches as skipped instead of merging them.
+    pub(crate) fn skip_keys(&mut self, skip: impl Fn(&str) -> bool, reason: &str) {
+        let (skipped, found): (Vec<FoundKey>, Vec<FoundKey>) = std::mem::take(&mut self.found)
+            .into_iter()
+            .partition(|key| skip(&key.value));
+        self.found = found;
+        self.report
+            .skipped
+            .extend(skipped.into_iter().map(|key| key.entry(reason)));
+    }
+
+    pub(crate) fn finish(self) -> KeyImportReport {
+        self.report
+    }
+
+    /// Merges the keys into the config and the running key managers; keys of
+    /// a provider whose keys cannot be edited are reported as skipped.
+    pub(crate) async fn apply(mut self, state: &AppState, config_path: &Path) -> KeyImportReport {
+        for provider in self.providers() {
+            let added_before = self.report.added.len();
+            let result = edit_provider_keys(state, config_path, provider, |keys| {
+                self.merge_into(provider, keys);
+                Ok(())
+            })
+            .await;
+            if let Err(err) = result {
+                warn!(provider, error = %err, "Failed to import keys");
+                // Keys merged before the save failed were never stored.
+                let unsaved = self.report.added.split_off(added_before);
+                self.report
+                    .skipped
+                    .extend(unsaved.into_iter().map(|key| ImportedKey {
+                        reason: err.clone(),
+                        ..key
+                    }));
+                self.reject(provider, &err);
+            }
+        }
+        self.finish()
+    }
+
+    fn classify(&mut self, file: &str, entry: Entry, hint: Option<&'static str>) {
+        let invalid = |reason: &str, key: &str| ImportedKey {
+            file: file.to_string(),
+            line: entry.line,
+            provider: None,
+            key: mask(key),
+            reason: reason.to_string(),
+        };
+        let Some(value) = entry.key else {
+            self.report
+                .invalid
+                .push(invalid(entry.problem.unwrap_or("no API key found"), ""));
+            return;
+        };
+        if !is_plausible_key(&value) {
+            self.report
+                .invalid
+                .push(invalid("not a valid API key", &value));
+            return;
+        }
+        let Some(provider) = provider_from_prefix(&value).or(hint) else {
+            self.report.invalid.push(invalid(
+                "unknown provider; choose one for this file",
+                &value,
+            ));
+            return;
+        };
+        let key = FoundKey {
+            file: file.to_string(),
+            line: entry.line,
+            provider,
+            value,
+            label: entry.label,
+        };
+        if self
+            .found
+            .iter()
+            .any(|other| other.provider == provider && other.value == key.value)
+        {
+            self.report.skipped.push(key.entry("duplicate in import"));
+            return;
+        }
+        self.found.push(key);
+    }
+}
+
+impl FoundKey {
+    fn entry(&self, reason: &str) -> ImportedKey {
+        ImportedKey {
+            file: self.file.clone(),
+            line: self.line,
+            provider: Some(self.provider),
+            key: mask(&self.value),
+            reason: reason.to_string(),
+        }
+    }
+}
+
+/// Imports the files into the running config, one provider at a time, and
+/// applies the new keys to running providers.
+pub(crate) async fn import_keys(
+    state: &AppState,
+    config_path: &Path,
+    files: &[(String, String)],
+    provider: Option<&'static str>,
+) -> KeyImportReport {
+    KeyImport::scan(files, provider)
+        .apply(state, config_path)
+        .await
+}
+
+/// A key (or the lack of one) found at a line of a file.
+struct Entry {
+    line: usize,
+    key: Option<String>,
+    label: String,
+    problem: Option<&'static str>,
+}
+
+fn parse_entries(text: &str) -> Vec<Entry> {
+    let trimmed = text.trim_start_matches('\u{feff}').trim();
+    if trimmed.starts_with('[') || trimmed.starts_with('{') {
+        return match serde_json::from_str::<Value>(trimmed) {
+            Ok(value) => {
+                let mut entries = Vec::new();
+                collect_json(&value, &mut entries);
+                entries
+            }
+            Err(_) => vec![Entry {
+                line: 0,
+                key: None,
+                label: String::new(),
+                problem: Some("file looks like JSON but does not parse"),
+            }],
+        };
+    }
+
+    let mut lines = text
+        .lines()
+        .enumerate()
+        .map(|(i, line)| (i + 1, line.trim()))
+        .filter(|(_, line)| !line.is_empty() && !line.starts_with('#'))
+        .peekable();
+    let header = lines.peek().and_then(|(_, line)| csv_header(line));
+    if header.is_some() {
+        lines.next();
+    }
+    lines
+        .map(|(line_no, line)| match &header {
+            Some((key_col, label_col)) => {
+                let fields = split_fields(line);
+                Entry {
+                    line: line_no,
+                    key: fields.get(*key_col).map(|f| f.to_string()),
+                    label: label_col
+                        .and_then(|col| fields.get(col))
+                        .map(|f| f.to_string())
+                        .unwrap_or_default(),
+                    problem: None,
+                }
+            }
+            None => parse_line(line_no, line),
+        })
+        .collect()
+}
+
+/// Plain keys, account lines and header-less CSV rows: the key is the field
+/// with a known prefix, else the only field, else the last one.
+fn parse_line(line_no: usize, line: &str) -> Entry {
+    let fields = split_fields(line);
+    let label = fields
+        .iter()
+        .find(|f| f.contains('@'))
+        .map(|f| f.to_string())
+        .unwrap_or_default();
+    let key = match fields.iter().find(|f| provider_from_prefix(f).is_some()) {
+        Some(key) => Some(key.to_string()),
+        None if fields.len() == 1 => Some(fields[0].to_string()),
+        None if fields.len() == 2 && !label.is_empty() => None,
+        None => fields.last().map(|f| f.to_string()),
+    };
+    Entry {
+        line: line_no,
+        problem: key.is_none().then_some("account line without an API key"),
+        key,
+        label,
+    }
+}
+
+fn split_fields(line: &str) -> Vec<&str> {
+    line.split(['|', ',', ';', '\t', ':'])
+        .map(|field| field.trim().trim_matches('"'))
+        .filter(|field| !field.is_empty())
+        .collect()
+}
+
+/// Columns of the key and the label when `line` is a CSV header.
+fn csv_header(line: &str) -> Option<(usize, Option<usize>)> {
+    let columns: Vec<String> = split_fields(line)
+        .into_iter()
+        .map(str::to_ascii_lowercase)
+        .collect();
+    let key = columns
+        .iter()
+        .position(|c| KEY_FIELDS.contains(&c.as_str()))?;
+    let label = LABEL_FIELDS
+        .iter()
+        .find_map(|field| columns.iter().position(|c| c == field));
+    Some((key, label))
+}
+
+/// Strings in arrays are keys; objects give their key and label fields, or
+/// are searched for nested keys when they have none.
+fn collect_json(value: &Value, entries: &mut Vec<Entry>) {
+    match value {
+        Value::String(key) => entries.push(Entry {
+            line: entries.len() + 1,
+            key: Some(key.clone()),
+            label: String::new(),
+            problem: None,
+        }),
+        Value::Array(items) => items.iter().for_each(|item| collect_json(item, entries)),
+        Value::Object(fields) => {
+            let field = |names: &[&str]| {
+                fields.iter().find_map(|(name, value)| {
+                    names
+                        .contains(&name.to_ascii_lowercase().as_str())
+                        .then(|| value.as_str())
+                        .flatten()
+                })
+            };
+            match field(&KEY_FIELDS) {
+                Some(key) => entries.push(Entry {
+                    line: entries.len() + 1,
+                    key: Some(key.to_string()),
+                    label: field(&LABEL_FIELDS).unwrap_or_default().to_string(),
+                    problem: None,
+                }),
+                None => fields
+                    .values()
+                    .filter(|value| value.is_array() || value.is_object())
+                    .for_each(|value| collect_json(value, entries)),
+            }
+        }
+        _ => {}
+    }
+}
+
+fn is_plausible_key(key: &str) -> bool {
+    (MIN_KEY_LEN..=MAX_KEY_LEN).contains(&key.len())
+        && key.chars().all(|c| c.is_ascii_graphic())
+        && !key.contains('@')
+}
+
+fn provider_from_prefix(key: &str) -> Option<&'static str> {
+    KEY_PREFIXES
+        .iter()
+        .find(|(prefix, _)| key.starts_with(prefix))
+        .map(|(_, provider)| *provider)
+}
+
+fn provider_from_file_name(name: &str) -> Option<&'static str> {
+    let name = name.to_ascii_lowercase();
+    PROVIDERS
+        .into_iter()
+        .find(|provider| name.contains(provider))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::keys::key_values;
+
+    fn scan(name: &str, text: &str, provider: Option<&'static str>) -> KeyImport {
+        KeyImport::scan(&[(name.to_string(), text.to_string())], provider)
+    }
+
+    #[test]
+    fn reads_bot_output_formats() {
+        let accounts = scan(
+            "accounts.txt",
+            "a@mail.tm:pw1:fc-1111111111\nb@mail.tm|pw2|tvly-2222222222\nc@mail.tm:pw3\n",
+            None,
+        );
+        assert_eq!(accounts.providers(), vec!["firecrawl", "tavily"]);
+        assert_eq!(accounts.found[0].label, "a@mail.tm");
+        assert_eq!(accounts.report.invalid.len(), 1);
+        assert_eq!(accounts.report.invalid[0].line, 3);
+
+        let csv = scan(
+            "export.csv",
+            "email,api_key\nd@mail.tm,fc-3333333333\n",
+            None,
+        );
+        assert_eq!(csv.found[0].value, "fc-3333333333");
+        assert_eq!(csv.found[0].label, "d@mail.tm");
+
+        let json = scan(
+            "keys.json",
+            r#"{"tavily": ["tvly-4444444444", {"apiKey": "tvly-5555555555", "label": "team"}]}"#,
+            None,
+        );
+        assert_eq!(json.found.len(), 2);
+        assert_eq!(json.found[1].label, "team");
+        assert_eq!(scan("broken.json", "[\"fc-", None).report.invalid.len(), 1);
+    }
+
+    #[test]
+    fn unprefixed_keys_need_a_provider_and_duplicates_are_skipped() {
+        let text = "custom-key-0001\nfc-6666666666\nfc-6666666666\nshort\n";
+        let unknown = scan("keys.txt", text, None);
+        assert_eq!(unknown.report.invalid.len(), 2);
+        assert_eq!(unknown.report.skipped[0].reason, "duplicate in import");
+
+        let mut import = scan("tavily_keys.txt", text, None);
+        assert_eq!(import.providers(), vec!["firecrawl", "tavily"]);
+        let mut tavily = vec![KeyRecord::from("custom-key-0001")];
+        import.merge_into("tavily", &mut tavily);
+        let mut firecrawl = Vec::new();
+        import.merge_into("firecrawl", &mut firecrawl);
+        assert_eq!(key_values(&firecrawl), vec!["fc-6666666666"]);
+        assert_eq!(firecrawl[0].source, KeySource::Import);
+
+        let report = import.finish();
+        assert_eq!(report.added.len(), 1);
+        assert_eq!(report.skipped.len(), 2);
+        assert_eq!(report.skipped[1].reason, "already configured");
+        assert_eq!(report.invalid.len(), 1);
+        assert!(!report.added[0].key.contains("6666666666"));
+    }
+}
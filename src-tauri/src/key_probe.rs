@@ -0,0 +1,348 @@
+//! Key validation: one cheap authenticated request per key against the
+//! configured upstream, classified so dead or spent keys can be found before
+//! real traffic reaches them.
+
+use std::path::Path;
+use std::sync::Arc;
+
+use reqwest::{Client, StatusCode};
+use serde::Serialize;
+use serde_json::Value;
+use tokio::sync::Semaphore;
+use tokio::task::JoinSet;
+use tracing::{info, warn};
+
+use crate::events::ProxyEvent;
+use crate::keys::{edit_provider_keys, KeyRecord};
+use crate::secrets::mask;
+use crate::{build_http_client, key_fingerprint, AppState};
+
+pub(crate) const DEFAULT_PROBE_CONCURRENCY: usize = 4;
+pub(crate) const MAX_PROBE_CONCURRENCY: usize = 32;
+/// Tavily answers 432/433 when a key or its plan is over its credit limit.
+const TAVILY_LIMIT_STATUS_CODES: [u16; 2] = [432, 433];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) enum ProbeOutcome {
+    Valid,
+    /// Upstream rejected the key.
+    Invalid,
+    /// The key works but has no credits left.
+    Exhausted,
+    RateLimited,
+    /// The probe itself failed (network, timeout, unexpected status); says
+    /// nothing about the key.
+    Error,
+}
+
+impl ProbeOutcome {
+    pub(crate) fn label(self) -> &'static str {
+        match self {
+            Self::Valid => "valid",
+            Self::Invalid => "invalid",
+            Self::Exhausted => "exhausted",
+            Self::RateLimited => "rate-limited",
+            Self::Error => "error",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct KeyProbeResult {
+    pub(crate) fingerprint: String,
+    /// Masked.
+    pub(crate) key: String,
+    pub(crate) label: String,
+    pub(crate) outcome: ProbeOutcome,
+    pub(crate) status: Option<u16>,
+    pub(crate) detail: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct KeyProbeReport {
+    pub(crate) provider: &'static str,
+    pub(crate) results: Vec<KeyProbeResult>,
+    /// Invalid keys removed from the config.
+    pub(crate) removed: usize,
+}
+
+impl KeyProbeReport {
+    pub(crate) fn invalid_keys(&self) -> impl Iterator<Item = &KeyProbeResult> {
+        self.results
+            .iter()
+            .filter(|result| result.outcome == ProbeOutcome::Invalid)
+    }
+}
+
+/// A request that needs a valid key but spends no credits.
+fn probe_url(provider: &str, upstream_base_url: &str) -> String {
+    if provider == "tavily" {
+        format!("{}/usage", upstream_base_url)
+    } else {
+        format!("{}/v1/team/credit-usage", upstream_base_url)
+    }
+}
+
+/// Classifies a probe response. Successful responses are checked for a spent
+/// credit balance.
+fn classify(status: StatusCode, body: &Value) -> (ProbeOutcome, String) {
+    let code = status.as_u16();
+    match code {
+        200..=299 => match remaining_credits(body) {
+            Some(remaining) if remaining <= 0.0 => {
+                (ProbeOutcome::Exhausted, "no credits left".to_string())
+            }
+            Some(remaining) => (ProbeOutcome::Valid, format!("{} credits left", remaining)),
+            None => (ProbeOutcome::Valid, String::new()),
+        },
+        401 | 403 => (ProbeOutcome::Invalid, "rejected by upstream".to_string()),
+        402 => (ProbeOutcome::Exhausted, "payment required".to_string()),
+        429 => (ProbeOutcome::RateLimited, "rate limited".to_string()),
+        code if TAVILY_LIMIT_STATUS_CODES.contains(&code) => {
+            (ProbeOutcome::Exhausted, "usage limit exceeded".to_string())
+        }
+        _ => (ProbeOutcome::Error, format!("unexpected status {}", code)),
+    }
+}
+
+/// Credits left according to Firecrawl's `data.remaining_credits` or
+/// Tavily's `key.limit - key.usage`; `None` when the body says neither.
+fn remaining_credits(body: &Value) -> Option<f64> {
+    if let Some(remaining) = body
+        .pointer("/data/remaining_credits")
+        .or_else(|| body.pointer("/data/remainingCredits"))
+        .and_then(Value::as_f64)
+    {
+        return Some(remaining);
+    }
+    let key = body.get("key")?;
+    let limit = key.get("limit").and_then(Value::as_f64)?;
+    let usage = key.get("usage").and_then(Value::as_f64).unwrap_or(0.0);
+    Some(limit - usage)
+}
+
+async fn probe_key(
+    client: &Client,
+    url: &str,
+    provider: &str,
+    key: &str,
+) -> (ProbeOutcome, Option<u16>, String) {
+    let mut request = client.get(url).bearer_auth(key);
+    if provider == "tavily" {
+        request = request.header("x-api-key", key);
+    }
+    match request.send().await {
+        Ok(response) => {
+            let status = response.status();
+            let body = response.json::<Value>().await.unwrap_or(Value::Null);
+            let (outcome, detail) = classify(status, &body);
+            (outcome, Some(status.as_u16()), detail)
+        }
+        Err(err) => (ProbeOutcome::Error, None, err.to_string()),
+    }
+}
+
+/// Probes `keys` with at most `concurrency` requests in flight and returns
+/// the results in key order.
+pub(crate) async fn probe_key_list(
+    client: &Client,
+    provider: &'static str,
+    upstream_base_url: &str,
+    keys: &[KeyRecord],
+    concurrency: usize,
+) -> Vec<KeyProbeResult> {
+    let url = Arc::new(probe_url(provider, upstream_base_url));
+    let permits = Arc::new(Semaphore::new(concurrency.clamp(1, MAX_PROBE_CONCURRENCY)));
+    let mut probes = JoinSet::new();
+    for (index, key) in keys.iter().enumerate() {
+        let (client, url, permits, value) = (
+            client.clone(),
+            url.clone(),
+            permits.clone(),
+            key.value.clone(),
+        );
+        probes.spawn(async move {
+            let _permit = permits.acquire_owned().await;
+            (index, probe_key(&client, &url, provider, &value).await)
+        });
+    }
+
+    let mut results: Vec<Option<KeyProbeResult>> = vec![None; keys.len()];
+    while let Some(joined) = probes.join_next().await {
+        let Ok((index, (outcome, status, detail))) = joined else {
+            continue;
+        };
+        let key = &keys[index];
+        results[index] = Some(KeyProbeResult {
+            fingerprint: key_fingerprint(&key.value),
+            key: mask(&key.value),
+            label: key.label.clone(),
+            outcome,
+            status,
+            detail,
+        });
+    }
+    results.into_iter().flatten().collect()
+}
+
+/// Probes a provider's enabled keys, feeds the results to its running key
+/// manager, and removes invalid keys from the config when asked to.
+pub(crate) async fn probe_provider_keys(
+    state: &AppState,
+    config_path: &Path,
+    provider: &'static str,
+    concurrency: usize,
+    remove_invalid: bool,
+) -> Result<KeyProbeReport, String> {
+    let (client, upstream, keys) = {
+        let config = state.config.read().await;
+        (
+            build_http_client(&config)?,
+            config.provider_upstream(provider).to_string(),
+            config.enabled_keys(provider),
+        )
+    };
+    if upstream.is_empty() {
+        return Err(format!("No upstream URL is configured for {}", provider));
+    }
+    let results = probe_key_list(&client, provider, &upstream, &keys, concurrency).await;
+    let mut report = KeyProbeReport {
+        provider,
+        results,
+        removed: 0,
+    };
+
+    let manager = state
+        .active_key_managers
+        .lock()
+        .await
+        .slot_mut(provider)
+        .clone();
+    if let Some(manager) = manager {
+        let mut manager = manager.lock().await;
+        for key in &keys {
+            let fingerprint = key_fingerprint(&key.value);
+            let Some(result) = report.results.iter().find(|r| r.fingerprint == fingerprint) else {
+                continue;
+            };
+            if let Some(index) = manager.record_probe(&key.value, result.outcome) {
+                state.events.publish(ProxyEvent::KeyState(
+                    manager.key_state_change(provider, index),
+                ));
+            }
+        }
+    }
+
+    let invalid: Vec<String> = report
+        .invalid_keys()
+        .map(|result| result.fingerprint.clone())
+        .collect();
+    if remove_invalid && !invalid.is_empty() {
+        edit_provider_keys(state, config_path, provider, |keys| {
+            keys.retain(|key| !invalid.contains(&key_fingerprint(&key.value)));
+            Ok(())
+        })
+        .await?;
+        report.removed = invalid.len();
+    }
+
+    let count = |outcome| {
+        report
+            .results
+            .iter()
+            .filter(|result| result.outcome == outcome)
+            .count()
+    };
+    info!(
+        provider,
+        valid = count(ProbeOutcome::Valid),
+        invalid = count(ProbeOutcome::Invalid),
+        exhausted = count(ProbeOutcome::Exhausted),
+        rate_limited = count(ProbeOutcome::RateLimited),
+        errors = count(ProbeOutcome::Error),
+        removed = report.removed,
+        "Keys validated"
+    );
+    if count(ProbeOutcome::Error) == report.results.len() && !report.results.is_empty() {
+        warn!(provider, "No key probe reached the upstream");
+    }
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::HeaderMap;
+    use axum::routing::get;
+    use axum::{Json, Router};
+    use serde_json::json;
+
+    #[test]
+    fn classifies_statuses_and_credit_balances() {
+        let ok = StatusCode::OK;
+        let firecrawl = json!({ "success": true, "data": { "remaining_credits": 0 } });
+        let tavily = json!({ "key": { "usage": 120, "limit": 1000 } });
+        assert_eq!(classify(ok, &firecrawl).0, ProbeOutcome::Exhausted);
+        assert_eq!(classify(ok, &tavily).0, ProbeOutcome::Valid);
+        assert_eq!(classify(ok, &tavily).1, "880 credits left");
+        assert_eq!(classify(ok, &Value::Null).0, ProbeOutcome::Valid);
+        assert_eq!(
+            classify(StatusCode::UNAUTHORIZED, &Value::Null).0,
+            ProbeOutcome::Invalid
+        );
+        assert_eq!(
+            classify(StatusCode::from_u16(432).unwrap(), &Value::Null).0,
+            ProbeOutcome::Exhausted
+        );
+        assert_eq!(
+            classify(StatusCode::TOO_MANY_REQUESTS, &Value::Null).0,
+            ProbeOutcome::RateLimited
+        );
+        assert_eq!(
+            classify(StatusCode::BAD_GATEWAY, &Value::Null).0,
+            ProbeOutcome::Error
+        );
+    }
+
+    #[tokio::test]
+    async fn probes_each_key_against_the_upstream() {
+        async fn credit_usage(headers: HeaderMap) -> (StatusCode, Json<Value>) {
+            let auth = headers
+                .get("authorization")
+                .and_then(|v| v.to_str().ok())
+                .unwrap_or_default();
+            match auth {
+                "Bearer fc-good" => (
+                    StatusCode::OK,
+                    Json(json!({ "data": { "remaining_credits": 500 } })),
+                ),
+                "Bearer fc-limited" => (StatusCode::TOO_MANY_REQUESTS, Json(Value::Null)),
+                _ => (StatusCode::UNAUTHORIZED, Json(Value::Null)),
+            }
+        }
+        let app = Router::new().route("/v1/team/credit-usage", get(credit_usage));
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let upstream = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await });
+
+        let keys: Vec<KeyRecord> = ["fc-good", "fc-bad", "fc-limited"]
+            .into_iter()
+            .map(KeyRecord::from)
+            .collect();
+        let results = probe_key_list(&Client::new(), "firecrawl", &upstream, &keys, 2).await;
+        let outcomes: Vec<ProbeOutcome> = results.iter().map(|r| r.outcome).collect();
+        assert_eq!(
+            outcomes,
+            vec![
+                ProbeOutcome::Valid,
+                ProbeOutcome::Invalid,
+                ProbeOutcome::RateLimited
+            ]
+        );
+        assert_eq!(results[1].status, Some(401));
+        assert!(!results[0].key.contains("fc-good"));
+    }
+}
@@ -0,0 +1,259 @@
+//! Optional key drop folder. `KEY_WATCH_DIR` is polled for new or changed
+//! key files (the `firecrawl_bot` outputs, or anything the bulk import
+//! reads) and keys never seen before are added to the live key pools.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+use tracing::{debug, info, warn};
+
+use crate::config_schema::write_atomic;
+use crate::key_import::KeyImport;
+use crate::keys::load_removed_keys;
+use crate::{key_fingerprint, AppState, PROVIDERS};
+
+pub(crate) const KEY_WATCH_STATE_FILE: &str = "key-watch-state.json";
+const WATCHED_EXTENSIONS: [&str; 3] = ["txt", "csv", "json"];
+/// Files modified more recently than this may still be being written and
+/// are left for the next poll.
+const SETTLE_TIME: Duration = Duration::from_secs(2);
+/// How often a disabled watcher checks whether it was turned on.
+const DISABLED_POLL: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct FileStamp {
+    len: u64,
+    modified_ms: u64,
+}
+
+/// What the watcher remembers between polls and restarts.
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+struct WatchState {
+    /// Fingerprints of every key that was configured while watching. Keys
+    /// removed by hand, or dropped after going bad, stay here and are never
+    /// re-added from the folder.
+    seen: BTreeSet<String>,
+    /// Files as they were when last read, by path.
+    files: BTreeMap<String, FileStamp>,
+}
+
+impl WatchState {
+    fn load(path: &Path) -> Self {
+        match fs::read_to_string(path) {
+            Ok(text) => serde_json::from_str(&text).unwrap_or_else(|e| {
+                warn!(error = %e, "Key watch state is unreadable; starting over");
+                Self::default()
+            }),
+            Err(_) => Self::default(),
+        }
+    }
+
+    fn save(&self, path: &Path) -> Result<(), String> {
+        let text = serde_json::to_string_pretty(self)
+            .map_err(|e| format!("Failed to serialize key watch state: {}", e))?;
+        write_atomic(path, &text).map_err(|e| format!("Failed to write key watch state: {}", e))
+    }
+
+    async fn remember_configured_keys(&mut self, state: &AppState) {
+        let config = state.config.read().await;
+        for provider in PROVIDERS {
+            self.seen.extend(
+                config
+                    .provider_keys(provider)
+                    .iter()
+                    .map(|key| key_fingerprint(&key.value)),
+            );
+        }
+    }
+}
+
+/// Polls the configured drop folder until the app exits. The folder and the
+/// interval are re-read from the config on every poll; `on_ingest` runs
+/// after keys were added.
+pub(crate) async fn watch_key_folder(
+    state: AppState,
+    config_path: PathBuf,
+    state_path: PathBuf,
+    on_ingest: impl Fn() + Send + 'static,
+) {
+    let mut watch = WatchState::load(&state_path);
+    loop {
+        let (dir, interval) = {
+            let config = state.config.read().await;
+            (
+                config.key_watch_dir.clone(),
+                Duration::from_secs(config.key_watch_interval_seconds.max(1)),
+            )
+        };
+        if dir.is_empty() {
+            tokio::time::sleep(DISABLED_POLL).await;
+            continue;
+        }
+        match poll_once(&state, &config_path, &mut watch, Path::new(&dir)).await {
+            Ok(0) => {}
+            Ok(_) => on_ingest(),
+            Err(err) => warn!(dir = %dir, error = %err, "Key drop folder poll failed"),
+        }
+        if let Err(err) = watch.save(&state_path) {
+            warn!(error = %err, "Failed to save key watch state");
+        }
+        tokio::time::sleep(interval).await;
+    }
+}
+
+/// Reads the files that changed since the last poll and adds their unseen
+/// keys. Returns how many keys were added.
+async fn poll_once(
+    state: &AppState,
+    config_path: &Path,
+    watch: &mut WatchState,
+    dir: &Path,
+) -> Result<usize, String> {
+    watch.remember_configured_keys(state).await;
+    let files = changed_files(watch, dir)?;
+    if files.is_empty() {
+        return Ok(0);
+    }
+
+    let removed = load_removed_keys(config_path);
+    let mut import = KeyImport::scan(&files, None);
+    import.skip_keys(
+        |key| {
+            let fingerprint = key_fingerprint(key);
+            watch.seen.contains(&fingerprint) || removed.contains(&fingerprint)
+        },
+        "seen before",
+    );
+    let report = import.apply(state, config_path).await;
+    watch.remember_configured_keys(state).await;
+
+    for key in &report.added {
+        info!(
+            provider = key.provider.unwrap_or_default(),
+            file = %key.file,
+            line = key.line,
+            key = %key.key,
+            "Key ingested from drop folder"
+        );
+    }
+    debug!(
+        files = files.len(),
+        skipped = report.skipped.len(),
+        invalid = report.invalid.len(),
+        "Key drop folder scanned"
+    );
+    Ok(report.added.len())
+}
+
+/// Watched files that changed since they were last read, as `(path, text)`,
+/// recording their new stamps in `watch`.
+fn changed_files(watch: &mut WatchState, dir: &Path) -> Result<Vec<(String, String)>, String> {
+    let paths = if dir.is_file() {
+        vec![dir.to_path_buf()]
+    } else {
+        let mut paths: Vec<PathBuf> = fs::read_dir(dir)
+            .map_err(|e| format!("Failed to read {}: {}", dir.display(), e))?
+            .filter_map(Result::ok)
+            .map(|entry| entry.path())
+            .filter(|path| {
+                path.is_file()
+                    && path
+                        .extension()
+                        .and_then(|ext| ext.to_str())
+                        .is_some_and(|ext| WATCHED_EXTENSIONS.contains(&ext))
+            })
+            .collect();
+        paths.sort();
+        paths
+    };
+
+    let settled_before = SystemTime::now() - SETTLE_TIME;
+    let mut files = Vec::new();
+    for path in paths {
+        let Ok(meta) = fs::metadata(&path) else {
+            continue;
+        };
+        let modified = meta.modified().unwrap_or(UNIX_EPOCH);
+        let stamp = FileStamp {
+            len: meta.len(),
+            modified_ms: modified
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_millis() as u64)
+                .unwrap_or_default(),
+        };
+        let name = path.to_string_lossy().to_string();
+        if modified > settled_before || watch.files.get(&name) == Some(&stamp) {
+            continue;
+        }
+        match fs::read_to_string(&path) {
+            Ok(text) => files.push((name.clone(), text)),
+            Err(e) => warn!(file = %name, error = %e, "Failed to read key file"),
+        }
+        watch.files.insert(name, stamp);
+    }
+    Ok(files)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use uuid::Uuid;
+
+    #[test]
+    fn only_settled_changed_key_files_are_read() {
+        let dir = std::env::temp_dir().join(format!("balance-proxy-watch-{}", Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        let keys = dir.join("firecrawl_keys.txt");
+        fs::write(&keys, "fc-1111111111\n").unwrap();
+        fs::write(dir.join("notes.md"), "fc-2222222222\n").unwrap();
+        let mut watch = WatchState::default();
+
+        // Too fresh: the bot may still be writing it.
+        assert!(changed_files(&mut watch, &dir).unwrap().is_empty());
+
+        let settle = |path: &Path| {
+            let old = SystemTime::now() - Duration::from_secs(60);
+            fs::File::options()
+                .append(true)
+                .open(path)
+                .unwrap()
+                .set_modified(old)
+                .unwrap();
+        };
+        settle(&keys);
+        let files = changed_files(&mut watch, &dir).unwrap();
+        assert_eq!(files.len(), 1);
+        assert!(files[0].0.ends_with("firecrawl_keys.txt"));
+        assert!(changed_files(&mut watch, &dir).unwrap().is_empty());
+
+        fs::write(&keys, "fc-1111111111\nfc-3333333333\n").unwrap();
+        settle(&keys);
+        assert_eq!(changed_files(&mut watch, &keys).unwrap().len(), 1);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn seen_keys_are_not_imported_again() {
+        let mut watch = WatchState::default();
+        watch.seen.insert(key_fingerprint("fc-1111111111"));
+        let files = vec![(
+            "firecrawl_keys.txt".to_string(),
+            "fc-1111111111\nfc-3333333333\n".to_string(),
+        )];
+        let mut import = KeyImport::scan(&files, None);
+        import.skip_keys(
+            |key| watch.seen.contains(&key_fingerprint(key)),
+            "seen before",
+        );
+        let mut keys = Vec::new();
+        import.merge_into("firecrawl", &mut keys);
+        assert_eq!(keys.len(), 1);
+        assert_eq!(keys[0].value, "fc-3333333333");
+        assert_eq!(import.finish().skipped[0].reason, "seen before");
+    }
+}
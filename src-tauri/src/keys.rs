@@ -0,0 +1,730 @@
+//! Per-key records for the provider key lists, and the edits the key
+//! management commands apply to the config and the running key managers.
+
+use std::collections::{BTreeSet, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Deserializer, Serialize};
+use tracing::{info, warn};
+
+use crate::config_schema::{write_atomic, write_config_file};
+use crate::env_config::{env_var_name, ConfigSource};
+use crate::secrets::mask;
+use crate::{key_fingerprint, now_ts, read_config_file_or_default, AppState};
+
+const MAX_KEY_WEIGHT: u32 = 100;
+const MAX_KEY_CONCURRENCY: u32 = 1000;
+/// Fingerprints of keys removed from the config, kept beside it so the key
+/// drop folder never adds them back.
+pub(crate) const REMOVED_KEYS_FILE: &str = "removed-keys.json";
+
+/// How a key got into the config.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) enum KeySource {
+    /// Entered in the config form or file, or set from the environment.
+    #[default]
+    Config,
+    /// Added through the key management commands.
+    Manual,
+    /// Read from a key or account file by the bulk import.
+    Import,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub(crate) struct KeyRecord {
+    pub(crate) value: String,
+    pub(crate) label: String,
+    /// Disabled keys stay in the config but are left out of rotation.
+    pub(crate) enabled: bool,
+    /// Consecutive requests the key serves each time rotation reaches it.
+    pub(crate) weight: u32,
+    pub(crate) notes: String,
+    /// Unix seconds; 0 when the key predates key records.
+    pub(crate) added_at: u64,
+    pub(crate) source: KeySource,
+    /// Day of the month (UTC) the key's quota renews. A key that runs out of
+    /// credits rests until then instead of cooling down.
+    pub(crate) quota_reset_day: Option<u8>,
+    /// `YYYY-MM-DD` (UTC); the key leaves rotation from this day on.
+    pub(crate) expires_on: Option<String>,
+    /// Spillover tier, e.g. `free` or `paid`; see `*_KEY_TIERS`.
+    pub(crate) tier: String,
+    /// Named key pool that `*_KEY_ROUTES` sends requests to; empty for the
+    /// default pool.
+    pub(crate) pool: String,
+    /// Requests the key may have in flight at once; rotation passes over it
+    /// while it is at the limit. Unlimited when unset.
+    pub(crate) max_concurrency: Option<u32>,
+}
+
+impl Default for KeyRecord {
+    fn default() -> Self {
+        Self {
+            value: String::new(),
+            label: String::new(),
+            enabled: true,
+            weight: 1,
+            notes: String::new(),
+            added_at: 0,
+            source: KeySource::Config,
+            quota_reset_day: None,
+            expires_on: None,
+            tier: String::new(),
+            pool: String::new(),
+            max_concurrency: None,
+        }
+    }
+}
+
+impl KeyRecord {
+    pub(crate) fn is_expired(&self, today: i64) -> bool {
+        self.expiry_day().is_some_and(|day| day <= today)
+    }
+
+    /// Days left before the key expires; 0 once it has.
+    pub(crate) fn days_until_expiry(&self, today: i64) -> Option<i64> {
+        self.expiry_day().map(|day| (day - today).max(0))
+    }
+
+    /// Days until the next quota reset; 0 never occurs, as a reset due
+    /// today has already happened.
+    pub(crate) fn days_until_reset(&self, today: i64) -> Option<i64> {
+        self.quota_reset_day
+            .map(|reset_day| next_quota_reset(reset_day, today) - today)
+    }
+
+    fn expiry_day(&self) -> Option<i64> {
+        self.expires_on
+            .as_deref()
+            .and_then(|date| parse_date(date).ok())
+    }
+
+    fn validate_settings(&self) -> Result<(), String> {
+        if let Some(limit) = self.max_concurrency {
+            if !(1..=MAX_KEY_CONCURRENCY).contains(&limit) {
+                return Err(format!(
+                    "Max concurrency must be between 1 and {}",
+                    MAX_KEY_CONCURRENCY
+                ));
+            }
+        }
+        if let Some(day) = self.quota_reset_day {
+            if !(1..=31).contains(&day) {
+                return Err("Quota reset day must be between 1 and 31".to_string());
+            }
+        }
+        if let Some(date) = &self.expires_on {
+            parse_date(date)?;
+        }
+        Ok(())
+    }
+}
+
+impl From<&str> for KeyRecord {
+    fn from(value: &str) -> Self {
+        Self {
+            value: value.to_string(),
+            ..Self::default()
+        }
+    }
+}
+
+/// A key as the UI sees it: masked, and addressed by its fingerprint.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct KeyView {
+    pub(crate) fingerprint: String,
+    #[serde(flatten)]
+    pub(crate) record: KeyRecord,
+}
+
+/// The editable metadata of a key; what `update_key` changes.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct KeyDetails {
+    pub(crate) label: String,
+    pub(crate) notes: String,
+    pub(crate) weight: u32,
+    #[serde(default)]
+    pub(crate) quota_reset_day: Option<u8>,
+    #[serde(default)]
+    pub(crate) expires_on: Option<String>,
+    #[serde(default)]
+    pub(crate) tier: String,
+    #[serde(default)]
+    pub(crate) pool: String,
+    #[serde(default)]
+    pub(crate) max_concurrency: Option<u32>,
+}
+
+impl KeyDetails {
+    fn validate(&self) -> Result<(), String> {
+        if !(1..=MAX_KEY_WEIGHT).contains(&self.weight) {
+            return Err(format!(
+                "Key weight must be between 1 and {}",
+                MAX_KEY_WEIGHT
+            ));
+        }
+        KeyRecord {
+            quota_reset_day: self.quota_reset_day,
+            expires_on: self.expires_on.clone(),
+            max_concurrency: self.max_concurrency,
+            ..KeyRecord::default()
+        }
+        .validate_settings()
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum KeyEntry {
+    Value(String),
+    Record(KeyRecord),
+}
+
+/// Key lists accept plain strings as well as records, so `.env` values and
+/// list-of-strings JSON keep working.
+pub(crate) fn deserialize_key_list<'de, D>(deserializer: D) -> Result<Vec<KeyRecord>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let entries = Vec::<KeyEntry>::deserialize(deserializer)?;
+    Ok(entries
+        .into_iter()
+        .map(|entry| match entry {
+            KeyEntry::Value(value) => KeyRecord::from(value.as_str()),
+            KeyEntry::Record(record) => record,
+        })
+        .collect())
+}
+
+#[cfg(test)]
+pub(crate) fn key_values(records: &[KeyRecord]) -> Vec<&str> {
+    records.iter().map(|record| record.value.as_str()).collect()
+}
+
+/// Splits values holding several comma/newline separated keys, trims them and
+/// drops duplicates, keeping the first record of each key.
+pub(crate) fn split_and_dedupe_keys(records: &[KeyRecord]) -> Vec<KeyRecord> {
+    let mut deduped = Vec::new();
+    let mut seen = HashSet::new();
+    for record in records {
+        for part in record.value.split([',', '\n', '\r']) {
+            let key = part.trim();
+            if key.is_empty() {
+                continue;
+            }
+            if seen.insert(key.to_string()) {
+                deduped.push(KeyRecord {
+                    value: key.to_string(),
+                    label: record.label.trim().to_string(),
+                    tier: record.tier.trim().to_string(),
+                    pool: record.pool.trim().to_string(),
+                    weight: record.weight.max(1),
+                    ..record.clone()
+                });
+            }
+        }
+    }
+    deduped
+}
+
+/// Checks the reset days, expiry dates and concurrency limits of a key list.
+pub(crate) fn validate_key_settings(records: &[KeyRecord]) -> Result<(), String> {
+    for record in records {
+        record
+            .validate_settings()
+            .map_err(|e| format!("Key {}: {}", mask(&record.value), e))?;
+    }
+    Ok(())
+}
+
+/// A `*_KEY_ROUTES` entry, written `pattern=pool`. `*` in the pattern matches
+/// any run of characters, so `/v1/crawl*` covers `/v1/crawl/{id}` too.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct KeyRoute {
+    pub(crate) pattern: String,
+    pub(crate) pool: String,
+}
+
+impl KeyRoute {
+    pub(crate) fn matches(&self, path: &str) -> bool {
+        wildcard_match(self.pattern.as_bytes(), path.as_bytes())
+    }
+}
+
+pub(crate) fn parse_key_routes(entries: &[String]) -> Result<Vec<KeyRoute>, String> {
+    entries
+        .iter()
+        .map(|entry| {
+            let (pattern, pool) = entry
+                .split_once('=')
+                .map(|(pattern, pool)| (pattern.trim(), pool.trim()))
+                .filter(|(pattern, pool)| pattern.starts_with('/') && !pool.is_empty())
+                .ok_or_else(|| {
+                    format!(
+                        "Key route \"{}\" must look like /v1/crawl*=pool",
+                        entry.trim()
+                    )
+                })?;
+            Ok(KeyRoute {
+                pattern: pattern.to_string(),
+                pool: pool.to_string(),
+            })
+        })
+        .collect()
+}
+
+/// Glob match where `*` spans any run of bytes. On a mismatch only the most
+/// recent `*` is extended, so matching takes at most pattern × text steps.
+fn wildcard_match(pattern: &[u8], text: &[u8]) -> bool {
+    let (mut p, mut t) = (0, 0);
+    let mut star: Option<(usize, usize)> = None;
+    while t < text.len() {
+        if pattern.get(p) == Some(&b'*') {
+            star = Some((p, t));
+            p += 1;
+        } else if pattern.get(p) == Some(&text[t]) {
+            p += 1;
+            t += 1;
+        } else if let Some((star_p, star_t)) = star {
+            p = star_p + 1;
+            t = star_t + 1;
+            star = Some((star_p, t));
+        } else {
+            return false;
+        }
+    }
+    pattern[p..].iter().all(|&ch| ch == b'*')
+}
+
+/// Dates keys that `previous` did not have.
+pub(crate) fn stamp_new_keys(records: &mut [KeyRecord], previous: &[KeyRecord]) {
+    let now = now_ts();
+    for record in records.iter_mut() {
+        if record.added_at == 0 && !previous.iter().any(|p| p.value == record.value) {
+            record.added_at = now;
+        }
+    }
+}
+
+pub(crate) fn key_views(records: &[KeyRecord]) -> Vec<KeyView> {
+    records
+        .iter()
+        .map(|record| KeyView {
+            fingerprint: key_fingerprint(&record.value),
+            record: KeyRecord {
+                value: mask(&record.value),
+                ..record.clone()
+            },
+        })
+        .collect()
+}
+
+pub(crate) fn add_key(
+    records: &mut Vec<KeyRecord>,
+    value: &str,
+    details: KeyDetails,
+) -> Result<(), String> {
+    details.validate()?;
+    let value = value.trim();
+    if value.is_empty() || value.contains([',', '\n', '\r']) {
+        return Err("Enter a single API key".to_string());
+    }
+    if records.iter().any(|record| record.value == value) {
+        return Err(format!("Key {} is already configured", mask(value)));
+    }
+    records.push(KeyRecord {
+        value: value.to_string(),
+        label: details.label,
+        notes: details.notes,
+        weight: details.weight,
+        quota_reset_day: details.quota_reset_day,
+        expires_on: details.expires_on,
+        tier: details.tier.trim().to_string(),
+        pool: details.pool.trim().to_string(),
+        max_concurrency: details.max_concurrency,
+        added_at: now_ts(),
+        source: KeySource::Manual,
+        ..KeyRecord::default()
+    });
+    Ok(())
+}
+
+pub(crate) fn remove_key(records: &mut Vec<KeyRecord>, fingerprint: &str) -> Result<(), String> {
+    let index = find_key(records, fingerprint)?;
+    records.remove(index);
+    Ok(())
+}
+
+pub(crate) fn set_key_enabled(
+    records: &mut [KeyRecord],
+    fingerprint: &str,
+    enabled: bool,
+) -> Result<(), String> {
+    let index = find_key(records, fingerprint)?;
+    records[index].enabled = enabled;
+    Ok(())
+}
+
+pub(crate) fn update_key(
+    records: &mut [KeyRecord],
+    fingerprint: &str,
+    details: KeyDetails,
+) -> Result<(), String> {
+    details.validate()?;
+    let index = find_key(records, fingerprint)?;
+    let record = &mut records[index];
+    record.label = details.label;
+    record.notes = details.notes;
+    record.weight = details.weight;
+    record.quota_reset_day = details.quota_reset_day;
+    record.expires_on = details.expires_on;
+    record.tier = details.tier.trim().to_string();
+    record.pool = details.pool.trim().to_string();
+    record.max_concurrency = details.max_concurrency;
+    Ok(())
+}
+
+/// Moves a key to `position` in the rotation order (clamped to the end).
+pub(crate) fn move_key(
+    records: &mut Vec<KeyRecord>,
+    fingerprint: &str,
+    position: usize,
+) -> Result<(), String> {
+    let index = find_key(records, fingerprint)?;
+    let record = records.remove(index);
+    records.insert(position.min(records.len()), record);
+    Ok(())
+}
+
+fn find_key(records: &[KeyRecord], fingerprint: &str) -> Result<usize, String> {
+    records
+        .iter()
+        .position(|record| key_fingerprint(&record.value) == fingerprint)
+        .ok_or_else(|| format!("No key with fingerprint {}", fingerprint))
+}
+
+/// Days since the Unix epoch, UTC.
+pub(crate) fn today() -> i64 {
+    (now_ts() / 86_400) as i64
+}
+
+/// Parses a `YYYY-MM-DD` date into days since the Unix epoch.
+pub(crate) fn parse_date(text: &str) -> Result<i64, String> {
+    let invalid = || format!("'{}' is not a YYYY-MM-DD date", text);
+    let mut parts = text.trim().splitn(3, '-');
+    let mut field = || -> Result<i64, String> {
+        parts
+            .next()
+            .and_then(|part| part.parse().ok())
+            .ok_or_else(invalid)
+    };
+    let (year, month, day) = (field()?, field()?, field()?);
+    if !(1970..=9999).contains(&year)
+        || !(1..=12).contains(&month)
+        || !(1..=days_in_month(year, month as u32)).contains(&day)
+    {
+        return Err(invalid());
+    }
+    Ok(days_from_civil(year, month as u32, day as u32))
+}
+
+/// The first day after `today` on which a quota resetting on `reset_day`
+/// renews; short months reset on their last day.
+fn next_quota_reset(reset_day: u8, today: i64) -> i64 {
+    let (year, month, _) = civil_from_days(today);
+    let reset_in = |year: i64, month: u32| {
+        let day = i64::from(reset_day).min(days_in_month(year, month));
+        days_from_civil(year, month, day as u32)
+    };
+    let this_month = reset_in(year, month);
+    if this_month > today {
+        return this_month;
+    }
+    if month == 12 {
+        reset_in(year + 1, 1)
+    } else {
+        reset_in(year, month + 1)
+    }
+}
+
+fn days_in_month(year: i64, month: u32) -> i64 {
+    let next = if month == 12 {
+        days_from_civil(year + 1, 1, 1)
+    } else {
+        days_from_civil(year, month + 1, 1)
+    };
+    next - days_from_civil(year, month, 1)
+}
+
+/// Days since the epoch of a proleptic Gregorian date (H. Hinnant's
+/// algorithm).
+fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let month_index = i64::from((month + 9) % 12);
+    let day_of_year = (153 * month_index + 2) / 5 + i64::from(day) - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days - era * 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * month_index + 2) / 5 + 1) as u32;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    } as u32;
+    let year = year_of_era + era * 400;
+    (if month <= 2 { year + 1 } else { year }, month, day)
+}
+
+/// Applies `edit` to a provider's keys, saves the result to the config file
+/// and hands the enabled keys to the running key manager, if any.
+pub(crate) async fn edit_provider_keys(
+    state: &AppState,
+    config_path: &Path,
+    provider: &'static str,
+    edit: impl FnOnce(&mut Vec<KeyRecord>) -> Result<(), String>,
+) -> Result<Vec<KeyRecord>, String> {
+    let field = if provider == "tavily" {
+        "tavilyApiKeys"
+    } else {
+        "firecrawlApiKeys"
+    };
+    let var = env_var_name(field);
+    if let Some(source @ (ConfigSource::Env | ConfigSource::DotEnv)) =
+        state.config_sources.read().await.get(&var).copied()
+    {
+        return Err(format!(
+            "{} is set by {}; edit it there",
+            var,
+            source.label()
+        ));
+    }
+
+    let manager = state
+        .active_key_managers
+        .lock()
+        .await
+        .slot_mut(provider)
+        .clone();
+    let mut config = state.config.write().await;
+    let mut keys = config.provider_keys(provider).to_vec();
+    edit(&mut keys)?;
+    let keys = split_and_dedupe_keys(&keys);
+    if manager.is_some() && !keys.iter().any(|key| key.enabled) {
+        return Err("A running provider needs at least one enabled key".to_string());
+    }
+
+    let mut on_disk = read_config_file_or_default(config_path)?;
+    remember_removed_keys(config_path, config.provider_keys(provider), &keys)?;
+    *on_disk.provider_keys_mut(provider) = keys.clone();
+    write_config_file(config_path, &on_disk)?;
+    *config.provider_keys_mut(provider) = keys.clone();
+    drop(config);
+
+    if let Some(manager) = manager {
+        manager.lock().await.replace_keys(&keys);
+    }
+    info!(
+        provider,
+        keys = keys.len(),
+        enabled = keys.iter().filter(|key| key.enabled).count(),
+        "Provider keys updated"
+    );
+    Ok(keys)
+}
+
+fn removed_keys_path(config_path: &Path) -> PathBuf {
+    config_path.with_file_name(REMOVED_KEYS_FILE)
+}
+
+/// Fingerprints of every key ever removed from the config at `config_path`.
+pub(crate) fn load_removed_keys(config_path: &Path) -> BTreeSet<String> {
+    let path = removed_keys_path(config_path);
+    match fs::read_to_string(&path) {
+        Ok(text) => serde_json::from_str(&text).unwrap_or_else(|e| {
+            warn!(path = %path.display(), error = %e, "Removed key list is unreadable");
+            BTreeSet::new()
+        }),
+        Err(_) => BTreeSet::new(),
+    }
+}
+
+/// Adds the keys of `before` missing from `after` to the removed key list.
+pub(crate) fn remember_removed_keys(
+    config_path: &Path,
+    before: &[KeyRecord],
+    after: &[KeyRecord],
+) -> Result<(), String> {
+    let removed: Vec<String> = before
+        .iter()
+        .filter(|key| !after.iter().any(|kept| kept.value == key.value))
+        .map(|key| key_fingerprint(&key.value))
+        .collect();
+    if removed.is_empty() {
+        return Ok(());
+    }
+    let mut known = load_removed_keys(config_path);
+    known.extend(removed);
+    let text = serde_json::to_string_pretty(&known)
+        .map_err(|e| format!("Failed to serialize removed keys: {}", e))?;
+    write_atomic(&removed_keys_path(config_path), &text)
+        .map_err(|e| format!("Failed to write removed keys: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ProxyConfig;
+
+    fn details(label: &str, weight: u32) -> KeyDetails {
+        KeyDetails {
+            label: label.to_string(),
+            notes: String::new(),
+            weight,
+            quota_reset_day: None,
+            expires_on: None,
+            tier: String::new(),
+            pool: String::new(),
+            max_concurrency: None,
+        }
+    }
+
+    #[test]
+    fn key_lists_read_plain_strings_and_records() {
+        let config: ProxyConfig = serde_json::from_value(serde_json::json!({
+            "tavilyApiKeys": [
+                "tvly-a, tvly-b",
+                { "value": "tvly-c", "label": "team", "enabled": false },
+                "tvly-a"
+            ]
+        }))
+        .unwrap();
+        let keys = split_and_dedupe_keys(&config.tavily_api_keys);
+        assert_eq!(key_values(&keys), vec!["tvly-a", "tvly-b", "tvly-c"]);
+        assert_eq!(keys[2].label, "team");
+        assert!(!keys[2].enabled && keys[0].enabled);
+        assert_eq!(keys[0].weight, 1);
+    }
+
+    #[test]
+    fn edits_address_keys_by_fingerprint() {
+        let mut keys = vec![KeyRecord::from("fc-1"), KeyRecord::from("fc-2")];
+        add_key(&mut keys, " fc-3 ", details("paid", 3)).unwrap();
+        assert!(add_key(&mut keys, "fc-1", details("", 1)).is_err());
+        assert!(add_key(&mut keys, "fc-4,fc-5", details("", 1)).is_err());
+        assert!(add_key(&mut keys, "fc-4", details("", 0)).is_err());
+        assert_eq!(keys[2].source, KeySource::Manual);
+        assert!(keys[2].added_at > 0);
+
+        let third = key_fingerprint("fc-3");
+        move_key(&mut keys, &third, 0).unwrap();
+        set_key_enabled(&mut keys, &key_fingerprint("fc-1"), false).unwrap();
+        update_key(&mut keys, &third, details("paid tier", 2)).unwrap();
+        remove_key(&mut keys, &key_fingerprint("fc-2")).unwrap();
+        assert!(remove_key(&mut keys, "000000000000").is_err());
+
+        assert_eq!(key_values(&keys), vec!["fc-3", "fc-1"]);
+        assert_eq!((keys[0].label.as_str(), keys[0].weight), ("paid tier", 2));
+        assert!(!keys[1].enabled);
+        assert!(key_views(&keys)[0].record.value.contains(&third));
+    }
+
+    #[test]
+    fn quota_resets_and_expiry_follow_the_calendar() {
+        let day = |date: &str| parse_date(date).unwrap();
+        assert_eq!(day("1970-01-01"), 0);
+        assert_eq!(civil_from_days(day("2024-02-29")), (2024, 2, 29));
+        assert!(parse_date("2023-02-29").is_err());
+        assert!(parse_date("2024-13-01").is_err());
+        assert!(parse_date("soon").is_err());
+
+        assert_eq!(next_quota_reset(15, day("2024-03-10")), day("2024-03-15"));
+        assert_eq!(next_quota_reset(15, day("2024-03-15")), day("2024-04-15"));
+        assert_eq!(next_quota_reset(31, day("2024-02-03")), day("2024-02-29"));
+        assert_eq!(next_quota_reset(1, day("2024-12-20")), day("2025-01-01"));
+
+        let key = KeyRecord {
+            quota_reset_day: Some(31),
+            expires_on: Some("2024-05-01".to_string()),
+            ..KeyRecord::from("fc-trial")
+        };
+        assert_eq!(key.days_until_reset(day("2024-04-28")), Some(2));
+        assert_eq!(key.days_until_expiry(day("2024-04-28")), Some(3));
+        assert!(!key.is_expired(day("2024-04-30")));
+        assert!(key.is_expired(day("2024-05-01")));
+        assert!(add_key(
+            &mut vec![],
+            "fc-x",
+            KeyDetails {
+                quota_reset_day: Some(32),
+                ..details("", 1)
+            }
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn key_routes_match_path_patterns() {
+        let routes = parse_key_routes(&[
+            "/v1/crawl*=heavy".to_string(),
+            " /v2/*/status = status ".to_string(),
+        ])
+        .unwrap();
+        assert_eq!(routes[1].pool, "status");
+        assert!(routes[0].matches("/v1/crawl"));
+        assert!(routes[0].matches("/v1/crawl/abc-123"));
+        assert!(!routes[0].matches("/v1/scrape"));
+        assert!(routes[1].matches("/v2/crawl/status"));
+        assert!(!routes[1].matches("/v2/crawl/status/1"));
+        assert!(parse_key_routes(&["/v1/crawl*".to_string()]).is_err());
+        assert!(parse_key_routes(&["crawl=heavy".to_string()]).is_err());
+    }
+
+    #[test]
+    fn wildcard_patterns_with_several_stars_match_without_backtracking() {
+        assert!(wildcard_match(
+            b"/v1/*/jobs/*/status",
+            b"/v1/crawl/jobs/42/status"
+        ));
+        assert!(wildcard_match(b"*a*b*c*", b"xxaxxbxxcxx"));
+        assert!(!wildcard_match(b"*a*b*c*", b"xxcxxbxxaxx"));
+        assert!(wildcard_match(b"**", b""));
+        assert!(!wildcard_match(b"/v1/*/status", b"/v1/crawl/status/1"));
+
+        let text = "a".repeat(200);
+        let pattern = format!("{}b", "*a".repeat(30));
+        assert!(!wildcard_match(pattern.as_bytes(), text.as_bytes()));
+    }
+
+    #[test]
+    fn removed_keys_are_remembered_beside_the_config() {
+        let dir = std::env::temp_dir().join(format!("balance-proxy-keys-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        let config_path = dir.join("proxy-config.json");
+        let before = vec![KeyRecord::from("fc-1"), KeyRecord::from("fc-2")];
+        remember_removed_keys(&config_path, &before, &before).unwrap();
+        assert!(!removed_keys_path(&config_path).exists());
+
+        remember_removed_keys(&config_path, &before, &before[1..]).unwrap();
+        remember_removed_keys(&config_path, &before[1..], &[]).unwrap();
+        let removed = load_removed_keys(&config_path);
+        assert!(removed.contains(&key_fingerprint("fc-1")));
+        assert!(removed.contains(&key_fingerprint("fc-2")));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
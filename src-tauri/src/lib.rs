@@ -1,31 +1,88 @@
-use std::collections::{HashSet, VecDeque};
+// The headless build only drives the proxy core; the UI-facing helpers it
+// leaves unused are still compiled so both builds share one code path.
+#![cfg_attr(not(feature = "desktop"), allow(dead_code))]
+
+mod capture;
+mod cli;
+mod config_schema;
+#[cfg(feature = "desktop")]
+mod desktop;
+mod drain;
+mod env_config;
+mod events;
+mod history;
+mod key_import;
+mod key_probe;
+mod key_watch;
+mod keys;
+mod log_files;
+mod logging;
+#[cfg(feature = "desktop")]
+mod notifications;
+mod pacing;
+mod profiles;
+mod secrets;
+mod supervisor;
+mod telemetry;
+#[cfg(feature = "desktop")]
+mod tray;
+
+pub use cli::run_cli;
+#[cfg(feature = "desktop")]
+pub use desktop::run;
+
 use std::fs;
 use std::io::ErrorKind;
 use std::net::SocketAddr;
-use std::path::PathBuf;
+use std::path::Path as FsPath;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 use std::sync::Arc;
 use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
 use axum::body::{Body, Bytes};
-use axum::extract::{Path, State};
+use axum::extract::{ConnectInfo, Path, Query, State};
 use axum::http::{HeaderMap, HeaderValue, Method, StatusCode, Uri};
 use axum::response::{IntoResponse, Response};
 use axum::routing::{any, get};
 use axum::{Json, Router};
+use opentelemetry_sdk::trace::SdkTracerProvider;
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
 use serde_json::json;
-use tauri::menu::MenuBuilder;
-use tauri::tray::TrayIconBuilder;
-use tauri::{Manager, WindowEvent};
-use tauri_plugin_autostart::MacosLauncher;
-use tauri_plugin_autostart::ManagerExt as _;
+use sha2::{Digest, Sha256};
 use tokio::net::TcpListener;
-use tokio::sync::{oneshot, Mutex, RwLock};
+use tokio::sync::{oneshot, Mutex, Notify, RwLock};
+use tracing::field::Empty;
+use tracing::{error, info, info_span, warn, Instrument};
+use tracing_opentelemetry::OpenTelemetrySpanExt;
 use uuid::Uuid;
 
+use capture::{capture_body, redact_headers, redact_text, CaptureEntry, DebugCapture};
+use config_schema::{load_config_file, upgrade_config_file, write_config_file};
+use drain::{InFlightGuard, RequestTracker};
+use env_config::ConfigSources;
+use events::{EventBus, KeyStateChange, LifecycleState, ProxyEvent, RequestCompleted};
+use history::{HistoryQuery, HistoryRetention, HistoryStore, RequestRecord};
+use key_probe::ProbeOutcome;
+use keys::{split_and_dedupe_keys, KeyRecord, KeyRoute};
+use log_files::{LogFileSettings, RotatingLogFile};
+use logging::{parse_log_level, LogBuffer, LogHandle};
+use pacing::KeyPacer;
+
+use supervisor::{handle_listener_exit, SupervisorState};
+use telemetry::{build_tracer_provider, extract_trace_context, inject_trace_context, OtelSettings};
+
+const PROVIDERS: [&str; 2] = ["firecrawl", "tavily"];
 const RETRYABLE_STATUS_CODES: [u16; 3] = [401, 402, 429];
-const MAX_LOG_LINES: usize = 500;
+/// Upstream says the key is out of credits.
+const PAYMENT_REQUIRED_STATUS_CODE: u16 = 402;
+const MAX_DRAIN_TIMEOUT_SECONDS: u64 = 600;
+/// Longest a request waits for a key to come under its concurrency or rate
+/// limit before it is turned away.
+const MAX_KEY_WAIT: Duration = Duration::from_secs(5);
+const LOG_DIR_NAME: &str = "logs";
+const HISTORY_DB_FILENAME: &str = "request-history.sqlite3";
+const CAPTURE_DIR_NAME: &str = "captures";
 const TAVILY_LOCAL_MCP_SCRIPT_FILENAME: &str = "tavily-local-proxy-mcp.mjs";
 const TAVILY_LOCAL_MCP_SCRIPT: &str = include_str!("../mcp/tavily-local-proxy-mcp.mjs");
 
@@ -59,15 +116,50 @@ const RESPONSE_HEADER_BLOCKLIST: [&str; 9] = [
 #[serde(rename_all = "camelCase", default)]
 struct ProxyConfig {
     proxy_token: String,
-    firecrawl_api_keys: Vec<String>,
+    #[serde(deserialize_with = "keys::deserialize_key_list")]
+    firecrawl_api_keys: Vec<KeyRecord>,
     upstream_base_url: String,
-    tavily_api_keys: Vec<String>,
+    #[serde(deserialize_with = "keys::deserialize_key_list")]
+    tavily_api_keys: Vec<KeyRecord>,
     tavily_upstream_base_url: String,
     request_timeout_ms: u64,
     key_cooldown_seconds: u64,
     host: String,
     port: u16,
     tavily_port: u16,
+    log_level: String,
+    log_json: bool,
+    log_file_enabled: bool,
+    log_file_max_size_mb: u64,
+    log_file_retention: usize,
+    log_file_compress: bool,
+    history_enabled: bool,
+    history_retention_days: u64,
+    history_max_rows: u64,
+    otel_enabled: bool,
+    otel_endpoint: String,
+    otel_service_name: String,
+    notifications_enabled: bool,
+    notify_min_healthy_keys: usize,
+    notify_cooldown_minutes: u64,
+    auto_start_proxy: bool,
+    drain_timeout_seconds: u64,
+    secrets_encryption: String,
+    /// Drop folder (or single file) polled for new keys; empty disables it.
+    key_watch_dir: String,
+    key_watch_interval_seconds: u64,
+    /// Key tiers in the order rotation uses them; keys of a later tier only
+    /// serve when every key of the earlier ones is cooling down.
+    firecrawl_key_tiers: Vec<String>,
+    tavily_key_tiers: Vec<String>,
+    /// `pattern=pool` entries; a request whose path matches a pattern draws
+    /// keys from that pool instead of the default one.
+    firecrawl_key_routes: Vec<String>,
+    tavily_key_routes: Vec<String>,
+    /// Requests per minute each key may make; rotation paces keys to stay
+    /// under it. 0 leaves pacing to observed `X-RateLimit-*` headers.
+    firecrawl_key_rpm: u64,
+    tavily_key_rpm: u64,
 }
 
 impl Default for ProxyConfig {
@@ -83,17 +175,43 @@ impl Default for ProxyConfig {
             host: "127.0.0.1".to_string(),
             port: 8787,
             tavily_port: 8788,
+            log_level: "info".to_string(),
+            log_json: false,
+            log_file_enabled: true,
+            log_file_max_size_mb: 10,
+            log_file_retention: 7,
+            log_file_compress: false,
+            history_enabled: true,
+            history_retention_days: 30,
+            history_max_rows: 200_000,
+            otel_enabled: false,
+            otel_endpoint: "http://127.0.0.1:4318/v1/traces".to_string(),
+            otel_service_name: "balance-proxy".to_string(),
+            notifications_enabled: true,
+            notify_min_healthy_keys: 1,
+            notify_cooldown_minutes: 10,
+            auto_start_proxy: false,
+            drain_timeout_seconds: 30,
+            secrets_encryption: "off".to_string(),
+            key_watch_dir: String::new(),
+            key_watch_interval_seconds: 10,
+            firecrawl_key_tiers: Vec::new(),
+            tavily_key_tiers: Vec::new(),
+            firecrawl_key_routes: Vec::new(),
+            tavily_key_routes: Vec::new(),
+            firecrawl_key_rpm: 0,
+            tavily_key_rpm: 0,
         }
     }
 }
 
 impl ProxyConfig {
     fn firecrawl_enabled(&self) -> bool {
-        !self.firecrawl_api_keys.is_empty() && !self.upstream_base_url.is_empty()
+        self.firecrawl_api_keys.iter().any(|k| k.enabled) && !self.upstream_base_url.is_empty()
     }
 
     fn tavily_enabled(&self) -> bool {
-        !self.tavily_api_keys.is_empty() && !self.tavily_upstream_base_url.is_empty()
+        self.tavily_api_keys.iter().any(|k| k.enabled) && !self.tavily_upstream_base_url.is_empty()
     }
 
     fn firecrawl_partially_configured(&self) -> bool {
@@ -117,6 +235,15 @@ impl ProxyConfig {
             .trim_end_matches('/')
             .to_string();
         self.host = self.host.trim().to_string();
+        self.log_level = self.log_level.trim().to_ascii_lowercase();
+        self.otel_endpoint = self.otel_endpoint.trim().to_string();
+        self.otel_service_name = self.otel_service_name.trim().to_string();
+        self.secrets_encryption = self.secrets_encryption.trim().to_ascii_lowercase();
+        self.key_watch_dir = self.key_watch_dir.trim().to_string();
+        self.firecrawl_key_tiers = normalize_list(&self.firecrawl_key_tiers);
+        self.tavily_key_tiers = normalize_list(&self.tavily_key_tiers);
+        self.firecrawl_key_routes = normalize_list(&self.firecrawl_key_routes);
+        self.tavily_key_routes = normalize_list(&self.tavily_key_routes);
         self.firecrawl_api_keys = split_and_dedupe_keys(&self.firecrawl_api_keys);
         self.tavily_api_keys = split_and_dedupe_keys(&self.tavily_api_keys);
         self
@@ -138,6 +265,47 @@ impl ProxyConfig {
         if self.port == self.tavily_port {
             return Err("PORT and TAVILY_PORT must be different".to_string());
         }
+        parse_log_level(&self.log_level)?;
+        if self.log_file_enabled && self.log_file_max_size_mb == 0 {
+            return Err("LOG_FILE_MAX_SIZE_MB must be greater than 0".to_string());
+        }
+        if self.log_file_enabled && self.log_file_retention == 0 {
+            return Err("LOG_FILE_RETENTION must be greater than 0".to_string());
+        }
+        if self.history_enabled && self.history_retention_days == 0 {
+            return Err("HISTORY_RETENTION_DAYS must be greater than 0".to_string());
+        }
+        if self.history_enabled && self.history_max_rows == 0 {
+            return Err("HISTORY_MAX_ROWS must be greater than 0".to_string());
+        }
+        if self.otel_enabled
+            && !(self.otel_endpoint.starts_with("http://")
+                || self.otel_endpoint.starts_with("https://"))
+        {
+            return Err("OTEL_ENDPOINT must be an http(s) URL".to_string());
+        }
+        if self.otel_enabled && self.otel_service_name.is_empty() {
+            return Err("OTEL_SERVICE_NAME cannot be empty".to_string());
+        }
+        if self.notifications_enabled && self.notify_cooldown_minutes == 0 {
+            return Err("NOTIFY_COOLDOWN_MINUTES must be greater than 0".to_string());
+        }
+        if self.drain_timeout_seconds > MAX_DRAIN_TIMEOUT_SECONDS {
+            return Err(format!(
+                "DRAIN_TIMEOUT_SECONDS must be at most {}",
+                MAX_DRAIN_TIMEOUT_SECONDS
+            ));
+        }
+        secrets::validate_mode(&self.secrets_encryption)?;
+        keys::validate_key_settings(&self.firecrawl_api_keys)?;
+        keys::validate_key_settings(&self.tavily_api_keys)?;
+        keys::parse_key_routes(&self.firecrawl_key_routes)
+            .map_err(|e| format!("FIRECRAWL_KEY_ROUTES: {}", e))?;
+        keys::parse_key_routes(&self.tavily_key_routes)
+            .map_err(|e| format!("TAVILY_KEY_ROUTES: {}", e))?;
+        if !self.key_watch_dir.is_empty() && self.key_watch_interval_seconds == 0 {
+            return Err("KEY_WATCH_INTERVAL_SECONDS must be greater than 0".to_string());
+        }
         Ok(())
     }
 
@@ -167,6 +335,80 @@ impl ProxyConfig {
         self.validate_provider_completeness()
     }
 
+    fn provider_enabled(&self, provider: &str) -> bool {
+        if provider == "tavily" {
+            self.tavily_enabled()
+        } else {
+            self.firecrawl_enabled()
+        }
+    }
+
+    fn provider_bind_addr(&self, provider: &str) -> String {
+        let port = if provider == "tavily" {
+            self.tavily_port
+        } else {
+            self.port
+        };
+        format!("{}:{}", self.host, port)
+    }
+
+    fn provider_keys(&self, provider: &str) -> &[KeyRecord] {
+        if provider == "tavily" {
+            &self.tavily_api_keys
+        } else {
+            &self.firecrawl_api_keys
+        }
+    }
+
+    fn provider_key_tiers(&self, provider: &str) -> &[String] {
+        if provider == "tavily" {
+            &self.tavily_key_tiers
+        } else {
+            &self.firecrawl_key_tiers
+        }
+    }
+
+    fn provider_key_routes(&self, provider: &str) -> &[String] {
+        if provider == "tavily" {
+            &self.tavily_key_routes
+        } else {
+            &self.firecrawl_key_routes
+        }
+    }
+
+    fn provider_key_rpm(&self, provider: &str) -> u64 {
+        if provider == "tavily" {
+            self.tavily_key_rpm
+        } else {
+            self.firecrawl_key_rpm
+        }
+    }
+
+    fn provider_keys_mut(&mut self, provider: &str) -> &mut Vec<KeyRecord> {
+        if provider == "tavily" {
+            &mut self.tavily_api_keys
+        } else {
+            &mut self.firecrawl_api_keys
+        }
+    }
+
+    /// Keys in rotation: the provider's keys minus the disabled ones.
+    fn enabled_keys(&self, provider: &str) -> Vec<KeyRecord> {
+        self.provider_keys(provider)
+            .iter()
+            .filter(|k| k.enabled)
+            .cloned()
+            .collect()
+    }
+
+    fn provider_upstream(&self, provider: &str) -> &str {
+        if provider == "tavily" {
+            &self.tavily_upstream_base_url
+        } else {
+            &self.upstream_base_url
+        }
+    }
+
     fn listen_url(&self) -> String {
         format!("http://{}:{}", self.host, self.port)
     }
@@ -176,31 +418,59 @@ impl ProxyConfig {
     }
 }
 
-fn split_and_dedupe_keys(raw_keys: &[String]) -> Vec<String> {
-    let mut deduped = Vec::new();
-    let mut seen = HashSet::new();
-    for raw in raw_keys {
-        for part in raw.split(|c| c == ',' || c == '\n' || c == '\r') {
-            let key = part.trim();
-            if key.is_empty() {
-                continue;
-            }
-            if seen.insert(key.to_string()) {
-                deduped.push(key.to_string());
-            }
-        }
+fn provider_display_name(provider: &str) -> &str {
+    match provider {
+        "firecrawl" => "Firecrawl",
+        "tavily" => "Tavily",
+        other => other,
     }
-    deduped
 }
 
-#[derive(Debug, Clone, Serialize)]
+fn parse_provider(provider: &str) -> Result<&'static str, String> {
+    PROVIDERS
+        .into_iter()
+        .find(|p| *p == provider)
+        .ok_or_else(|| format!("Unknown provider '{}'", provider))
+}
+
+fn provider_port_setting(provider: &str) -> &'static str {
+    if provider == "tavily" {
+        "TAVILY_PORT"
+    } else {
+        "PORT"
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 struct KeyStatus {
     index: usize,
     key_preview: String,
+    #[serde(default)]
+    label: String,
+    #[serde(default)]
+    tier: String,
+    #[serde(default)]
+    pool: String,
+    #[serde(default)]
+    in_flight: usize,
+    #[serde(default)]
+    max_concurrency: Option<u32>,
+    /// Requests the key can make before pacing holds it back.
+    #[serde(default)]
+    rate_remaining: Option<u64>,
     is_cooling_down: bool,
     cooldown_remaining_secs: u64,
     fail_count: u64,
+    is_dead: bool,
+    #[serde(default)]
+    is_exhausted: bool,
+    #[serde(default)]
+    is_expired: bool,
+    #[serde(default)]
+    days_until_reset: Option<i64>,
+    #[serde(default)]
+    days_until_expiry: Option<i64>,
 }
 
 fn truncate_key(key: &str) -> String {
@@ -211,19 +481,61 @@ fn truncate_key(key: &str) -> String {
     }
 }
 
-fn idle_key_statuses(keys: &[String]) -> Vec<KeyStatus> {
+/// Stable, non-reversible identifier for a key, safe to persist and display.
+fn key_fingerprint(key: &str) -> String {
+    let digest = Sha256::digest(key.as_bytes());
+    digest[..6].iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Trims list entries such as tier names and drops blanks and repeats.
+fn normalize_list(entries: &[String]) -> Vec<String> {
+    let mut normalized: Vec<String> = Vec::new();
+    for entry in entries.iter().map(|entry| entry.trim()) {
+        if !entry.is_empty() && !normalized.iter().any(|e| e == entry) {
+            normalized.push(entry.to_string());
+        }
+    }
+    normalized
+}
+
+/// Statuses of the keys a provider would rotate through, before it starts.
+fn idle_key_statuses(keys: &[KeyRecord]) -> Vec<KeyStatus> {
     keys.iter()
+        .filter(|k| k.enabled)
         .enumerate()
-        .map(|(i, k)| KeyStatus {
-            index: i,
-            key_preview: truncate_key(k),
-            is_cooling_down: false,
-            cooldown_remaining_secs: 0,
-            fail_count: 0,
-        })
+        .map(|(i, k)| idle_key_status(i, k, false, 0))
         .collect()
 }
 
+/// Status of a key with no failures recorded; the key manager fills in the
+/// rest.
+fn idle_key_status(
+    index: usize,
+    key: &KeyRecord,
+    is_cooling_down: bool,
+    cooldown_remaining_secs: u64,
+) -> KeyStatus {
+    let today = keys::today();
+    KeyStatus {
+        index,
+        key_preview: truncate_key(&key.value),
+        label: key.label.clone(),
+        tier: key.tier.clone(),
+        pool: key.pool.clone(),
+        in_flight: 0,
+        max_concurrency: key.max_concurrency,
+        rate_remaining: None,
+        is_cooling_down,
+        cooldown_remaining_secs,
+        fail_count: 0,
+        is_dead: false,
+        is_exhausted: false,
+        is_expired: key.is_expired(today),
+        days_until_reset: key.days_until_reset(today),
+        days_until_expiry: key.days_until_expiry(today),
+    }
+}
+
 fn derive_status_flags(
     config: &ProxyConfig,
     firecrawl_running: bool,
@@ -251,8 +563,13 @@ fn derive_status_flags(
 #[derive(Clone)]
 struct AppState {
     config: Arc<RwLock<ProxyConfig>>,
+    config_sources: Arc<RwLock<ConfigSources>>,
     runtime: Arc<Mutex<ProxyRuntime>>,
-    logs: Arc<Mutex<VecDeque<String>>>,
+    logs: LogBuffer,
+    log_handle: LogHandle,
+    history: Option<HistoryStore>,
+    capture: DebugCapture,
+    events: EventBus,
     active_key_managers: Arc<Mutex<ActiveKeyManagers>>,
 }
 
@@ -260,6 +577,52 @@ struct AppState {
 struct ProxyRuntime {
     firecrawl_handle: Option<ServerHandle>,
     tavily_handle: Option<ServerHandle>,
+    firecrawl_supervisor: SupervisorState,
+    tavily_supervisor: SupervisorState,
+    /// Trackers of listeners that were told to stop and are finishing their
+    /// in-flight requests.
+    firecrawl_draining: Option<RequestTracker>,
+    tavily_draining: Option<RequestTracker>,
+    /// Name of the config profile the providers were started from.
+    active_profile: Option<String>,
+}
+
+impl ProxyRuntime {
+    fn handle_mut(&mut self, provider: &str) -> &mut Option<ServerHandle> {
+        if provider == "tavily" {
+            &mut self.tavily_handle
+        } else {
+            &mut self.firecrawl_handle
+        }
+    }
+
+    fn draining_mut(&mut self, provider: &str) -> &mut Option<RequestTracker> {
+        if provider == "tavily" {
+            &mut self.tavily_draining
+        } else {
+            &mut self.firecrawl_draining
+        }
+    }
+
+    /// Requests served by the running listener plus any still draining from a
+    /// previous one.
+    fn in_flight(&self, provider: &str) -> usize {
+        let (handle, draining) = if provider == "tavily" {
+            (&self.tavily_handle, &self.tavily_draining)
+        } else {
+            (&self.firecrawl_handle, &self.firecrawl_draining)
+        };
+        handle.as_ref().map_or(0, |h| h.requests.in_flight())
+            + draining.as_ref().map_or(0, RequestTracker::in_flight)
+    }
+
+    fn supervisor_mut(&mut self, provider: &str) -> &mut SupervisorState {
+        if provider == "tavily" {
+            &mut self.tavily_supervisor
+        } else {
+            &mut self.firecrawl_supervisor
+        }
+    }
 }
 
 #[derive(Default)]
@@ -268,10 +631,25 @@ struct ActiveKeyManagers {
     tavily: Option<Arc<Mutex<RoundRobinKeyManager>>>,
 }
 
+impl ActiveKeyManagers {
+    fn slot_mut(&mut self, provider: &str) -> &mut Option<Arc<Mutex<RoundRobinKeyManager>>> {
+        if provider == "tavily" {
+            &mut self.tavily
+        } else {
+            &mut self.firecrawl
+        }
+    }
+}
+
 struct ServerHandle {
     shutdown_tx: Option<oneshot::Sender<()>>,
-    join_handle: tauri::async_runtime::JoinHandle<()>,
+    join_handle: tokio::task::JoinHandle<()>,
     listen_url: String,
+    started_at: Instant,
+    requests: RequestTracker,
+    /// Set before a deliberate shutdown so the listener task can tell a stop
+    /// from a crash.
+    stop_requested: Arc<AtomicBool>,
 }
 
 #[derive(Debug, Clone, Serialize)]
@@ -286,6 +664,13 @@ struct ProxyStatus {
     tavily_enabled: bool,
     firecrawl_running: bool,
     tavily_running: bool,
+    firecrawl_crash_count: u32,
+    tavily_crash_count: u32,
+    firecrawl_in_flight: usize,
+    tavily_in_flight: usize,
+    firecrawl_draining: bool,
+    tavily_draining: bool,
+    active_profile: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize)]
@@ -310,7 +695,10 @@ struct ProxyServerState {
     upstream_base_url: String,
     key_manager: Arc<Mutex<RoundRobinKeyManager>>,
     http_client: Client,
-    logs: Arc<Mutex<VecDeque<String>>>,
+    history: Option<HistoryStore>,
+    capture: DebugCapture,
+    events: EventBus,
+    requests: RequestTracker,
 }
 
 #[derive(Debug, Clone)]
@@ -319,97 +707,383 @@ struct TavilyMcpLaunchConfig {
     args: Vec<String>,
 }
 
+/// Why no key could be handed out.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum KeyUnavailable {
+    /// Every usable key is at its concurrency or rate limit. Carries when
+    /// the soonest rate slot opens, if any key is only paced.
+    Busy(Option<Duration>),
+    /// The pool has no usable keys: they all expired, or were removed
+    /// mid-request.
+    NoKeys,
+}
+
 #[derive(Clone)]
 struct SelectedKey {
     index: usize,
     value: String,
+    tier: String,
+    pool: String,
 }
 
 struct RoundRobinKeyManager {
-    keys: Vec<String>,
+    keys: Vec<KeyRecord>,
     next_index: usize,
+    /// Key that served the last request and how many it has served in a row,
+    /// so weighted keys keep their turn.
+    streak: Option<(usize, u32)>,
     cooldown_until: Vec<Option<Instant>>,
     fail_count: Vec<u64>,
+    dead: Vec<bool>,
+    /// Out of credits and resting until the key's quota reset day.
+    exhausted: Vec<bool>,
+    /// Requests each key has upstream right now; see `acquire_key`.
+    in_flight: Vec<Arc<AtomicUsize>>,
+    /// Woken whenever a key's in-flight count drops.
+    released: Arc<Notify>,
+    /// Recent sends and observed rate limits per key.
+    pacers: Vec<KeyPacer>,
+    /// Configured requests per minute per key; 0 when unknown.
+    rate_per_minute: u64,
     cooldown_seconds: u64,
+    /// Tier names in spillover order; keys of other tiers come last.
+    tier_order: Vec<String>,
+    /// Path patterns that send requests to a named key pool.
+    routes: Vec<KeyRoute>,
 }
 
 impl RoundRobinKeyManager {
-    fn new(keys: Vec<String>, cooldown_seconds: u64) -> Self {
-        let key_count = keys.len();
-        Self {
-            keys,
+    /// Rotates through the enabled records of `keys`, in order.
+    fn new(keys: &[KeyRecord], cooldown_seconds: u64) -> Self {
+        let mut manager = Self {
+            keys: Vec::new(),
             next_index: 0,
-            cooldown_until: vec![None; key_count],
-            fail_count: vec![0; key_count],
+            streak: None,
+            cooldown_until: Vec::new(),
+            fail_count: Vec::new(),
+            dead: Vec::new(),
+            exhausted: Vec::new(),
+            in_flight: Vec::new(),
+            released: Arc::default(),
+            pacers: Vec::new(),
+            rate_per_minute: 0,
             cooldown_seconds,
+            tier_order: Vec::new(),
+            routes: Vec::new(),
+        };
+        manager.replace_keys(keys);
+        manager
+    }
+
+    /// Swaps in a new key list while running. Keys that stay keep their
+    /// cooldown, failure count and dead flag; rotation restarts at the top.
+    fn replace_keys(&mut self, keys: &[KeyRecord]) {
+        let keys: Vec<KeyRecord> = keys.iter().filter(|k| k.enabled).cloned().collect();
+        let previous = |key: &KeyRecord| self.keys.iter().position(|k| k.value == key.value);
+        let carried: Vec<Option<usize>> = keys.iter().map(previous).collect();
+        self.cooldown_until = carried
+            .iter()
+            .map(|i| i.and_then(|i| self.cooldown_until[i]))
+            .collect();
+        self.fail_count = carried
+            .iter()
+            .map(|i| i.map_or(0, |i| self.fail_count[i]))
+            .collect();
+        self.dead = carried
+            .iter()
+            .map(|i| i.is_some_and(|i| self.dead[i]))
+            .collect();
+        self.exhausted = carried
+            .iter()
+            .map(|i| i.is_some_and(|i| self.exhausted[i]))
+            .collect();
+        self.in_flight = carried
+            .iter()
+            .map(|i| i.map_or_else(Arc::default, |i| self.in_flight[i].clone()))
+            .collect();
+        self.pacers = carried
+            .iter()
+            .map(|i| i.map_or_else(KeyPacer::default, |i| self.pacers[i].clone()))
+            .collect();
+        self.keys = keys;
+        self.next_index = 0;
+        self.streak = None;
+    }
+
+    fn set_tier_order(&mut self, tiers: &[String]) {
+        self.tier_order = tiers.to_vec();
+    }
+
+    fn set_rate_limit(&mut self, per_minute: u64) {
+        self.rate_per_minute = per_minute;
+    }
+
+    /// Feeds an upstream response's rate-limit headers to the key's pacing.
+    fn observe_rate_limit(&mut self, key: &str, headers: &reqwest::header::HeaderMap) {
+        if let Some(index) = self.key_index(key) {
+            self.pacers[index].observe(headers, Instant::now());
+        }
+    }
+
+    fn set_key_routes(&mut self, routes: Vec<KeyRoute>) {
+        self.routes = routes;
+    }
+
+    /// Pool of the first route matching `path`; the default pool (`""`)
+    /// when none does.
+    fn route_pool(&self, path: &str) -> String {
+        self.routes
+            .iter()
+            .find(|route| route.matches(path))
+            .map(|route| route.pool.clone())
+            .unwrap_or_default()
+    }
+
+    /// Indices of the keys in `pool`. A named pool without keys falls back to
+    /// the default pool, which holds the keys of no pool, or every key when
+    /// all of them belong to one.
+    fn pool_members(&self, pool: &str) -> Vec<usize> {
+        let in_pool = |pool: &str| -> Vec<usize> {
+            (0..self.keys.len())
+                .filter(|&idx| self.keys[idx].pool == pool)
+                .collect()
+        };
+        for pool in [pool, ""] {
+            let members = in_pool(pool);
+            if !members.is_empty() {
+                return members;
+            }
         }
+        (0..self.keys.len()).collect()
+    }
+
+    fn in_flight(&self, index: usize) -> usize {
+        self.in_flight[index].load(Ordering::SeqCst)
+    }
+
+    fn at_concurrency_limit(&self, index: usize) -> bool {
+        self.keys[index]
+            .max_concurrency
+            .is_some_and(|limit| self.in_flight(index) >= limit as usize)
+    }
+
+    /// Position of the key's tier in the spillover order.
+    fn tier_rank(&self, index: usize) -> usize {
+        self.tier_order
+            .iter()
+            .position(|tier| *tier == self.keys[index].tier)
+            .unwrap_or(self.tier_order.len())
     }
 
     fn key_count(&self) -> usize {
         self.keys.len()
     }
 
-    fn select_key(&mut self) -> SelectedKey {
+    /// Current position of `key`. Indices go stale across awaits, as the key
+    /// list can be replaced meanwhile; `None` once the key was removed.
+    fn key_index(&self, key: &str) -> Option<usize> {
+        self.keys.iter().position(|k| k.value == key)
+    }
+
+    fn key_values(&self) -> Vec<String> {
+        self.keys.iter().map(|k| k.value.clone()).collect()
+    }
+
+    /// Picks the next available key of `pool`. Expired keys are never used,
+    /// and a pool left with only those has `NoKeys`; dead keys are skipped
+    /// unless every remaining key is dead. Tiers are tried in order, so a
+    /// later tier only serves while every key of the earlier ones is cooling
+    /// down. Keys at their concurrency or rate limit are never used; when
+    /// those are all that is left the pool is `Busy`. Otherwise falls back
+    /// to the key whose cooldown ends soonest. A key with weight `n` serves
+    /// `n` requests in a row before rotation moves on.
+    fn select_key(&mut self, pool: &str) -> Result<SelectedKey, KeyUnavailable> {
+        let today = keys::today();
+        let mut members = self.pool_members(pool);
+        members.retain(|&idx| !self.keys[idx].is_expired(today));
+        if members.is_empty() {
+            return Err(KeyUnavailable::NoKeys);
+        }
         let now = Instant::now();
         let count = self.keys.len();
         let start = self.next_index % count;
-
-        let mut earliest_idx = start;
-        let mut earliest_wait = Duration::MAX;
-
-        for offset in 0..count {
-            let idx = (start + offset) % count;
-            let wait = match self.cooldown_until[idx] {
-                Some(deadline) if deadline > now => deadline - now,
-                _ => Duration::ZERO,
-            };
-
-            if wait == Duration::ZERO {
-                self.next_index = (idx + 1) % count;
-                return SelectedKey {
-                    index: idx,
-                    value: self.keys[idx].clone(),
+        members.sort_by_key(|&idx| (idx + count - start) % count);
+        let all_dead = members.iter().all(|&idx| self.dead[idx]);
+        let mut ranks: Vec<usize> = members.iter().map(|&idx| self.tier_rank(idx)).collect();
+        ranks.sort_unstable();
+        ranks.dedup();
+
+        let mut earliest: Option<(Duration, usize)> = None;
+        let mut next_rate_slot: Option<Duration> = None;
+
+        for rank in ranks {
+            for &idx in &members {
+                if self.tier_rank(idx) != rank
+                    || (self.dead[idx] && !all_dead)
+                    || self.at_concurrency_limit(idx)
+                {
+                    continue;
+                }
+                let paced = self.pacers[idx].wait(self.rate_per_minute, now);
+                if !paced.is_zero() {
+                    next_rate_slot = Some(next_rate_slot.map_or(paced, |slot| slot.min(paced)));
+                    continue;
+                }
+                let wait = match self.cooldown_until[idx] {
+                    Some(deadline) if deadline > now => deadline - now,
+                    _ => Duration::ZERO,
                 };
-            }
 
-            if wait < earliest_wait {
-                earliest_wait = wait;
-                earliest_idx = idx;
+                if wait == Duration::ZERO {
+                    return Ok(self.take_turn(idx));
+                }
+
+                if earliest.is_none_or(|(earliest_wait, _)| wait < earliest_wait) {
+                    earliest = Some((wait, idx));
+                }
             }
         }
 
+        let Some((_, earliest_idx)) = earliest else {
+            return Err(KeyUnavailable::Busy(next_rate_slot));
+        };
         self.next_index = (earliest_idx + 1) % count;
+        self.streak = None;
+        Ok(self.selected(earliest_idx))
+    }
+
+    /// Selects a key of `pool`, counts the send against its rate limit, and
+    /// counts the request against its concurrency limit until the returned
+    /// guard is dropped.
+    fn acquire_key(&mut self, pool: &str) -> Result<(SelectedKey, InFlightGuard), KeyUnavailable> {
+        let selected = self.select_key(pool)?;
+        self.pacers[selected.index].record_send(Instant::now());
+        let guard = InFlightGuard::notifying(
+            self.in_flight[selected.index].clone(),
+            self.released.clone(),
+        );
+        Ok((selected, guard))
+    }
+
+    /// Hands out an available key and advances the weighted rotation.
+    fn take_turn(&mut self, idx: usize) -> SelectedKey {
+        self.exhausted[idx] = false;
+        let served = match self.streak {
+            Some((last, served)) if last == idx => served + 1,
+            _ => 1,
+        };
+        if served >= self.keys[idx].weight.max(1) {
+            self.next_index = (idx + 1) % self.keys.len();
+            self.streak = None;
+        } else {
+            self.next_index = idx;
+            self.streak = Some((idx, served));
+        }
+        self.selected(idx)
+    }
+
+    fn selected(&self, idx: usize) -> SelectedKey {
         SelectedKey {
-            index: earliest_idx,
-            value: self.keys[earliest_idx].clone(),
+            index: idx,
+            value: self.keys[idx].value.clone(),
+            tier: self.keys[idx].tier.clone(),
+            pool: self.keys[idx].pool.clone(),
         }
     }
 
-    fn mark_retryable_failure(&mut self, key_index: usize) {
+    /// Records a retryable upstream status and returns the key's new state.
+    fn mark_retryable_failure(&mut self, key_index: usize, status: u16) -> KeyStatus {
         self.fail_count[key_index] += 1;
-        self.cooldown_until[key_index] =
+        if status == PAYMENT_REQUIRED_STATUS_CODE {
+            self.rest_until_reset(key_index);
+        } else {
+            self.cool_down(key_index);
+        }
+        self.key_status(key_index)
+    }
+
+    fn cool_down(&mut self, index: usize) {
+        self.cooldown_until[index] =
             Some(Instant::now() + Duration::from_secs(self.cooldown_seconds));
     }
 
-    fn get_statuses(&self) -> Vec<KeyStatus> {
+    /// Benches a key that ran out of credits until its quota resets, or for
+    /// a normal cooldown when its reset day is unknown.
+    fn rest_until_reset(&mut self, index: usize) {
+        let Some(days) = self.keys[index].days_until_reset(keys::today()) else {
+            self.cool_down(index);
+            return;
+        };
+        let reset_at = (keys::today() + days) as u64 * 86_400;
+        let rest = Duration::from_secs(reset_at.saturating_sub(now_ts()));
+        self.cooldown_until[index] = Some(Instant::now() + rest);
+        self.exhausted[index] = true;
+    }
+
+    /// Applies a validation probe to the key with this value, if it is in
+    /// rotation: rejected keys die, spent or throttled keys cool down, and a
+    /// key that answered normally is revived. Returns the key's index.
+    fn record_probe(&mut self, key: &str, outcome: ProbeOutcome) -> Option<usize> {
+        let index = self.key_index(key)?;
+        match outcome {
+            ProbeOutcome::Valid => {
+                self.dead[index] = false;
+                self.exhausted[index] = false;
+                self.cooldown_until[index] = None;
+            }
+            ProbeOutcome::Invalid => {
+                self.fail_count[index] += 1;
+                self.dead[index] = true;
+                self.cooldown_until[index] = None;
+            }
+            ProbeOutcome::Exhausted => self.rest_until_reset(index),
+            ProbeOutcome::RateLimited => self.cool_down(index),
+            ProbeOutcome::Error => return None,
+        }
+        Some(index)
+    }
+
+    fn key_status(&self, index: usize) -> KeyStatus {
         let now = Instant::now();
-        self.keys
-            .iter()
-            .enumerate()
-            .map(|(i, key)| {
-                let (is_cooling_down, remaining) = match self.cooldown_until[i] {
-                    Some(deadline) if deadline > now => (true, (deadline - now).as_secs()),
-                    _ => (false, 0),
-                };
-                KeyStatus {
-                    index: i,
-                    key_preview: truncate_key(key),
-                    is_cooling_down,
-                    cooldown_remaining_secs: remaining,
-                    fail_count: self.fail_count[i],
-                }
+        let (is_cooling_down, remaining) = match self.cooldown_until[index] {
+            Some(deadline) if deadline > now => (true, (deadline - now).as_secs()),
+            _ => (false, 0),
+        };
+        KeyStatus {
+            fail_count: self.fail_count[index],
+            is_dead: self.dead[index],
+            is_exhausted: self.exhausted[index] && is_cooling_down,
+            in_flight: self.in_flight(index),
+            rate_remaining: self.pacers[index].remaining(self.rate_per_minute, now),
+            ..idle_key_status(index, &self.keys[index], is_cooling_down, remaining)
+        }
+    }
+
+    /// Keys that are neither dead nor cooling down right now.
+    fn healthy_count(&self) -> usize {
+        let now = Instant::now();
+        let today = keys::today();
+        (0..self.keys.len())
+            .filter(|&i| {
+                !self.dead[i]
+                    && !self.keys[i].is_expired(today)
+                    && self.cooldown_until[i].is_none_or(|d| d <= now)
             })
-            .collect()
+            .count()
+    }
+
+    fn key_state_change(&self, provider: &'static str, index: usize) -> KeyStateChange {
+        KeyStateChange {
+            provider,
+            key: self.key_status(index),
+            key_fingerprint: key_fingerprint(&self.keys[index].value),
+            healthy_keys: self.healthy_count(),
+            total_keys: self.keys.len(),
+        }
+    }
+
+    fn get_statuses(&self) -> Vec<KeyStatus> {
+        (0..self.keys.len()).map(|i| self.key_status(i)).collect()
     }
 }
 
@@ -419,40 +1093,67 @@ fn now_ts() -> u64 {
         .map_or(0, |v| v.as_secs())
 }
 
-async fn append_log(logs: &Arc<Mutex<VecDeque<String>>>, level: &str, message: String) {
-    let line = format!("{} [{}] {}", now_ts(), level, message);
-    println!("{}", line);
-    let mut guard = logs.lock().await;
-    if guard.len() >= MAX_LOG_LINES {
-        guard.pop_front();
+fn open_history_store(data_dir: &FsPath, config: &ProxyConfig) -> Option<HistoryStore> {
+    if !config.history_enabled {
+        return None;
     }
-    guard.push_back(line);
+    let path = data_dir.join(HISTORY_DB_FILENAME);
+    let retention = HistoryRetention {
+        max_age_days: config.history_retention_days.max(1),
+        max_rows: config.history_max_rows.max(1),
+    };
+    match HistoryStore::open(&path, retention) {
+        Ok(store) => Some(store),
+        Err(err) => {
+            warn!(error = %err, "Request history unavailable");
+            None
+        }
+    }
+}
+
+/// The log file when it is enabled. Runs before tracing is installed, so a
+/// failure is returned for `warn_unavailable` to log once it is.
+fn open_log_file(
+    data_dir: &FsPath,
+    config: &ProxyConfig,
+) -> Result<Option<RotatingLogFile>, String> {
+    if !config.log_file_enabled {
+        return Ok(None);
+    }
+    let settings = LogFileSettings {
+        dir: data_dir.join(LOG_DIR_NAME),
+        max_file_bytes: config.log_file_max_size_mb.max(1) * 1024 * 1024,
+        retention: config.log_file_retention.max(1),
+        compress: config.log_file_compress,
+    };
+    RotatingLogFile::open(settings)
+        .map(Some)
+        .map_err(|e| format!("Failed to open log file: {}", e))
 }
 
-fn config_path(app: &tauri::AppHandle) -> Result<PathBuf, String> {
-    let app_data_dir = app
-        .path()
-        .app_data_dir()
-        .map_err(|e| format!("Failed to resolve app data dir: {}", e))?;
-    fs::create_dir_all(&app_data_dir)
-        .map_err(|e| format!("Failed to create app data dir: {}", e))?;
-    Ok(app_data_dir.join("proxy-config.json"))
+/// The OTLP exporter when it is enabled; failures are returned like
+/// `open_log_file` does.
+fn open_tracer_provider(config: &ProxyConfig) -> Result<Option<SdkTracerProvider>, String> {
+    if !config.otel_enabled {
+        return Ok(None);
+    }
+    let settings = OtelSettings {
+        endpoint: config.otel_endpoint.clone(),
+        service_name: config.otel_service_name.clone(),
+    };
+    build_tracer_provider(&settings).map(Some)
 }
 
-fn tavily_local_mcp_script_path(app: &tauri::AppHandle) -> Result<PathBuf, String> {
-    let app_data_dir = app
-        .path()
-        .app_data_dir()
-        .map_err(|e| format!("Failed to resolve app data dir: {}", e))?;
-    fs::create_dir_all(&app_data_dir)
-        .map_err(|e| format!("Failed to create app data dir: {}", e))?;
-    Ok(app_data_dir.join(TAVILY_LOCAL_MCP_SCRIPT_FILENAME))
+/// Logs why an optional log output could not be opened; the app carries on
+/// without it.
+fn warn_unavailable<T>(output: &str, opened: &Result<Option<T>, String>) {
+    if let Err(err) = opened {
+        warn!(error = %err, "{} unavailable, continuing without it", output);
+    }
 }
 
-fn ensure_tavily_local_mcp_launcher(
-    app: &tauri::AppHandle,
-) -> Result<TavilyMcpLaunchConfig, String> {
-    let script_path = tavily_local_mcp_script_path(app)?;
+fn ensure_tavily_local_mcp_launcher(data_dir: &FsPath) -> Result<TavilyMcpLaunchConfig, String> {
+    let script_path = data_dir.join(TAVILY_LOCAL_MCP_SCRIPT_FILENAME);
     let should_write = match fs::read_to_string(&script_path) {
         Ok(existing) => existing != TAVILY_LOCAL_MCP_SCRIPT,
         Err(err) if err.kind() == ErrorKind::NotFound => true,
@@ -481,25 +1182,37 @@ fn ensure_tavily_local_mcp_launcher(
     })
 }
 
-fn load_or_init_config(app: &tauri::AppHandle) -> Result<ProxyConfig, String> {
-    let path = config_path(app)?;
-    if !path.exists() {
-        let config = ProxyConfig::default().normalized();
-        let text = serde_json::to_string_pretty(&config)
-            .map_err(|e| format!("Failed to serialize default config: {}", e))?;
-        fs::write(&path, text).map_err(|e| format!("Failed to write default config: {}", e))?;
-        return Ok(config);
+/// Writes the default config to `path` unless a file is already there, and
+/// stores an existing file in the current format. Returns the version the
+/// file was upgraded from.
+fn init_config_file(path: &FsPath) -> Result<Option<u64>, String> {
+    if path.exists() {
+        return upgrade_config_file(path);
     }
+    write_config_file(path, &ProxyConfig::default().normalized())?;
+    Ok(None)
+}
 
-    let text = fs::read_to_string(&path).map_err(|e| format!("Failed to read config: {}", e))?;
-    let config: ProxyConfig =
-        serde_json::from_str(&text).map_err(|e| format!("Failed to parse config: {}", e))?;
-    Ok(config.normalized())
+fn read_config_file(path: &FsPath) -> Result<ProxyConfig, String> {
+    load_config_file(path)?.config()
+}
+
+/// The config stored at `path`, or the defaults while there is no file yet.
+/// A file that exists but fails to load is an error, so a save never replaces
+/// it with defaults.
+fn read_config_file_or_default(path: &FsPath) -> Result<ProxyConfig, String> {
+    if !path.exists() {
+        return Ok(ProxyConfig::default());
+    }
+    read_config_file(path)
 }
 
 fn build_firecrawl_router(state: ProxyServerState) -> Router {
     Router::new()
         .route("/health", get(health))
+        .route("/_admin/history", get(admin_history))
+        .route("/_admin/history/stats", get(admin_history_stats))
+        .route("/_admin/keys", get(admin_keys))
         .route("/v1", any(proxy_v1_root))
         .route("/v1/*path", any(proxy_v1_path))
         .route("/v2", any(proxy_v2_root))
@@ -510,6 +1223,9 @@ fn build_firecrawl_router(state: ProxyServerState) -> Router {
 fn build_tavily_router(state: ProxyServerState) -> Router {
     Router::new()
         .route("/health", get(health))
+        .route("/_admin/history", get(admin_history))
+        .route("/_admin/history/stats", get(admin_history_stats))
+        .route("/_admin/keys", get(admin_keys))
         .route("/", any(proxy_tavily_root))
         .route("/*path", any(proxy_tavily_path))
         .with_state(state)
@@ -521,6 +1237,7 @@ async fn health() -> Json<serde_json::Value> {
 
 async fn proxy_v1_root(
     State(state): State<ProxyServerState>,
+    ConnectInfo(client): ConnectInfo<SocketAddr>,
     method: Method,
     uri: Uri,
     headers: HeaderMap,
@@ -534,12 +1251,14 @@ async fn proxy_v1_root(
         headers,
         body,
         target_url,
+        client,
     )
     .await
 }
 
 async fn proxy_v1_path(
     State(state): State<ProxyServerState>,
+    ConnectInfo(client): ConnectInfo<SocketAddr>,
     Path(path): Path<String>,
     method: Method,
     uri: Uri,
@@ -554,12 +1273,14 @@ async fn proxy_v1_path(
         headers,
         body,
         target_url,
+        client,
     )
     .await
 }
 
 async fn proxy_v2_root(
     State(state): State<ProxyServerState>,
+    ConnectInfo(client): ConnectInfo<SocketAddr>,
     method: Method,
     uri: Uri,
     headers: HeaderMap,
@@ -573,12 +1294,14 @@ async fn proxy_v2_root(
         headers,
         body,
         target_url,
+        client,
     )
     .await
 }
 
 async fn proxy_v2_path(
     State(state): State<ProxyServerState>,
+    ConnectInfo(client): ConnectInfo<SocketAddr>,
     Path(path): Path<String>,
     method: Method,
     uri: Uri,
@@ -593,12 +1316,14 @@ async fn proxy_v2_path(
         headers,
         body,
         target_url,
+        client,
     )
     .await
 }
 
 async fn proxy_tavily_root(
     State(state): State<ProxyServerState>,
+    ConnectInfo(client): ConnectInfo<SocketAddr>,
     method: Method,
     uri: Uri,
     headers: HeaderMap,
@@ -612,12 +1337,14 @@ async fn proxy_tavily_root(
         headers,
         body,
         target_url,
+        client,
     )
     .await
 }
 
 async fn proxy_tavily_path(
     State(state): State<ProxyServerState>,
+    ConnectInfo(client): ConnectInfo<SocketAddr>,
     Path(path): Path<String>,
     method: Method,
     uri: Uri,
@@ -632,6 +1359,7 @@ async fn proxy_tavily_path(
         headers,
         body,
         target_url,
+        client,
     )
     .await
 }
@@ -725,6 +1453,28 @@ fn sanitize_response_headers(headers: &HeaderMap) -> HeaderMap {
     sanitized
 }
 
+/// Result of forwarding one inbound request, kept alongside the response so
+/// the caller can record it without re-reading the body.
+struct ProxyOutcome {
+    response: Response,
+    status: u16,
+    key: Option<SelectedKey>,
+    retries: usize,
+    payload: Bytes,
+}
+
+impl ProxyOutcome {
+    fn error(status: StatusCode, detail: &str, key: Option<SelectedKey>, retries: usize) -> Self {
+        Self {
+            response: json_error(status, detail),
+            status: status.as_u16(),
+            key,
+            retries,
+            payload: Bytes::from(json!({ "detail": detail }).to_string()),
+        }
+    }
+}
+
 async fn proxy_request_to_target(
     state: ProxyServerState,
     method: Method,
@@ -732,92 +1482,357 @@ async fn proxy_request_to_target(
     headers: HeaderMap,
     body: Bytes,
     target_url: String,
+    client: SocketAddr,
 ) -> Response {
-    if !is_authorized(&headers, &state.proxy_token) {
-        return json_error(StatusCode::UNAUTHORIZED, "Unauthorized");
-    }
-
     let request_id = headers
         .get("x-request-id")
         .and_then(|v| v.to_str().ok())
         .map(|v| v.to_string())
         .unwrap_or_else(|| Uuid::new_v4().to_string());
+    let started = Instant::now();
+    let _in_flight = state.requests.track();
+
+    let span = info_span!(
+        "proxy_request",
+        otel.kind = "server",
+        otel.status_code = Empty,
+        provider = state.provider,
+        request_id = %request_id,
+        http.request.method = %method,
+        url.path = %request_path,
+        client.address = %client,
+        http.response.status_code = Empty,
+        retries = Empty,
+    );
+    let _ = span.set_parent(extract_trace_context(&headers));
+
+    let outcome = if is_authorized(&headers, &state.proxy_token) {
+        let forward = forward_with_retries(
+            &state,
+            &method,
+            &request_path,
+            &headers,
+            &body,
+            &target_url,
+            &request_id,
+            client,
+        )
+        .instrument(span.clone());
+        tokio::select! {
+            outcome = forward => outcome,
+            _ = state.requests.aborted() => ProxyOutcome::error(
+                StatusCode::SERVICE_UNAVAILABLE,
+                "Proxy stopped before the upstream responded",
+                None,
+                0,
+            ),
+        }
+    } else {
+        ProxyOutcome::error(StatusCode::UNAUTHORIZED, "Unauthorized", None, 0)
+    };
+
+    span.record("http.response.status_code", outcome.status);
+    span.record("retries", outcome.retries);
+    if outcome.status >= 500 {
+        span.record("otel.status_code", "ERROR");
+    }
+
+    state
+        .events
+        .publish(ProxyEvent::RequestCompleted(RequestCompleted {
+            ts: now_ts(),
+            request_id: request_id.clone(),
+            provider: state.provider,
+            method: method.to_string(),
+            path: request_path.clone(),
+            status: outcome.status,
+            key_index: outcome.key.as_ref().map(|k| k.index + 1),
+            retries: outcome.retries,
+            latency_ms: started.elapsed().as_millis() as u64,
+        }));
+
+    if let Some(history) = &state.history {
+        history.record(RequestRecord {
+            ts: now_ts(),
+            request_id: request_id.clone(),
+            provider: state.provider.to_string(),
+            client: client.to_string(),
+            method: method.to_string(),
+            path: request_path.clone(),
+            status: outcome.status,
+            key_index: outcome.key.as_ref().map(|k| k.index + 1),
+            key_fingerprint: outcome.key.as_ref().map(|k| key_fingerprint(&k.value)),
+            retries: outcome.retries,
+            latency_ms: started.elapsed().as_millis() as u64,
+            request_bytes: body.len() as u64,
+            response_bytes: outcome.payload.len() as u64,
+        });
+    }
+
+    if let Some(max_body_bytes) = state.capture.try_claim() {
+        let secrets = {
+            let manager = state.key_manager.lock().await;
+            let mut secrets = manager.key_values();
+            secrets.push(state.proxy_token.clone());
+            secrets
+        };
+        let entry = CaptureEntry {
+            id: format!("{}-{}", now_ts(), Uuid::new_v4().simple()),
+            ts: now_ts(),
+            provider: state.provider.to_string(),
+            request_id,
+            method: method.to_string(),
+            path: request_path,
+            query: target_url.split_once('?').map(|(_, q)| q.to_string()),
+            upstream_url: redact_text(&target_url, &secrets),
+            request_headers: redact_headers(&headers, &secrets),
+            request_body: capture_body(&body, max_body_bytes, &secrets),
+            status: outcome.status,
+            key_index: outcome.key.as_ref().map(|k| k.index + 1),
+            retries: outcome.retries,
+            latency_ms: started.elapsed().as_millis() as u64,
+            response_headers: redact_headers(outcome.response.headers(), &secrets),
+            response_body: capture_body(&outcome.payload, max_body_bytes, &secrets),
+        };
+        let capture = state.capture.clone();
+        tokio::task::spawn_blocking(move || {
+            if let Err(err) = capture.write(&entry) {
+                warn!(error = %err, "Failed to write debug capture");
+            }
+        });
+    }
+
+    outcome.response
+}
+
+fn retry_reason(status: u16) -> &'static str {
+    match status {
+        401 => "unauthorized",
+        402 => "payment_required",
+        429 => "rate_limited",
+        _ => "other",
+    }
+}
 
+/// Publishes a key transition and, for cooldowns, schedules the matching
+/// "available again" event for when the cooldown lapses.
+fn publish_key_state(state: &ProxyServerState, manager: &RoundRobinKeyManager, key: &str) {
+    let Some(index) = manager.key_index(key) else {
+        return;
+    };
+    let change = manager.key_state_change(state.provider, index);
+    let cooldown_secs = change.key.cooldown_remaining_secs;
+    let is_cooling_down = change.key.is_cooling_down;
+    state.events.publish(ProxyEvent::KeyState(change));
+    if !is_cooling_down {
+        return;
+    }
+
+    let key_manager = state.key_manager.clone();
+    let events = state.events.clone();
+    let provider = state.provider;
+    let key = key.to_string();
+    tokio::spawn(async move {
+        tokio::time::sleep(Duration::from_secs(cooldown_secs + 1)).await;
+        let manager = key_manager.lock().await;
+        // The key may have been removed or moved by an edit meanwhile.
+        let Some(index) = manager.key_index(&key) else {
+            return;
+        };
+        let change = manager.key_state_change(provider, index);
+        drop(manager);
+        // A newer failure extended the cooldown; its own timer reports the exit.
+        if !change.key.is_cooling_down && !change.key.is_dead {
+            events.publish(ProxyEvent::KeyState(change));
+        }
+    });
+}
+
+/// Acquires a key of `pool`, waiting up to `MAX_KEY_WAIT` for one to come
+/// under its concurrency or rate limit.
+async fn acquire_key_waiting(
+    state: &ProxyServerState,
+    pool: &str,
+) -> Result<(SelectedKey, InFlightGuard), KeyUnavailable> {
+    let deadline = tokio::time::Instant::now() + MAX_KEY_WAIT;
+    let released = state.key_manager.lock().await.released.clone();
+    loop {
+        // Listen before looking, so a release in between is not missed.
+        let notified = released.notified();
+        tokio::pin!(notified);
+        notified.as_mut().enable();
+        let next_rate_slot = match state.key_manager.lock().await.acquire_key(pool) {
+            Err(KeyUnavailable::Busy(next_rate_slot)) => next_rate_slot,
+            acquired => return acquired,
+        };
+        let wake_at = next_rate_slot
+            .map(|slot| deadline.min(tokio::time::Instant::now() + slot))
+            .unwrap_or(deadline);
+        if tokio::time::timeout_at(wake_at, notified).await.is_err() && wake_at >= deadline {
+            return Err(KeyUnavailable::Busy(next_rate_slot));
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+async fn forward_with_retries(
+    state: &ProxyServerState,
+    method: &Method,
+    request_path: &str,
+    headers: &HeaderMap,
+    body: &Bytes,
+    target_url: &str,
+    request_id: &str,
+    client: SocketAddr,
+) -> ProxyOutcome {
     let started = Instant::now();
     let mut retry_count = 0usize;
 
-    let max_attempts = {
+    let (pool, max_attempts) = {
         let manager = state.key_manager.lock().await;
-        manager.key_count()
+        let pool = manager.route_pool(request_path);
+        // One attempt even without keys, so the caller hears why.
+        let members = manager.pool_members(&pool).len().max(1);
+        (pool, members)
     };
 
     for attempt in 0..max_attempts {
-        let selected = {
-            let mut manager = state.key_manager.lock().await;
-            manager.select_key()
+        // Held until the upstream response is read, or the attempt given up.
+        let waiting_since = Instant::now();
+        let (selected, _in_flight) = match acquire_key_waiting(state, &pool).await {
+            Ok(acquired) => acquired,
+            Err(KeyUnavailable::Busy(_)) => {
+                warn!(
+                    provider = state.provider,
+                    request_id = %request_id,
+                    path = %request_path,
+                    "proxy_keys_busy"
+                );
+                return ProxyOutcome::error(
+                    StatusCode::TOO_MANY_REQUESTS,
+                    "Every key is at its concurrency or rate limit",
+                    None,
+                    retry_count,
+                );
+            }
+            Err(KeyUnavailable::NoKeys) => {
+                return ProxyOutcome::error(
+                    StatusCode::SERVICE_UNAVAILABLE,
+                    "No API keys are available for this request",
+                    None,
+                    retry_count,
+                )
+            }
         };
+        let waited = waiting_since.elapsed();
+        if waited >= Duration::from_millis(1) {
+            info!(
+                provider = state.provider,
+                request_id = %request_id,
+                key_index = selected.index + 1,
+                waited_ms = waited.as_millis() as u64,
+                "proxy_paced"
+            );
+        }
+
+        let attempt_span = info_span!(
+            "upstream_attempt",
+            otel.kind = "client",
+            otel.status_code = Empty,
+            provider = state.provider,
+            attempt = attempt + 1,
+            key_index = selected.index + 1,
+            key_tier = %selected.tier,
+            key_pool = %selected.pool,
+            http.response.status_code = Empty,
+            retry_reason = Empty,
+            error = Empty,
+        );
 
-        let request_headers =
-            match sanitize_request_headers(&headers, &selected.value, state.provider) {
+        let mut request_headers =
+            match sanitize_request_headers(headers, &selected.value, state.provider) {
                 Ok(value) => value,
-                Err(err) => return json_error(StatusCode::INTERNAL_SERVER_ERROR, &err),
+                Err(err) => {
+                    return ProxyOutcome::error(
+                        StatusCode::INTERNAL_SERVER_ERROR,
+                        &err,
+                        Some(selected),
+                        retry_count,
+                    )
+                }
             };
 
+        // Upstream sees this attempt as the parent span, not the caller's.
+        inject_trace_context(&attempt_span.context(), &mut request_headers);
+
         let mut request = state
             .http_client
-            .request(method.clone(), &target_url)
+            .request(method.clone(), target_url)
             .headers(request_headers);
 
         if !body.is_empty() {
             request = request.body(body.clone());
         }
 
-        let response = match request.send().await {
+        let response = match request.send().instrument(attempt_span.clone()).await {
             Ok(value) => value,
             Err(err) => {
-                append_log(
-                    &state.logs,
-                    "WARN",
-                    format!(
-                        "proxy_upstream_error provider={} request_id={} method={} path={} key_index={} attempt={} retries={} err={}",
-                        state.provider,
-                        request_id,
-                        method,
-                        request_path,
-                        selected.index + 1,
-                        attempt + 1,
-                        retry_count,
-                        err
-                    ),
-                )
-                .await;
-                return json_error(StatusCode::BAD_GATEWAY, "Upstream request failed");
+                attempt_span.record("error", tracing::field::display(&err));
+                attempt_span.record("otel.status_code", "ERROR");
+                warn!(
+                    provider = state.provider,
+                    request_id = %request_id,
+                    method = %method,
+                    path = %request_path,
+                    key_index = selected.index + 1,
+                    key_tier = %selected.tier,
+                    key_pool = %selected.pool,
+                    attempt = attempt + 1,
+                    retries = retry_count,
+                    client = %client,
+                    error = %err,
+                    "proxy_upstream_error"
+                );
+                return ProxyOutcome::error(
+                    StatusCode::BAD_GATEWAY,
+                    "Upstream request failed",
+                    Some(selected),
+                    retry_count,
+                );
             }
         };
 
         let status = response.status();
+        attempt_span.record("http.response.status_code", status.as_u16());
+        state
+            .key_manager
+            .lock()
+            .await
+            .observe_rate_limit(&selected.value, response.headers());
         if RETRYABLE_STATUS_CODES.contains(&status.as_u16()) {
+            attempt_span.record("retry_reason", retry_reason(status.as_u16()));
             {
                 let mut manager = state.key_manager.lock().await;
-                manager.mark_retryable_failure(selected.index);
+                if let Some(index) = manager.key_index(&selected.value) {
+                    manager.mark_retryable_failure(index, status.as_u16());
+                    publish_key_state(state, &manager, &selected.value);
+                }
             }
             if attempt < max_attempts - 1 {
                 retry_count += 1;
-                append_log(
-                    &state.logs,
-                    "INFO",
-                    format!(
-                        "proxy_retry provider={} request_id={} method={} path={} status={} key_index={} retries={}",
-                        state.provider,
-                        request_id,
-                        method,
-                        request_path,
-                        status.as_u16(),
-                        selected.index + 1,
-                        retry_count
-                    ),
-                )
-                .await;
+                info!(
+                    provider = state.provider,
+                    request_id = %request_id,
+                    method = %method,
+                    path = %request_path,
+                    status = status.as_u16(),
+                    key_index = selected.index + 1,
+                    key_tier = %selected.tier,
+                    key_pool = %selected.pool,
+                    retries = retry_count,
+                    client = %client,
+                    "proxy_retry"
+                );
                 continue;
             }
         }
@@ -825,25 +1840,30 @@ async fn proxy_request_to_target(
         let response_headers = sanitize_response_headers(response.headers());
         let payload = match response.bytes().await {
             Ok(value) => value,
-            Err(_) => return json_error(StatusCode::BAD_GATEWAY, "Failed to read upstream body"),
+            Err(_) => {
+                return ProxyOutcome::error(
+                    StatusCode::BAD_GATEWAY,
+                    "Failed to read upstream body",
+                    Some(selected),
+                    retry_count,
+                )
+            }
         };
 
-        append_log(
-            &state.logs,
-            "INFO",
-            format!(
-                "proxy_done provider={} request_id={} method={} path={} status={} key_index={} retries={} total_ms={}",
-                state.provider,
-                request_id,
-                method,
-                request_path,
-                status.as_u16(),
-                selected.index + 1,
-                retry_count,
-                started.elapsed().as_millis()
-            ),
-        )
-        .await;
+        info!(
+            provider = state.provider,
+            request_id = %request_id,
+            method = %method,
+            path = %request_path,
+            status = status.as_u16(),
+            key_index = selected.index + 1,
+            key_tier = %selected.tier,
+            key_pool = %selected.pool,
+            retries = retry_count,
+            latency_ms = started.elapsed().as_millis() as u64,
+            client = %client,
+            "proxy_done"
+        );
 
         let mut builder = Response::builder().status(status);
         for (name, value) in response_headers {
@@ -852,51 +1872,81 @@ async fn proxy_request_to_target(
             }
         }
         builder = builder.header("X-Proxy-Key-Index", (selected.index + 1).to_string());
+        if !selected.tier.is_empty() {
+            builder = builder.header("X-Proxy-Key-Tier", &selected.tier);
+        }
+        if !selected.pool.is_empty() {
+            builder = builder.header("X-Proxy-Key-Pool", &selected.pool);
+        }
         builder = builder.header("X-Proxy-Retry-Count", retry_count.to_string());
         builder = builder.header("X-Proxy-Provider", state.provider);
 
-        return builder.body(Body::from(payload)).unwrap_or_else(|_| {
-            json_error(
+        return match builder.body(Body::from(payload.clone())) {
+            Ok(response) => ProxyOutcome {
+                response,
+                status: status.as_u16(),
+                key: Some(selected),
+                retries: retry_count,
+                payload,
+            },
+            Err(_) => ProxyOutcome::error(
                 StatusCode::INTERNAL_SERVER_ERROR,
                 "Failed to build response",
-            )
-        });
+                Some(selected),
+                retry_count,
+            ),
+        };
     }
 
-    json_error(
+    ProxyOutcome::error(
         StatusCode::INTERNAL_SERVER_ERROR,
         "Unexpected routing state",
+        None,
+        retry_count,
     )
 }
 
-#[tauri::command]
-async fn load_proxy_config(state: tauri::State<'_, AppState>) -> Result<ProxyConfig, String> {
-    Ok(state.config.read().await.clone())
+async fn admin_history(
+    State(state): State<ProxyServerState>,
+    headers: HeaderMap,
+    Query(query): Query<HistoryQuery>,
+) -> Response {
+    if !is_authorized(&headers, &state.proxy_token) {
+        return json_error(StatusCode::UNAUTHORIZED, "Unauthorized");
+    }
+    let Some(history) = state.history.clone() else {
+        return json_error(StatusCode::NOT_FOUND, "Request history is disabled");
+    };
+    match tokio::task::spawn_blocking(move || history.query(&query)).await {
+        Ok(Ok(page)) => Json(page).into_response(),
+        Ok(Err(err)) => json_error(StatusCode::INTERNAL_SERVER_ERROR, &err),
+        Err(_) => json_error(StatusCode::INTERNAL_SERVER_ERROR, "History query failed"),
+    }
 }
 
-#[tauri::command]
-async fn save_proxy_config(
-    app: tauri::AppHandle,
-    state: tauri::State<'_, AppState>,
-    config: ProxyConfig,
-) -> Result<String, String> {
-    let normalized = config.normalized();
-    normalized.validate()?;
-
-    let path = config_path(&app)?;
-    let text = serde_json::to_string_pretty(&normalized)
-        .map_err(|e| format!("Failed to serialize config: {}", e))?;
-    fs::write(&path, text).map_err(|e| format!("Failed to write config: {}", e))?;
-
-    *state.config.write().await = normalized;
-    append_log(
-        &state.logs,
-        "INFO",
-        format!("Config saved: {}", path.to_string_lossy()),
-    )
-    .await;
+async fn admin_keys(State(state): State<ProxyServerState>, headers: HeaderMap) -> Response {
+    if !is_authorized(&headers, &state.proxy_token) {
+        return json_error(StatusCode::UNAUTHORIZED, "Unauthorized");
+    }
+    Json(state.key_manager.lock().await.get_statuses()).into_response()
+}
 
-    Ok(path.to_string_lossy().to_string())
+async fn admin_history_stats(
+    State(state): State<ProxyServerState>,
+    headers: HeaderMap,
+    Query(query): Query<HistoryQuery>,
+) -> Response {
+    if !is_authorized(&headers, &state.proxy_token) {
+        return json_error(StatusCode::UNAUTHORIZED, "Unauthorized");
+    }
+    let Some(history) = state.history.clone() else {
+        return json_error(StatusCode::NOT_FOUND, "Request history is disabled");
+    };
+    match tokio::task::spawn_blocking(move || history.stats(&query)).await {
+        Ok(Ok(stats)) => Json(stats).into_response(),
+        Ok(Err(err)) => json_error(StatusCode::INTERNAL_SERVER_ERROR, &err),
+        Err(_) => json_error(StatusCode::INTERNAL_SERVER_ERROR, "History query failed"),
+    }
 }
 
 fn compose_proxy_status(runtime: &ProxyRuntime, config: &ProxyConfig) -> ProxyStatus {
@@ -920,238 +1970,266 @@ fn compose_proxy_status(runtime: &ProxyRuntime, config: &ProxyConfig) -> ProxySt
         tavily_enabled,
         firecrawl_running,
         tavily_running,
+        firecrawl_crash_count: runtime.firecrawl_supervisor.crash_count,
+        tavily_crash_count: runtime.tavily_supervisor.crash_count,
+        firecrawl_in_flight: runtime.in_flight("firecrawl"),
+        tavily_in_flight: runtime.in_flight("tavily"),
+        firecrawl_draining: runtime.firecrawl_draining.is_some(),
+        tavily_draining: runtime.tavily_draining.is_some(),
+        active_profile: runtime.active_profile.clone(),
     }
 }
 
-#[tauri::command]
-async fn get_proxy_status(state: tauri::State<'_, AppState>) -> Result<ProxyStatus, String> {
-    let config = state.config.read().await.clone();
-    let runtime = state.runtime.lock().await;
-    Ok(compose_proxy_status(&runtime, &config))
+fn build_http_client(config: &ProxyConfig) -> Result<Client, String> {
+    Client::builder()
+        .timeout(Duration::from_millis(config.request_timeout_ms))
+        .build()
+        .map_err(|e| format!("Failed to build HTTP client: {}", e))
 }
 
-#[tauri::command]
-async fn start_proxy(state: tauri::State<'_, AppState>) -> Result<ProxyStatus, String> {
-    let config = state.config.read().await.clone();
-    config.validate()?;
-    let firecrawl_enabled = config.firecrawl_enabled();
-    let tavily_enabled = config.tavily_enabled();
-
-    let (start_firecrawl, start_tavily) = {
-        let runtime = state.runtime.lock().await;
-        (
-            firecrawl_enabled && runtime.firecrawl_handle.is_none(),
-            tavily_enabled && runtime.tavily_handle.is_none(),
-        )
+/// Binds and spawns the listener for one provider. Does nothing when the
+/// provider is already running.
+async fn start_provider(
+    state: &AppState,
+    config: &ProxyConfig,
+    provider: &'static str,
+    http_client: Client,
+) -> Result<(), String> {
+    if state.runtime.lock().await.handle_mut(provider).is_some() {
+        return Ok(());
+    }
+    let name = provider_display_name(provider);
+
+    let addr: SocketAddr = config
+        .provider_bind_addr(provider)
+        .parse()
+        .map_err(|e| format!("Invalid HOST/{}: {}", provider_port_setting(provider), e))?;
+    let listener = TcpListener::bind(addr)
+        .await
+        .map_err(|e| format!("Failed to bind http://{}: {}", addr, e))?;
+    let local_addr = listener
+        .local_addr()
+        .map_err(|e| format!("Failed to resolve {} local addr: {}", provider, e))?;
+    let listen_url = format!("http://{}", local_addr);
+
+    let mut manager =
+        RoundRobinKeyManager::new(config.provider_keys(provider), config.key_cooldown_seconds);
+    manager.set_tier_order(config.provider_key_tiers(provider));
+    manager.set_rate_limit(config.provider_key_rpm(provider));
+    manager.set_key_routes(keys::parse_key_routes(
+        config.provider_key_routes(provider),
+    )?);
+    let key_manager = Arc::new(Mutex::new(manager));
+    let requests = RequestTracker::default();
+    let server_state = ProxyServerState {
+        provider,
+        proxy_token: config.proxy_token.clone(),
+        upstream_base_url: config.provider_upstream(provider).to_string(),
+        key_manager: key_manager.clone(),
+        http_client,
+        history: state.history.clone(),
+        capture: state.capture.clone(),
+        events: state.events.clone(),
+        requests: requests.clone(),
     };
+    let router = if provider == "tavily" {
+        build_tavily_router(server_state)
+    } else {
+        build_firecrawl_router(server_state)
+    };
+    let (shutdown_tx, shutdown_rx) = oneshot::channel::<()>();
 
-    if !start_firecrawl && !start_tavily {
-        let runtime = state.runtime.lock().await;
-        return Ok(compose_proxy_status(&runtime, &config));
-    }
+    info!(provider, listen_url = %listen_url, "{} proxy starting", name);
 
-    let http_client = Client::builder()
-        .timeout(Duration::from_millis(config.request_timeout_ms))
-        .build()
-        .map_err(|e| format!("Failed to build HTTP client: {}", e))?;
-
-    let mut new_firecrawl_handle: Option<ServerHandle> = None;
-    let mut new_tavily_handle: Option<ServerHandle> = None;
-    let mut new_firecrawl_manager: Option<Arc<Mutex<RoundRobinKeyManager>>> = None;
-    let mut new_tavily_manager: Option<Arc<Mutex<RoundRobinKeyManager>>> = None;
-
-    if start_firecrawl {
-        let firecrawl_addr: SocketAddr = format!("{}:{}", config.host, config.port)
-            .parse()
-            .map_err(|e| format!("Invalid HOST/PORT: {}", e))?;
-        let firecrawl_listener = TcpListener::bind(firecrawl_addr)
-            .await
-            .map_err(|e| format!("Failed to bind {}: {}", config.listen_url(), e))?;
-        let firecrawl_local_addr = firecrawl_listener
-            .local_addr()
-            .map_err(|e| format!("Failed to resolve local addr: {}", e))?;
-        let firecrawl_listen_url = format!("http://{}", firecrawl_local_addr);
-
-        let firecrawl_key_manager = Arc::new(Mutex::new(RoundRobinKeyManager::new(
-            config.firecrawl_api_keys.clone(),
-            config.key_cooldown_seconds,
-        )));
-        new_firecrawl_manager = Some(firecrawl_key_manager.clone());
-
-        let firecrawl_state = ProxyServerState {
-            provider: "firecrawl",
-            proxy_token: config.proxy_token.clone(),
-            upstream_base_url: config.upstream_base_url.clone(),
-            key_manager: firecrawl_key_manager,
-            http_client: http_client.clone(),
-            logs: state.logs.clone(),
-        };
-        let firecrawl_router = build_firecrawl_router(firecrawl_state);
-        let (firecrawl_shutdown_tx, firecrawl_shutdown_rx) = oneshot::channel::<()>();
+    let stop_requested = Arc::new(AtomicBool::new(false));
+    // Held until the handle is installed so a listener that dies instantly
+    // still finds its handle when reporting the exit.
+    let mut runtime = state.runtime.lock().await;
 
-        append_log(
-            &state.logs,
-            "INFO",
-            format!("Firecrawl proxy starting at {}", firecrawl_listen_url),
+    let task_state = state.clone();
+    let task_stop_flag = stop_requested.clone();
+    let join_handle = tokio::spawn(async move {
+        let server = axum::serve(
+            listener,
+            router.into_make_service_with_connect_info::<SocketAddr>(),
         )
-        .await;
-
-        let logs = state.logs.clone();
-        let firecrawl_join_handle = tauri::async_runtime::spawn(async move {
-            let server = axum::serve(firecrawl_listener, firecrawl_router).with_graceful_shutdown(
-                async move {
-                    let _ = firecrawl_shutdown_rx.await;
-                },
-            );
-
-            if let Err(err) = server.await {
-                append_log(&logs, "ERROR", format!("Firecrawl proxy crashed: {}", err)).await;
-            }
-        });
-
-        new_firecrawl_handle = Some(ServerHandle {
-            shutdown_tx: Some(firecrawl_shutdown_tx),
-            join_handle: firecrawl_join_handle,
-            listen_url: firecrawl_listen_url,
+        .with_graceful_shutdown(async move {
+            let _ = shutdown_rx.await;
         });
-    }
+        // Run the server in its own task so a panic surfaces here as a
+        // JoinError instead of silently ending the listener.
+        let result = tokio::spawn(async move { server.await }).await;
+        if task_stop_flag.load(Ordering::SeqCst) {
+            return;
+        }
 
-    if start_tavily {
-        let tavily_addr: SocketAddr = format!("{}:{}", config.host, config.tavily_port)
-            .parse()
-            .map_err(|e| format!("Invalid HOST/TAVILY_PORT: {}", e))?;
-        let tavily_listener = TcpListener::bind(tavily_addr)
-            .await
-            .map_err(|e| format!("Failed to bind {}: {}", config.tavily_listen_url(), e))?;
-        let tavily_local_addr = tavily_listener
-            .local_addr()
-            .map_err(|e| format!("Failed to resolve tavily local addr: {}", e))?;
-        let tavily_listen_url = format!("http://{}", tavily_local_addr);
-
-        let tavily_key_manager = Arc::new(Mutex::new(RoundRobinKeyManager::new(
-            config.tavily_api_keys.clone(),
-            config.key_cooldown_seconds,
-        )));
-        new_tavily_manager = Some(tavily_key_manager.clone());
-
-        let tavily_state = ProxyServerState {
-            provider: "tavily",
-            proxy_token: config.proxy_token.clone(),
-            upstream_base_url: config.tavily_upstream_base_url.clone(),
-            key_manager: tavily_key_manager,
-            http_client: http_client.clone(),
-            logs: state.logs.clone(),
+        let detail = match result {
+            Ok(Ok(())) => "listener exited".to_string(),
+            Ok(Err(err)) => err.to_string(),
+            Err(err) => format!("listener task failed: {}", err),
         };
-        let tavily_router = build_tavily_router(tavily_state);
-        let (tavily_shutdown_tx, tavily_shutdown_rx) = oneshot::channel::<()>();
-
-        append_log(
-            &state.logs,
-            "INFO",
-            format!("Tavily proxy starting at {}", tavily_listen_url),
-        )
-        .await;
-
-        let logs = state.logs.clone();
-        let tavily_join_handle = tauri::async_runtime::spawn(async move {
-            let server =
-                axum::serve(tavily_listener, tavily_router).with_graceful_shutdown(async move {
-                    let _ = tavily_shutdown_rx.await;
-                });
-
-            if let Err(err) = server.await {
-                append_log(&logs, "ERROR", format!("Tavily proxy crashed: {}", err)).await;
-            }
-        });
-
-        new_tavily_handle = Some(ServerHandle {
-            shutdown_tx: Some(tavily_shutdown_tx),
-            join_handle: tavily_join_handle,
-            listen_url: tavily_listen_url,
-        });
-    }
+        error!(provider, error = %detail, "{} proxy crashed", name);
+        task_state
+            .events
+            .lifecycle(provider, LifecycleState::Crashed, None, Some(detail));
+        handle_listener_exit(task_state, provider, task_stop_flag).await;
+    });
+
+    state.events.lifecycle(
+        provider,
+        LifecycleState::Started,
+        Some(listen_url.clone()),
+        None,
+    );
+    *runtime.handle_mut(provider) = Some(ServerHandle {
+        shutdown_tx: Some(shutdown_tx),
+        join_handle,
+        listen_url,
+        started_at: Instant::now(),
+        requests,
+        stop_requested,
+    });
+    runtime.supervisor_mut(provider).cancel_restart();
+    drop(runtime);
+
+    *state.active_key_managers.lock().await.slot_mut(provider) = Some(key_manager);
+    Ok(())
+}
 
-    let status = {
+/// Shuts one provider's listener down and waits for it to exit. The listener
+/// stops accepting connections at once; in-flight requests get up to
+/// `DRAIN_TIMEOUT_SECONDS` to finish before they are answered with 503.
+/// Returns whether it was running.
+async fn stop_provider(state: &AppState, provider: &'static str) -> bool {
+    let drain_timeout = Duration::from_secs(state.config.read().await.drain_timeout_seconds);
+    let handle = {
         let mut runtime = state.runtime.lock().await;
-        if let Some(handle) = new_firecrawl_handle {
-            runtime.firecrawl_handle = Some(handle);
+        runtime.supervisor_mut(provider).cancel_restart();
+        let handle = runtime.handle_mut(provider).take();
+        if let Some(handle) = &handle {
+            *runtime.draining_mut(provider) = Some(handle.requests.clone());
         }
-        if let Some(handle) = new_tavily_handle {
-            runtime.tavily_handle = Some(handle);
-        }
-        compose_proxy_status(&runtime, &config)
+        handle
     };
+    let Some(mut handle) = handle else {
+        return false;
+    };
+    let name = provider_display_name(provider);
+    handle.stop_requested.store(true, Ordering::SeqCst);
+    if let Some(shutdown_tx) = handle.shutdown_tx.take() {
+        let _ = shutdown_tx.send(());
+    }
 
+    let in_flight = handle.requests.in_flight();
+    if in_flight > 0 {
+        info!(
+            provider,
+            in_flight, "{} proxy draining in-flight requests", name
+        );
+        state
+            .events
+            .lifecycle(provider, LifecycleState::Draining, None, None);
+    }
+    if tokio::time::timeout(drain_timeout, &mut handle.join_handle)
+        .await
+        .is_err()
     {
-        let mut active = state.active_key_managers.lock().await;
-        if let Some(manager) = new_firecrawl_manager {
-            active.firecrawl = Some(manager);
-        } else if !firecrawl_enabled {
-            active.firecrawl = None;
-        }
-
-        if let Some(manager) = new_tavily_manager {
-            active.tavily = Some(manager);
-        } else if !tavily_enabled {
-            active.tavily = None;
-        }
+        warn!(
+            provider,
+            in_flight = handle.requests.in_flight(),
+            timeout_secs = drain_timeout.as_secs(),
+            "{} proxy drain timed out; aborting remaining requests",
+            name
+        );
+        handle.requests.abort_all();
+        let _ = handle.join_handle.await;
     }
+    *state.runtime.lock().await.draining_mut(provider) = None;
+    *state.active_key_managers.lock().await.slot_mut(provider) = None;
+    state
+        .events
+        .lifecycle(provider, LifecycleState::Stopped, None, None);
+    info!(provider, "{} proxy stopped", name);
+    true
+}
 
-    Ok(status)
+async fn current_proxy_status(state: &AppState) -> ProxyStatus {
+    let config = state.config.read().await.clone();
+    let runtime = state.runtime.lock().await;
+    compose_proxy_status(&runtime, &config)
 }
 
-#[tauri::command]
-async fn stop_proxy(state: tauri::State<'_, AppState>) -> Result<ProxyStatus, String> {
+/// Starts every fully configured provider that is not already running.
+async fn start_all_providers(state: &AppState) -> Result<ProxyStatus, String> {
     let config = state.config.read().await.clone();
-    let (firecrawl_handle, tavily_handle) = {
+    config.validate()?;
+
+    let to_start: Vec<&'static str> = {
         let mut runtime = state.runtime.lock().await;
-        (
-            runtime.firecrawl_handle.take(),
-            runtime.tavily_handle.take(),
-        )
+        PROVIDERS
+            .into_iter()
+            .filter(|p| config.provider_enabled(p) && runtime.handle_mut(p).is_none())
+            .collect()
     };
-
-    if firecrawl_handle.is_none() && tavily_handle.is_none() {
-        let runtime = state.runtime.lock().await;
-        return Ok(compose_proxy_status(&runtime, &config));
-    }
-
-    if let Some(mut handle) = firecrawl_handle {
-        if let Some(shutdown_tx) = handle.shutdown_tx.take() {
-            let _ = shutdown_tx.send(());
+    if !to_start.is_empty() {
+        let http_client = build_http_client(&config)?;
+        for provider in to_start {
+            start_provider(state, &config, provider, http_client.clone()).await?;
         }
-        let _ = handle.join_handle.await;
     }
 
-    if let Some(mut handle) = tavily_handle {
-        if let Some(shutdown_tx) = handle.shutdown_tx.take() {
-            let _ = shutdown_tx.send(());
+    {
+        let mut active = state.active_key_managers.lock().await;
+        for provider in PROVIDERS {
+            if !config.provider_enabled(provider) {
+                *active.slot_mut(provider) = None;
+            }
         }
-        let _ = handle.join_handle.await;
     }
+    Ok(current_proxy_status(state).await)
+}
 
-    // Clear active key manager references
-    {
-        let mut active = state.active_key_managers.lock().await;
-        active.firecrawl = None;
-        active.tavily = None;
+async fn stop_all_providers(state: &AppState) -> ProxyStatus {
+    // Drain both listeners side by side so the total wait is one timeout.
+    let (firecrawl, tavily) = tokio::join!(
+        stop_provider(state, "firecrawl"),
+        stop_provider(state, "tavily")
+    );
+    if firecrawl || tavily {
+        info!("All proxies stopped");
     }
+    current_proxy_status(state).await
+}
 
-    append_log(&state.logs, "INFO", "All proxies stopped".to_string()).await;
-    let runtime = state.runtime.lock().await;
-    Ok(compose_proxy_status(&runtime, &config))
+/// Starts a single provider; it must be fully configured.
+async fn start_single_provider(
+    state: &AppState,
+    provider: &'static str,
+) -> Result<ProxyStatus, String> {
+    let config = state.config.read().await.clone();
+    config.validate_common()?;
+    if !config.provider_enabled(provider) {
+        return Err(format!(
+            "{} is not fully configured",
+            provider_display_name(provider)
+        ));
+    }
+    start_provider(state, &config, provider, build_http_client(&config)?).await?;
+    Ok(current_proxy_status(state).await)
 }
 
-#[tauri::command]
-async fn get_recent_logs(state: tauri::State<'_, AppState>) -> Result<Vec<String>, String> {
-    let logs = state.logs.lock().await;
-    Ok(logs.iter().cloned().collect())
+async fn restart_single_provider(
+    state: &AppState,
+    provider: &'static str,
+) -> Result<ProxyStatus, String> {
+    stop_provider(state, provider).await;
+    start_single_provider(state, provider).await
 }
 
 async fn build_provider_key_status(
     configured: bool,
     running: bool,
-    keys: &[String],
+    keys: &[KeyRecord],
     active_manager: Option<Arc<Mutex<RoundRobinKeyManager>>>,
 ) -> ProviderKeyStatusSnapshot {
     let keys = if let Some(manager) = active_manager {
@@ -1203,39 +2281,19 @@ async fn build_key_status_snapshot_inner(state: &AppState) -> KeyStatusSnapshot
     KeyStatusSnapshot { firecrawl, tavily }
 }
 
-#[tauri::command]
-async fn get_key_status(state: tauri::State<'_, AppState>) -> Result<Vec<KeyStatus>, String> {
-    let snapshot = build_key_status_snapshot_inner(state.inner()).await;
-    Ok(snapshot.firecrawl.keys)
-}
-
-#[tauri::command]
-async fn get_key_status_snapshot(
-    state: tauri::State<'_, AppState>,
-) -> Result<KeyStatusSnapshot, String> {
-    Ok(build_key_status_snapshot_inner(state.inner()).await)
-}
-
-#[tauri::command]
-async fn build_mcp_config(
-    app: tauri::AppHandle,
-    state: tauri::State<'_, AppState>,
-    target: Option<String>,
+/// Renders the MCP client config for `target`. The Tavily launcher script is
+/// written into `data_dir` when Tavily is part of the output.
+fn render_mcp_config(
+    data_dir: &FsPath,
+    config: &ProxyConfig,
+    target: &str,
 ) -> Result<String, String> {
-    let config = state.config.read().await.clone();
     let tavily_launcher = if config.tavily_enabled() {
-        Some(ensure_tavily_local_mcp_launcher(&app)?)
+        Some(ensure_tavily_local_mcp_launcher(data_dir)?)
     } else {
         None
     };
-    let payload = build_mcp_payload(
-        &config,
-        target
-            .unwrap_or_else(|| "both".to_string())
-            .to_ascii_lowercase()
-            .as_str(),
-        tavily_launcher.as_ref(),
-    )?;
+    let payload = build_mcp_payload(config, target, tavily_launcher.as_ref())?;
 
     serde_json::to_string_pretty(&payload)
         .map_err(|e| format!("Failed to serialize MCP config: {}", e))
@@ -1330,30 +2388,6 @@ fn build_mcp_payload(
     Ok(json!({ "mcpServers": servers }))
 }
 
-#[tauri::command]
-async fn get_launch_on_login_enabled(app: tauri::AppHandle) -> Result<bool, String> {
-    app.autolaunch()
-        .is_enabled()
-        .map_err(|e| format!("Failed to read launch-on-login state: {}", e))
-}
-
-#[tauri::command]
-async fn set_launch_on_login_enabled(app: tauri::AppHandle, enabled: bool) -> Result<bool, String> {
-    let manager = app.autolaunch();
-    if enabled {
-        manager
-            .enable()
-            .map_err(|e| format!("Failed to enable launch-on-login: {}", e))?;
-    } else {
-        manager
-            .disable()
-            .map_err(|e| format!("Failed to disable launch-on-login: {}", e))?;
-    }
-    manager
-        .is_enabled()
-        .map_err(|e| format!("Failed to verify launch-on-login state: {}", e))
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1361,15 +2395,9 @@ mod tests {
     fn base_config() -> ProxyConfig {
         ProxyConfig {
             proxy_token: "token".to_string(),
-            firecrawl_api_keys: Vec::new(),
             upstream_base_url: String::new(),
-            tavily_api_keys: Vec::new(),
             tavily_upstream_base_url: String::new(),
-            request_timeout_ms: 60_000,
-            key_cooldown_seconds: 60,
-            host: "127.0.0.1".to_string(),
-            port: 8787,
-            tavily_port: 8788,
+            ..ProxyConfig::default()
         }
     }
 
@@ -1383,7 +2411,7 @@ mod tests {
     #[test]
     fn validate_allows_single_firecrawl_provider() {
         let mut config = base_config();
-        config.firecrawl_api_keys = vec!["fc-key-1".to_string()];
+        config.firecrawl_api_keys = vec!["fc-key-1".into()];
         config.upstream_base_url = "https://api.firecrawl.dev".to_string();
 
         assert!(config.validate().is_ok());
@@ -1392,9 +2420,9 @@ mod tests {
     #[test]
     fn validate_rejects_partial_tavily_provider() {
         let mut config = base_config();
-        config.firecrawl_api_keys = vec!["fc-key-1".to_string()];
+        config.firecrawl_api_keys = vec!["fc-key-1".into()];
         config.upstream_base_url = "https://api.firecrawl.dev".to_string();
-        config.tavily_api_keys = vec!["tvly-key-1".to_string()];
+        config.tavily_api_keys = vec!["tvly-key-1".into()];
 
         let err = config
             .validate()
@@ -1405,7 +2433,7 @@ mod tests {
     #[test]
     fn build_mcp_payload_both_returns_only_configured_provider() {
         let mut config = base_config();
-        config.firecrawl_api_keys = vec!["fc-key-1".to_string()];
+        config.firecrawl_api_keys = vec!["fc-key-1".into()];
         config.upstream_base_url = "https://api.firecrawl.dev".to_string();
 
         let payload = build_mcp_payload(&config, "both", None).expect("mcp payload should build");
@@ -1421,7 +2449,7 @@ mod tests {
     #[test]
     fn build_mcp_payload_rejects_unconfigured_target() {
         let mut config = base_config();
-        config.firecrawl_api_keys = vec!["fc-key-1".to_string()];
+        config.firecrawl_api_keys = vec!["fc-key-1".into()];
         config.upstream_base_url = "https://api.firecrawl.dev".to_string();
 
         let err = build_mcp_payload(&config, "tavily", None)
@@ -1432,7 +2460,7 @@ mod tests {
     #[test]
     fn build_mcp_payload_tavily_uses_local_launcher() {
         let mut config = base_config();
-        config.tavily_api_keys = vec!["tvly-key-1".to_string()];
+        config.tavily_api_keys = vec!["tvly-key-1".into()];
         config.tavily_upstream_base_url = "https://api.tavily.com".to_string();
 
         let launcher = tavily_launcher();
@@ -1472,7 +2500,7 @@ mod tests {
     #[test]
     fn build_mcp_payload_tavily_requires_launcher() {
         let mut config = base_config();
-        config.tavily_api_keys = vec!["tvly-key-1".to_string()];
+        config.tavily_api_keys = vec!["tvly-key-1".into()];
         config.tavily_upstream_base_url = "https://api.tavily.com".to_string();
 
         let err = build_mcp_payload(&config, "tavily", None)
@@ -1483,9 +2511,9 @@ mod tests {
     #[test]
     fn derive_status_flags_handles_running_and_degraded_states() {
         let mut config = base_config();
-        config.firecrawl_api_keys = vec!["fc-key-1".to_string()];
+        config.firecrawl_api_keys = vec!["fc-key-1".into()];
         config.upstream_base_url = "https://api.firecrawl.dev".to_string();
-        config.tavily_api_keys = vec!["tvly-key-1".to_string()];
+        config.tavily_api_keys = vec!["tvly-key-1".into()];
         config.tavily_upstream_base_url = "https://api.tavily.com".to_string();
 
         let all_running = derive_status_flags(&config, true, true);
@@ -1497,92 +2525,260 @@ mod tests {
         let all_stopped = derive_status_flags(&config, false, false);
         assert_eq!(all_stopped, (false, false, false, true, true));
     }
-}
 
-fn show_main_window<R: tauri::Runtime, M: Manager<R>>(manager: &M) {
-    #[cfg(target_os = "macos")]
-    let _ = manager.app_handle().set_dock_visibility(true);
+    #[test]
+    fn key_manager_skips_dead_keys_and_reports_transitions() {
+        let mut manager = RoundRobinKeyManager::new(&["key-a".into(), "key-b".into()], 60);
+
+        let cooled = manager.mark_retryable_failure(1, 429);
+        assert!(cooled.is_cooling_down && !cooled.is_dead);
+
+        let rejected = manager.mark_retryable_failure(0, 401);
+        assert!(rejected.is_cooling_down && !rejected.is_dead);
 
-    if let Some(window) = manager.get_webview_window("main") {
-        let _ = window.show();
-        let _ = window.unminimize();
-        let _ = window.set_focus();
+        // The cooling key is still preferred over a dead one.
+        manager.dead[0] = true;
+        assert_eq!(manager.select_key("").unwrap().index, 1);
+        assert_eq!(manager.select_key("").unwrap().index, 1);
     }
-}
 
-#[cfg_attr(mobile, tauri::mobile_entry_point)]
-pub fn run() {
-    tauri::Builder::default()
-        .on_window_event(|window, event| {
-            if let WindowEvent::CloseRequested { api, .. } = event {
-                api.prevent_close();
-                let _ = window.hide();
+    #[test]
+    fn key_manager_honors_weights_and_keeps_state_across_key_edits() {
+        let mut heavy = KeyRecord::from("key-a");
+        heavy.weight = 2;
+        let disabled = KeyRecord {
+            enabled: false,
+            ..KeyRecord::from("key-c")
+        };
+        let mut manager =
+            RoundRobinKeyManager::new(&[heavy.clone(), "key-b".into(), disabled.clone()], 60);
+        let order: Vec<usize> = (0..6)
+            .map(|_| manager.select_key("").unwrap().index)
+            .collect();
+        assert_eq!(order, vec![0, 0, 1, 0, 0, 1]);
+
+        manager.mark_retryable_failure(1, 429);
+        let enabled = KeyRecord {
+            enabled: true,
+            ..disabled
+        };
+        manager.replace_keys(&["key-b".into(), enabled, heavy]);
+        assert_eq!(manager.key_count(), 3);
+        assert!(manager.key_status(0).is_cooling_down);
+        assert_eq!(manager.key_status(0).fail_count, 1);
+        assert_eq!(manager.select_key("").unwrap().value, "key-c");
+    }
 
-                #[cfg(target_os = "macos")]
-                let _ = window.app_handle().set_dock_visibility(false);
-            }
-        })
-        .on_menu_event(|app, event| {
-            if event.id() == "tray_show" {
-                show_main_window(app);
-            } else if event.id() == "tray_quit" {
-                app.exit(0);
-            }
-        })
-        .plugin(tauri_plugin_autostart::init(
-            MacosLauncher::LaunchAgent,
-            None,
-        ))
-        .plugin(tauri_plugin_clipboard_manager::init())
-        .plugin(tauri_plugin_opener::init())
-        .setup(|app| {
-            let config = load_or_init_config(&app.handle())?;
-            let mut logs = VecDeque::new();
-            logs.push_back(format!(
-                "{} [INFO] App initialized. Config path is in app data directory.",
-                now_ts()
-            ));
+    #[test]
+    fn key_updates_follow_the_key_across_list_edits() {
+        let mut manager = RoundRobinKeyManager::new(&["key-a".into(), "key-b".into()], 60);
+        let selected = manager.select_key("").unwrap();
+        manager.replace_keys(&["key-c".into(), "key-a".into()]);
+        assert_eq!(manager.key_index(&selected.value), Some(1));
+
+        manager.replace_keys(&["key-c".into()]);
+        assert_eq!(manager.key_index(&selected.value), None);
+        manager.observe_rate_limit(&selected.value, &reqwest::header::HeaderMap::new());
+    }
 
-            app.manage(AppState {
-                config: Arc::new(RwLock::new(config)),
-                runtime: Arc::new(Mutex::new(ProxyRuntime::default())),
-                logs: Arc::new(Mutex::new(logs)),
-                active_key_managers: Arc::new(Mutex::new(ActiveKeyManagers::default())),
-            });
-
-            let tray_menu = MenuBuilder::new(app)
-                .text("tray_show", "Show Window")
-                .separator()
-                .text("tray_quit", "Quit")
-                .build()
-                .map_err(|e| format!("Failed to build tray menu: {}", e))?;
-
-            let mut tray = TrayIconBuilder::with_id("main-tray")
-                .menu(&tray_menu)
-                .tooltip("Balance Proxy")
-                .show_menu_on_left_click(true);
-
-            if let Some(icon) = app.default_window_icon().cloned() {
-                tray = tray.icon(icon);
-            }
+    #[test]
+    fn key_probes_update_key_state() {
+        let mut manager = RoundRobinKeyManager::new(&["key-a".into(), "key-b".into()], 60);
+        assert_eq!(
+            manager.record_probe("key-a", ProbeOutcome::Invalid),
+            Some(0)
+        );
+        assert_eq!(
+            manager.record_probe("key-b", ProbeOutcome::Exhausted),
+            Some(1)
+        );
+        assert_eq!(manager.record_probe("key-b", ProbeOutcome::Error), None);
+        assert_eq!(manager.record_probe("key-x", ProbeOutcome::Valid), None);
+        assert!(manager.key_status(0).is_dead);
+        assert!(manager.key_status(1).is_cooling_down);
+        assert_eq!(manager.healthy_count(), 0);
+
+        manager.record_probe("key-a", ProbeOutcome::Valid);
+        assert!(!manager.key_status(0).is_dead);
+        assert_eq!(manager.select_key("").unwrap().value, "key-a");
+    }
+
+    #[test]
+    fn expired_keys_leave_rotation_and_spent_keys_rest_until_reset() {
+        let expired = KeyRecord {
+            expires_on: Some("2000-01-01".to_string()),
+            ..KeyRecord::from("key-old")
+        };
+        let monthly = KeyRecord {
+            quota_reset_day: Some(1),
+            ..KeyRecord::from("key-monthly")
+        };
+        let mut manager = RoundRobinKeyManager::new(&[expired, monthly, "key-plain".into()], 60);
+        let order: Vec<usize> = (0..4)
+            .map(|_| manager.select_key("").unwrap().index)
+            .collect();
+        assert_eq!(order, vec![1, 2, 1, 2]);
+        assert!(manager.key_status(0).is_expired);
+        assert_eq!(manager.healthy_count(), 2);
+
+        let spent = manager.mark_retryable_failure(1, 402);
+        assert!(spent.is_exhausted && spent.is_cooling_down);
+        assert!(spent.days_until_reset.is_some_and(|days| days >= 1));
+        let plain = manager.mark_retryable_failure(2, 402);
+        assert!(!plain.is_exhausted && plain.cooldown_remaining_secs <= 60);
+    }
+
+    #[test]
+    fn pools_of_only_expired_keys_have_no_keys() {
+        let expired = |value: &str| KeyRecord {
+            expires_on: Some("2000-01-01".to_string()),
+            ..KeyRecord::from(value)
+        };
+        let mut manager = RoundRobinKeyManager::new(&[expired("key-a"), expired("key-b")], 60);
+        assert_eq!(manager.select_key("").err(), Some(KeyUnavailable::NoKeys));
+        assert!(manager.acquire_key("").is_err());
+    }
+
+    #[test]
+    fn later_tiers_only_serve_while_earlier_ones_cool_down() {
+        let tiered = |value: &str, tier: &str| KeyRecord {
+            tier: tier.to_string(),
+            ..KeyRecord::from(value)
+        };
+        let keys = [
+            tiered("key-paid", "paid"),
+            tiered("key-free-1", "free"),
+            tiered("key-free-2", "free"),
+        ];
+        let mut manager = RoundRobinKeyManager::new(&keys, 60);
+        manager.set_tier_order(&["free".to_string(), "paid".to_string()]);
+        let order: Vec<usize> = (0..4)
+            .map(|_| manager.select_key("").unwrap().index)
+            .collect();
+        assert_eq!(order, vec![1, 2, 1, 2]);
+
+        manager.mark_retryable_failure(1, 429);
+        manager.mark_retryable_failure(2, 402);
+        let spilled = manager.select_key("").unwrap();
+        assert_eq!((spilled.index, spilled.tier.as_str()), (0, "paid"));
+
+        manager.set_tier_order(&["paid".to_string()]);
+        manager.cooldown_until = vec![None; 3];
+        assert_eq!(manager.select_key("").unwrap().index, 0);
+        assert_eq!(manager.select_key("").unwrap().index, 0);
+        assert_eq!(manager.key_status(0).tier, "paid");
+    }
+
+    #[test]
+    fn routed_requests_draw_from_their_key_pool() {
+        let pooled = |value: &str, pool: &str| KeyRecord {
+            pool: pool.to_string(),
+            ..KeyRecord::from(value)
+        };
+        let keys = [
+            pooled("key-cheap-1", ""),
+            pooled("key-heavy", "heavy"),
+            pooled("key-cheap-2", ""),
+        ];
+        let mut manager = RoundRobinKeyManager::new(&keys, 60);
+        let routes = ["/v1/crawl*=heavy".to_string(), "/v1/map=spare".to_string()];
+        manager.set_key_routes(keys::parse_key_routes(&routes).unwrap());
+
+        assert_eq!(manager.route_pool("/v1/crawl/abc"), "heavy");
+        assert_eq!(manager.route_pool("/v1/scrape"), "");
+        assert_eq!(manager.pool_members("heavy"), vec![1]);
+        // A pool without keys falls back to the default pool.
+        assert_eq!(manager.pool_members("spare"), vec![0, 2]);
+
+        let cheap: Vec<usize> = (0..4)
+            .map(|_| manager.select_key("").unwrap().index)
+            .collect();
+        assert_eq!(cheap, vec![0, 2, 0, 2]);
+        let heavy = manager.select_key("heavy").unwrap();
+        assert_eq!((heavy.index, heavy.pool.as_str()), (1, "heavy"));
+        manager.mark_retryable_failure(1, 429);
+        assert_eq!(manager.select_key("heavy").unwrap().index, 1);
+    }
+
+    #[test]
+    fn keys_at_their_concurrency_limit_are_passed_over() {
+        let limited = KeyRecord {
+            max_concurrency: Some(1),
+            ..KeyRecord::from("key-free")
+        };
+        let mut manager = RoundRobinKeyManager::new(&[limited, "key-paid".into()], 60);
+        let (first, guard) = manager.acquire_key("").unwrap();
+        assert_eq!(first.index, 0);
+        assert_eq!(manager.key_status(0).in_flight, 1);
+
+        let (second, busy) = manager.acquire_key("").unwrap();
+        let (third, _) = manager.acquire_key("").unwrap();
+        assert_eq!((second.index, third.index), (1, 1));
+        drop(busy);
+
+        drop(guard);
+        assert_eq!(manager.key_status(0).in_flight, 0);
+        let (again, _held) = manager.acquire_key("").unwrap();
+        assert_eq!(again.index, 0);
+
+        // A key at its limit is never used past it, even over a cooling one.
+        manager.mark_retryable_failure(1, 429);
+        assert_eq!(manager.select_key("").unwrap().index, 1);
+    }
 
-            tray.build(app)
-                .map_err(|e| format!("Failed to create tray icon: {}", e))?;
-            Ok(())
-        })
-        .invoke_handler(tauri::generate_handler![
-            load_proxy_config,
-            save_proxy_config,
-            get_proxy_status,
-            start_proxy,
-            stop_proxy,
-            get_recent_logs,
-            get_key_status,
-            get_key_status_snapshot,
-            build_mcp_config,
-            get_launch_on_login_enabled,
-            set_launch_on_login_enabled
-        ])
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+    #[tokio::test]
+    async fn saturated_pools_are_busy_until_a_request_finishes() {
+        let limited = |value: &str| KeyRecord {
+            max_concurrency: Some(1),
+            ..KeyRecord::from(value)
+        };
+        let mut manager = RoundRobinKeyManager::new(&[limited("key-a"), limited("key-b")], 60);
+        let (_, first) = manager.acquire_key("").unwrap();
+        let (_, _second) = manager.acquire_key("").unwrap();
+        assert_eq!(
+            manager.acquire_key("").err(),
+            Some(KeyUnavailable::Busy(None))
+        );
+
+        let released = manager.released.clone();
+        let notified = released.notified();
+        tokio::pin!(notified);
+        notified.as_mut().enable();
+        drop(first);
+        tokio::time::timeout(Duration::from_secs(1), notified)
+            .await
+            .expect("a finished request wakes waiters");
+        assert_eq!(manager.acquire_key("").unwrap().0.index, 0);
+    }
+
+    #[test]
+    fn paced_keys_hand_over_before_hitting_their_rate_limit() {
+        let mut manager = RoundRobinKeyManager::new(&["key-a".into(), "key-b".into()], 60);
+        manager.set_rate_limit(1);
+        let (first, _) = manager.acquire_key("").unwrap();
+        assert_eq!(first.index, 0);
+        assert_eq!(manager.key_status(0).rate_remaining, Some(0));
+
+        let (second, _) = manager.acquire_key("").unwrap();
+        assert_eq!(second.index, 1);
+        // Both keys spent their minute: nothing is sent past the limit.
+        match manager.acquire_key("") {
+            Err(KeyUnavailable::Busy(Some(slot))) => assert!(slot > Duration::from_secs(55)),
+            _ => panic!("a paced pool must be busy"),
+        }
+
+        let mut headers = reqwest::header::HeaderMap::new();
+        headers.insert("x-ratelimit-remaining", "0".parse().unwrap());
+        manager.set_rate_limit(0);
+        manager.observe_rate_limit("key-a", &headers);
+        assert_eq!(manager.key_status(0).rate_remaining, Some(0));
+        assert_eq!(manager.key_status(1).rate_remaining, None);
+    }
+
+    #[test]
+    fn empty_pools_report_no_keys() {
+        let mut manager = RoundRobinKeyManager::new(&[], 60);
+        assert_eq!(manager.select_key("").err(), Some(KeyUnavailable::NoKeys));
+        assert!(manager.acquire_key("heavy").is_err());
+    }
 }
@@ -0,0 +1,372 @@
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, UNIX_EPOCH};
+
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+
+use crate::now_ts;
+
+const ACTIVE_LOG_FILENAME: &str = "balance-proxy.log";
+const ROTATED_LOG_PREFIX: &str = "balance-proxy.";
+const SECONDS_PER_DAY: u64 = 86_400;
+/// How long `flush` waits for the writer, e.g. when it is the thread that
+/// panicked.
+const FLUSH_TIMEOUT: Duration = Duration::from_secs(2);
+
+#[derive(Debug, Clone)]
+pub(crate) struct LogFileSettings {
+    pub(crate) dir: PathBuf,
+    pub(crate) max_file_bytes: u64,
+    pub(crate) retention: usize,
+    pub(crate) compress: bool,
+}
+
+enum WriterMessage {
+    Line(u64, String),
+    /// Acknowledged once every line queued before it is written.
+    Flush(Sender<()>),
+}
+
+/// Handle to the log file. Lines are handed to a dedicated writer thread, so
+/// logging never waits on disk I/O, rotation or compression. Clones share the
+/// thread, which is stopped by `close` or when the last clone is dropped.
+#[derive(Clone)]
+pub(crate) struct RotatingLogFile {
+    writer: Arc<WriterThread>,
+}
+
+struct WriterThread {
+    sender: Mutex<Option<Sender<WriterMessage>>>,
+    thread: Mutex<Option<JoinHandle<()>>>,
+}
+
+impl RotatingLogFile {
+    pub(crate) fn open(settings: LogFileSettings) -> io::Result<Self> {
+        let file = LogFileWriter::open(settings)?;
+        let (sender, rx) = mpsc::channel();
+        let thread = thread::Builder::new()
+            .name("log-file".to_string())
+            .spawn(move || run_writer(file, rx))?;
+        Ok(Self {
+            writer: Arc::new(WriterThread {
+                sender: Mutex::new(Some(sender)),
+                thread: Mutex::new(Some(thread)),
+            }),
+        })
+    }
+
+    pub(crate) fn write_line(&self, ts: u64, line: String) {
+        // Lines logged after `close` are dropped.
+        self.writer.send(WriterMessage::Line(ts, line));
+    }
+
+    /// Waits, up to `FLUSH_TIMEOUT`, until every line logged so far is
+    /// written.
+    pub(crate) fn flush(&self) {
+        let (ack, done) = mpsc::channel();
+        self.writer.send(WriterMessage::Flush(ack));
+        let _ = done.recv_timeout(FLUSH_TIMEOUT);
+    }
+
+    /// Writes out the queued lines and stops the writer thread.
+    pub(crate) fn close(&self) {
+        self.writer.close();
+    }
+}
+
+impl WriterThread {
+    fn send(&self, message: WriterMessage) {
+        let sender = self.sender.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(sender) = sender.as_ref() {
+            let _ = sender.send(message);
+        }
+    }
+
+    fn close(&self) {
+        // Dropping the sender ends the writer loop once the queue is empty.
+        self.sender.lock().unwrap_or_else(|e| e.into_inner()).take();
+        let thread = self.thread.lock().unwrap_or_else(|e| e.into_inner()).take();
+        if let Some(thread) = thread {
+            let _ = thread.join();
+        }
+    }
+}
+
+impl Drop for WriterThread {
+    fn drop(&mut self) {
+        self.close();
+    }
+}
+
+fn run_writer(mut file: LogFileWriter, rx: Receiver<WriterMessage>) {
+    while let Ok(message) = rx.recv() {
+        match message {
+            WriterMessage::Line(ts, line) => file.write_line(ts, &line),
+            WriterMessage::Flush(ack) => {
+                let _ = ack.send(());
+            }
+        }
+    }
+}
+
+/// Append-only log file that rotates when it grows past `max_file_bytes` or
+/// when the UTC day changes, keeping at most `retention` rotated files.
+struct LogFileWriter {
+    settings: LogFileSettings,
+    file: Option<File>,
+    size: u64,
+    day: u64,
+}
+
+impl LogFileWriter {
+    fn open(settings: LogFileSettings) -> io::Result<Self> {
+        fs::create_dir_all(&settings.dir)?;
+        let path = settings.dir.join(ACTIVE_LOG_FILENAME);
+        let file = OpenOptions::new().create(true).append(true).open(&path)?;
+        let metadata = file.metadata()?;
+        let modified = metadata
+            .modified()
+            .ok()
+            .and_then(|v| v.duration_since(UNIX_EPOCH).ok())
+            .map_or_else(now_ts, |v| v.as_secs());
+
+        Ok(Self {
+            settings,
+            file: Some(file),
+            size: metadata.len(),
+            day: modified / SECONDS_PER_DAY,
+        })
+    }
+
+    fn write_line(&mut self, ts: u64, line: &str) {
+        let line_len = line.len() as u64 + 1;
+        let day_changed = ts / SECONDS_PER_DAY != self.day;
+        let too_large = self.size > 0 && self.size + line_len > self.settings.max_file_bytes;
+        if day_changed || too_large {
+            // A failed rotation keeps writing to the current file rather than
+            // dropping lines.
+            let _ = self.rotate(ts);
+        }
+
+        if let Some(file) = self.file.as_mut() {
+            if writeln!(file, "{}", line).is_ok() {
+                self.size += line_len;
+            }
+        }
+    }
+
+    fn rotate(&mut self, ts: u64) -> io::Result<()> {
+        let active_path = self.settings.dir.join(ACTIVE_LOG_FILENAME);
+        self.file = None;
+
+        if self.size > 0 {
+            let rotated_path = unique_rotated_path(&self.settings.dir, ts);
+            fs::rename(&active_path, &rotated_path)?;
+            if self.settings.compress {
+                gzip_in_place(&rotated_path)?;
+            }
+        }
+
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&active_path)?;
+        self.file = Some(file);
+        self.size = 0;
+        self.day = ts / SECONDS_PER_DAY;
+        prune_rotated_files(&self.settings.dir, self.settings.retention)
+    }
+}
+
+fn unique_rotated_path(dir: &Path, ts: u64) -> PathBuf {
+    let mut suffix = 0;
+    loop {
+        let name = if suffix == 0 {
+            format!("{}{}.log", ROTATED_LOG_PREFIX, ts)
+        } else {
+            format!("{}{}-{}.log", ROTATED_LOG_PREFIX, ts, suffix)
+        };
+        let path = dir.join(name);
+        let gz_path = path.with_extension("log.gz");
+        if !path.exists() && !gz_path.exists() {
+            return path;
+        }
+        suffix += 1;
+    }
+}
+
+fn gzip_in_place(path: &Path) -> io::Result<()> {
+    let gz_path = path.with_extension("log.gz");
+    let mut input = File::open(path)?;
+    let mut encoder = GzEncoder::new(File::create(&gz_path)?, Compression::default());
+    io::copy(&mut input, &mut encoder)?;
+    encoder.finish()?;
+    fs::remove_file(path)
+}
+
+/// Rotated files sorted oldest first, by the rotation timestamp and
+/// collision suffix embedded in their names.
+fn rotated_log_files(dir: &Path) -> io::Result<Vec<PathBuf>> {
+    let mut files: Vec<(u64, u64, PathBuf)> = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        let Some(name) = path.file_name().and_then(|v| v.to_str()) else {
+            continue;
+        };
+        let Some(rest) = name.strip_prefix(ROTATED_LOG_PREFIX) else {
+            continue;
+        };
+        if !(rest.ends_with(".log") || rest.ends_with(".log.gz")) {
+            continue;
+        }
+        let stem = rest.trim_end_matches(".gz").trim_end_matches(".log");
+        let (ts_part, suffix_part) = stem.split_once('-').unwrap_or((stem, "0"));
+        let (Ok(ts), Ok(suffix)) = (ts_part.parse::<u64>(), suffix_part.parse::<u64>()) else {
+            continue;
+        };
+        files.push((ts, suffix, path));
+    }
+    files.sort();
+    Ok(files.into_iter().map(|(_, _, path)| path).collect())
+}
+
+fn prune_rotated_files(dir: &Path, retention: usize) -> io::Result<()> {
+    let files = rotated_log_files(dir)?;
+    if files.len() > retention {
+        for path in &files[..files.len() - retention] {
+            fs::remove_file(path)?;
+        }
+    }
+    Ok(())
+}
+
+fn line_timestamp(line: &str) -> Option<u64> {
+    line.split(' ').next()?.parse().ok()
+}
+
+/// Concatenates every log line with a timestamp inside `[start_ts, end_ts]`
+/// from rotated and active files into `destination`. Returns the line count.
+pub(crate) fn export_log_range(
+    dir: &Path,
+    start_ts: Option<u64>,
+    end_ts: Option<u64>,
+    destination: &Path,
+) -> io::Result<usize> {
+    let mut sources = rotated_log_files(dir)?;
+    let active_path = dir.join(ACTIVE_LOG_FILENAME);
+    if active_path.exists() {
+        sources.push(active_path);
+    }
+
+    let mut output = File::create(destination)?;
+    let mut exported = 0;
+    for source in sources {
+        let file = File::open(&source)?;
+        let reader: Box<dyn Read> = if source.extension().is_some_and(|ext| ext == "gz") {
+            Box::new(GzDecoder::new(file))
+        } else {
+            Box::new(file)
+        };
+
+        for line in BufReader::new(reader).lines() {
+            let line = line?;
+            let Some(ts) = line_timestamp(&line) else {
+                continue;
+            };
+            if start_ts.is_some_and(|start| ts < start) || end_ts.is_some_and(|end| ts > end) {
+                continue;
+            }
+            writeln!(output, "{}", line)?;
+            exported += 1;
+        }
+    }
+    output.flush()?;
+    Ok(exported)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use uuid::Uuid;
+
+    fn temp_settings(max_file_bytes: u64, retention: usize, compress: bool) -> LogFileSettings {
+        LogFileSettings {
+            dir: std::env::temp_dir().join(format!("balance-proxy-logs-{}", Uuid::new_v4())),
+            max_file_bytes,
+            retention,
+            compress,
+        }
+    }
+
+    #[test]
+    fn rotates_by_size_and_prunes_to_retention() {
+        let settings = temp_settings(32, 2, false);
+        let dir = settings.dir.clone();
+        let mut log = LogFileWriter::open(settings).expect("log file should open");
+        let ts = now_ts();
+        for i in 0..6 {
+            log.write_line(ts, &format!("{} [INFO] line-{:02}-padding", ts, i));
+        }
+
+        let rotated = rotated_log_files(&dir).expect("rotated files should list");
+        assert_eq!(rotated.len(), 2);
+        let active = fs::read_to_string(dir.join(ACTIVE_LOG_FILENAME)).unwrap();
+        assert!(active.contains("line-05"));
+        let _ = fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn lines_are_written_by_the_writer_thread() {
+        let settings = temp_settings(1024, 2, false);
+        let dir = settings.dir.clone();
+        let log = RotatingLogFile::open(settings).expect("log file should open");
+        let active_path = dir.join(ACTIVE_LOG_FILENAME);
+        let ts = now_ts();
+        log.write_line(ts, format!("{} [INFO] from-the-thread", ts));
+        log.flush();
+        assert!(fs::read_to_string(&active_path)
+            .unwrap()
+            .contains("from-the-thread"));
+
+        // Closing drains the queue and joins the thread; later lines are
+        // dropped.
+        log.write_line(ts, format!("{} [INFO] before-close", ts));
+        log.clone().close();
+        log.write_line(ts, format!("{} [INFO] after-close", ts));
+        log.flush();
+        let written = fs::read_to_string(&active_path).unwrap();
+        assert!(written.contains("before-close") && !written.contains("after-close"));
+        let _ = fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn export_reads_compressed_files_and_filters_range() {
+        let settings = temp_settings(1, 10, true);
+        let dir = settings.dir.clone();
+        let mut log = LogFileWriter::open(settings).expect("log file should open");
+        let base = now_ts();
+        for offset in 0..4 {
+            let ts = base + offset;
+            log.write_line(ts, &format!("{} [INFO] entry-{}", ts, offset));
+        }
+        assert!(rotated_log_files(&dir)
+            .unwrap()
+            .iter()
+            .all(|p| p.extension().is_some_and(|ext| ext == "gz")));
+
+        let destination = dir.join("export.log");
+        let count = export_log_range(&dir, Some(base + 1), Some(base + 2), &destination)
+            .expect("export should succeed");
+        assert_eq!(count, 2);
+        let exported = fs::read_to_string(&destination).unwrap();
+        assert!(exported.contains("entry-1") && exported.contains("entry-2"));
+        assert!(!exported.contains("entry-0") && !exported.contains("entry-3"));
+        let _ = fs::remove_dir_all(dir);
+    }
+}
@@ -0,0 +1,244 @@
+use std::collections::VecDeque;
+use std::fmt::{self, Write as _};
+use std::panic;
+use std::sync::{Arc, Mutex};
+
+use opentelemetry::trace::TracerProvider as _;
+use opentelemetry_sdk::trace::SdkTracerProvider;
+use tracing::field::{Field, Visit};
+use tracing::{Event, Subscriber};
+use tracing_subscriber::filter::LevelFilter;
+use tracing_subscriber::layer::{Context, SubscriberExt};
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::{fmt as tracing_fmt, reload, Layer, Registry};
+
+use crate::events::{EventBus, ProxyEvent};
+use crate::log_files::RotatingLogFile;
+use crate::now_ts;
+
+const MAX_LOG_LINES: usize = 500;
+
+/// In-memory tail of recent log lines served to the UI.
+#[derive(Clone, Default)]
+pub(crate) struct LogBuffer {
+    lines: Arc<Mutex<VecDeque<String>>>,
+}
+
+impl LogBuffer {
+    fn push(&self, line: String) {
+        let mut guard = self.lines.lock().unwrap_or_else(|e| e.into_inner());
+        if guard.len() >= MAX_LOG_LINES {
+            guard.pop_front();
+        }
+        guard.push_back(line);
+    }
+
+    pub(crate) fn snapshot(&self) -> Vec<String> {
+        let guard = self.lines.lock().unwrap_or_else(|e| e.into_inner());
+        guard.iter().cloned().collect()
+    }
+}
+
+/// Handle to the installed subscriber, used to change the level at runtime.
+#[derive(Clone)]
+pub(crate) struct LogHandle {
+    level: reload::Handle<LevelFilter, Registry>,
+    tracer_provider: Option<SdkTracerProvider>,
+    file: Option<RotatingLogFile>,
+}
+
+impl LogHandle {
+    pub(crate) fn set_level(&self, level: LevelFilter) -> Result<(), String> {
+        self.level
+            .reload(level)
+            .map_err(|e| format!("Failed to update log level: {}", e))
+    }
+
+    /// Flushes spans still queued for OTLP export and lines still queued for
+    /// the log file. Call once before exit.
+    pub(crate) fn shutdown(&self) {
+        if let Some(provider) = &self.tracer_provider {
+            let _ = provider.shutdown();
+        }
+        if let Some(file) = &self.file {
+            file.close();
+        }
+    }
+}
+
+pub(crate) fn parse_log_level(raw: &str) -> Result<LevelFilter, String> {
+    match raw.trim().to_ascii_lowercase().as_str() {
+        "trace" => Ok(LevelFilter::TRACE),
+        "debug" => Ok(LevelFilter::DEBUG),
+        "info" => Ok(LevelFilter::INFO),
+        "warn" | "warning" => Ok(LevelFilter::WARN),
+        "error" => Ok(LevelFilter::ERROR),
+        "off" => Ok(LevelFilter::OFF),
+        _ => Err(format!(
+            "LOG_LEVEL must be one of trace/debug/info/warn/error/off, got '{}'",
+            raw.trim()
+        )),
+    }
+}
+
+/// Installs the global subscriber: stdout output (plain or JSON), the line
+/// layer that feeds `get_recent_logs`, live `log-line` events and the
+/// optional log file, plus span export when a tracer provider is given.
+pub(crate) fn init_tracing(
+    level: LevelFilter,
+    json: bool,
+    buffer: LogBuffer,
+    events: EventBus,
+    file: Option<RotatingLogFile>,
+    tracer_provider: Option<SdkTracerProvider>,
+) -> Result<LogHandle, String> {
+    let (filter, handle) = reload::Layer::new(level);
+    let stdout_layer = if json {
+        tracing_fmt::layer()
+            .json()
+            .flatten_event(true)
+            .with_current_span(false)
+            .with_span_list(false)
+            .boxed()
+    } else {
+        tracing_fmt::layer().with_target(false).boxed()
+    };
+
+    let otel_layer = tracer_provider.as_ref().map(|provider| {
+        tracing_opentelemetry::layer().with_tracer(provider.tracer("balance-proxy"))
+    });
+
+    tracing_subscriber::registry()
+        .with(filter)
+        .with(stdout_layer)
+        .with(LineLayer {
+            buffer,
+            events,
+            file: file.clone(),
+        })
+        .with(otel_layer)
+        .try_init()
+        .map_err(|e| format!("Failed to initialize logging: {}", e))?;
+
+    if let Some(file) = &file {
+        flush_on_panic(file.clone());
+    }
+    Ok(LogHandle {
+        level: handle,
+        tracer_provider,
+        file,
+    })
+}
+
+/// Records panics in the log file and waits for the queued lines to reach
+/// it, since a panic can end the process before the writer catches up.
+fn flush_on_panic(file: RotatingLogFile) {
+    let previous = panic::take_hook();
+    panic::set_hook(Box::new(move |info| {
+        let ts = now_ts();
+        file.write_line(
+            ts,
+            format_line(ts, "ERROR", &format!("panic: {}", info), ""),
+        );
+        file.flush();
+        previous(info);
+    }));
+}
+
+struct LineLayer {
+    buffer: LogBuffer,
+    events: EventBus,
+    file: Option<RotatingLogFile>,
+}
+
+impl<S: Subscriber> Layer<S> for LineLayer {
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+        let mut visitor = LineVisitor::default();
+        event.record(&mut visitor);
+        let ts = now_ts();
+        let line = format_line(
+            ts,
+            event.metadata().level().as_str(),
+            &visitor.message,
+            &visitor.fields,
+        );
+        if let Some(file) = &self.file {
+            file.write_line(ts, line.clone());
+        }
+        self.buffer.push(line.clone());
+        self.events.publish(ProxyEvent::LogLine(line));
+    }
+}
+
+fn format_line(ts: u64, level: &str, message: &str, fields: &str) -> String {
+    let mut line = format!("{} [{}] {}", ts, level, message);
+    if !fields.is_empty() {
+        if !message.is_empty() {
+            line.push(' ');
+        }
+        line.push_str(fields);
+    }
+    line
+}
+
+#[derive(Default)]
+struct LineVisitor {
+    message: String,
+    fields: String,
+}
+
+impl LineVisitor {
+    fn push_field(&mut self, name: &str, value: fmt::Arguments<'_>) {
+        if !self.fields.is_empty() {
+            self.fields.push(' ');
+        }
+        let _ = write!(self.fields, "{}={}", name, value);
+    }
+}
+
+impl Visit for LineVisitor {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        if field.name() == "message" {
+            self.message = value.to_string();
+        } else {
+            self.push_field(field.name(), format_args!("{}", value));
+        }
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        if field.name() == "message" {
+            self.message = format!("{:?}", value);
+        } else {
+            self.push_field(field.name(), format_args!("{:?}", value));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_log_level_accepts_env_style_values() {
+        assert_eq!(parse_log_level("INFO"), Ok(LevelFilter::INFO));
+        assert_eq!(parse_log_level(" warning "), Ok(LevelFilter::WARN));
+        assert!(parse_log_level("verbose").is_err());
+    }
+
+    #[test]
+    fn format_line_keeps_level_marker_for_ui_filters() {
+        let line = format_line(42, "WARN", "proxy_retry", "provider=tavily status=429");
+        assert_eq!(line, "42 [WARN] proxy_retry provider=tavily status=429");
+    }
+
+    #[test]
+    fn log_buffer_drops_oldest_lines() {
+        let buffer = LogBuffer::default();
+        for i in 0..(MAX_LOG_LINES + 3) {
+            buffer.push(i.to_string());
+        }
+        let lines = buffer.snapshot();
+        assert_eq!(lines.len(), MAX_LOG_LINES);
+        assert_eq!(lines.first().map(String::as_str), Some("3"));
+    }
+}
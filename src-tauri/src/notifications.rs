@@ -0,0 +1,267 @@
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use crate::events::{KeyStateChange, LifecycleState, ProxyEvent};
+use crate::provider_display_name;
+
+/// Key alerts carry the key fingerprint, so a key keeps its throttle when
+/// edits to the key list move it to another index.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum AlertKind {
+    PoolExhausted,
+    PoolLow,
+    KeyDead(String),
+    KeyExhausted(String),
+    ListenerCrashed,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Alert {
+    pub(crate) title: String,
+    pub(crate) body: String,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct AlertSettings {
+    pub(crate) min_healthy_keys: usize,
+    pub(crate) cooldown: Duration,
+}
+
+/// Turns proxy events into user-facing alerts. Pool alerts fire when the
+/// healthy-key count crosses a threshold, and every alert kind is throttled
+/// per provider so a flapping pool does not flood the desktop.
+#[derive(Default)]
+pub(crate) struct AlertPolicy {
+    last_healthy: HashMap<&'static str, usize>,
+    last_sent: HashMap<(&'static str, AlertKind), Instant>,
+}
+
+impl AlertPolicy {
+    pub(crate) fn evaluate(
+        &mut self,
+        event: &ProxyEvent,
+        settings: AlertSettings,
+        now: Instant,
+    ) -> Option<Alert> {
+        let (provider, kind, alert) = match event {
+            ProxyEvent::KeyState(change) => self.key_alert(change, settings)?,
+            ProxyEvent::Lifecycle(lifecycle) if lifecycle.state == LifecycleState::Crashed => (
+                lifecycle.provider,
+                AlertKind::ListenerCrashed,
+                Alert {
+                    title: format!(
+                        "{} proxy stopped unexpectedly",
+                        provider_display_name(lifecycle.provider)
+                    ),
+                    body: lifecycle
+                        .error
+                        .clone()
+                        .unwrap_or_else(|| "The listener exited.".to_string()),
+                },
+            ),
+            ProxyEvent::Lifecycle(lifecycle) => {
+                // A fresh start resets the threshold tracking for the pool.
+                self.last_healthy.remove(lifecycle.provider);
+                return None;
+            }
+            _ => return None,
+        };
+
+        let throttle_key = (provider, kind);
+        if self
+            .last_sent
+            .get(&throttle_key)
+            .is_some_and(|sent| now.duration_since(*sent) < settings.cooldown)
+        {
+            return None;
+        }
+        self.last_sent.insert(throttle_key, now);
+        Some(alert)
+    }
+
+    fn key_alert(
+        &mut self,
+        change: &KeyStateChange,
+        settings: AlertSettings,
+    ) -> Option<(&'static str, AlertKind, Alert)> {
+        let provider = change.provider;
+        let name = provider_display_name(provider);
+        let previous = self
+            .last_healthy
+            .insert(provider, change.healthy_keys)
+            .unwrap_or(change.total_keys);
+
+        if change.healthy_keys == 0 && previous > 0 {
+            let body = if change.key.is_dead && change.total_keys == 1 {
+                "The only configured key was rejected by the upstream.".to_string()
+            } else {
+                format!(
+                    "All {} keys are cooling down or invalid; requests will fail until one recovers.",
+                    change.total_keys
+                )
+            };
+            return Some((
+                provider,
+                AlertKind::PoolExhausted,
+                Alert {
+                    title: format!("{} key pool exhausted", name),
+                    body,
+                },
+            ));
+        }
+
+        if change.key.is_dead {
+            return Some((
+                provider,
+                AlertKind::KeyDead(change.key_fingerprint.clone()),
+                Alert {
+                    title: format!("{} key #{} is invalid", name, change.key.index + 1),
+                    body: format!(
+                        "{} was rejected by the upstream and removed from rotation.",
+                        change.key.key_preview
+                    ),
+                },
+            ));
+        }
+
+        if change.key.is_exhausted {
+            let rest = match change.key.days_until_reset {
+                Some(1) => "until its quota resets tomorrow".to_string(),
+                Some(days) => format!("until its quota resets in {} days", days),
+                None => "until its quota resets".to_string(),
+            };
+            return Some((
+                provider,
+                AlertKind::KeyExhausted(change.key_fingerprint.clone()),
+                Alert {
+                    title: format!("{} key #{} is out of credits", name, change.key.index + 1),
+                    body: format!("{} is out of rotation {}.", change.key.key_preview, rest),
+                },
+            ));
+        }
+
+        if change.healthy_keys < settings.min_healthy_keys && previous >= settings.min_healthy_keys
+        {
+            return Some((
+                provider,
+                AlertKind::PoolLow,
+                Alert {
+                    title: format!("{} key pool running low", name),
+                    body: format!(
+                        "Only {} of {} keys are healthy.",
+                        change.healthy_keys, change.total_keys
+                    ),
+                },
+            ));
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::events::ProxyLifecycle;
+    use crate::KeyStatus;
+
+    const SETTINGS: AlertSettings = AlertSettings {
+        min_healthy_keys: 2,
+        cooldown: Duration::from_secs(600),
+    };
+
+    fn key_event(index: usize, is_dead: bool, healthy_keys: usize) -> ProxyEvent {
+        ProxyEvent::KeyState(key_change(index, is_dead, healthy_keys))
+    }
+
+    fn key_change(index: usize, is_dead: bool, healthy_keys: usize) -> KeyStateChange {
+        KeyStateChange {
+            provider: "tavily",
+            key: KeyStatus {
+                index,
+                key_preview: format!("tvly-{}", index),
+                label: String::new(),
+                tier: String::new(),
+                pool: String::new(),
+                in_flight: 0,
+                max_concurrency: None,
+                rate_remaining: None,
+                is_cooling_down: !is_dead,
+                cooldown_remaining_secs: if is_dead { 0 } else { 60 },
+                fail_count: 1,
+                is_dead,
+                is_exhausted: false,
+                is_expired: false,
+                days_until_reset: None,
+                days_until_expiry: None,
+            },
+            key_fingerprint: format!("fp-{}", index),
+            healthy_keys,
+            total_keys: 3,
+        }
+    }
+
+    #[test]
+    fn pool_alerts_fire_on_threshold_crossings() {
+        let mut policy = AlertPolicy::default();
+        let now = Instant::now();
+
+        let low = policy.evaluate(&key_event(0, false, 1), SETTINGS, now);
+        assert!(low.is_some_and(|a| a.title.contains("running low")));
+        // Still below the threshold: no new crossing, no alert.
+        assert_eq!(
+            policy.evaluate(&key_event(1, false, 1), SETTINGS, now),
+            None
+        );
+
+        let exhausted = policy.evaluate(&key_event(2, false, 0), SETTINGS, now);
+        assert!(exhausted.is_some_and(|a| a.title.contains("exhausted")));
+    }
+
+    #[test]
+    fn alerts_are_throttled_per_kind() {
+        let mut policy = AlertPolicy::default();
+        let crashed = ProxyEvent::Lifecycle(ProxyLifecycle {
+            provider: "firecrawl",
+            state: LifecycleState::Crashed,
+            listen_url: None,
+            error: Some("accept failed".to_string()),
+        });
+        let now = Instant::now();
+
+        assert!(policy.evaluate(&crashed, SETTINGS, now).is_some());
+        assert!(policy
+            .evaluate(&crashed, SETTINGS, now + Duration::from_secs(60))
+            .is_none());
+        assert!(policy
+            .evaluate(&crashed, SETTINGS, now + Duration::from_secs(601))
+            .is_some());
+        // Another kind is throttled independently.
+        assert!(policy
+            .evaluate(&key_event(1, true, 2), SETTINGS, now)
+            .is_some_and(|a| a.title.contains("#2 is invalid")));
+    }
+
+    #[test]
+    fn spent_keys_alert_once_per_key_across_list_edits() {
+        let mut policy = AlertPolicy::default();
+        let now = Instant::now();
+        let spent = |index| {
+            let mut change = key_change(index, false, 2);
+            change.key.is_exhausted = true;
+            change.key.days_until_reset = Some(12);
+            change.key_fingerprint = "fp-spent".to_string();
+            ProxyEvent::KeyState(change)
+        };
+
+        let alert = policy.evaluate(&spent(0), SETTINGS, now).unwrap();
+        assert_eq!(alert.title, "Tavily key #1 is out of credits");
+        assert!(alert.body.contains("resets in 12 days"));
+        // The same key at a new index is still throttled; another key is not.
+        assert!(policy.evaluate(&spent(2), SETTINGS, now).is_none());
+        let mut other = key_change(1, false, 2);
+        other.key.is_exhausted = true;
+        assert!(policy
+            .evaluate(&ProxyEvent::KeyState(other), SETTINGS, now)
+            .is_some());
+    }
+}
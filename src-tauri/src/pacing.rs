@@ -0,0 +1,153 @@
+//! Proactive per-key rate pacing. Each key remembers when it was used in the
+//! last minute, so a provider's known requests-per-minute limit is kept
+//! before upstream has to answer 429. `X-RateLimit-Remaining` (and
+//! `X-RateLimit-Reset`, when sent) tighten the budget to what upstream
+//! actually reports.
+
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+use reqwest::header::HeaderMap;
+
+use crate::now_ts;
+
+pub(crate) const RATE_WINDOW: Duration = Duration::from_secs(60);
+/// `X-RateLimit-Reset` values above this are Unix timestamps, not seconds.
+const RESET_EPOCH_THRESHOLD: u64 = 1_000_000_000;
+
+#[derive(Debug, Clone, Default)]
+pub(crate) struct KeyPacer {
+    /// When requests went out in the current window, oldest first.
+    sent: VecDeque<Instant>,
+    /// Requests upstream said are left, counted down per send, and when that
+    /// budget refills.
+    observed: Option<(u64, Instant)>,
+}
+
+impl KeyPacer {
+    /// How long the key must wait before its next request stays within
+    /// `per_minute` (0 for no configured limit) and the observed budget.
+    pub(crate) fn wait(&self, per_minute: u64, now: Instant) -> Duration {
+        let mut wait = Duration::ZERO;
+        if per_minute > 0 {
+            let recent: Vec<Instant> = self.recent(now).collect();
+            if recent.len() as u64 >= per_minute {
+                let frees_at = recent[recent.len() - per_minute as usize] + RATE_WINDOW;
+                wait = frees_at.saturating_duration_since(now);
+            }
+        }
+        if let Some((0, refill_at)) = self.observed {
+            wait = wait.max(refill_at.saturating_duration_since(now));
+        }
+        wait
+    }
+
+    /// Requests left before the key has to wait; `None` when neither a limit
+    /// is configured nor one was observed.
+    pub(crate) fn remaining(&self, per_minute: u64, now: Instant) -> Option<u64> {
+        let configured =
+            (per_minute > 0).then(|| per_minute.saturating_sub(self.recent(now).count() as u64));
+        let observed = self
+            .observed
+            .filter(|(_, refill_at)| *refill_at > now)
+            .map(|(remaining, _)| remaining);
+        match (configured, observed) {
+            (Some(configured), Some(observed)) => Some(configured.min(observed)),
+            (configured, observed) => configured.or(observed),
+        }
+    }
+
+    pub(crate) fn record_send(&mut self, at: Instant) {
+        while self
+            .sent
+            .front()
+            .is_some_and(|sent| *sent + RATE_WINDOW <= at)
+        {
+            self.sent.pop_front();
+        }
+        self.sent.push_back(at);
+        if let Some((remaining, refill_at)) = &mut self.observed {
+            if *refill_at <= at {
+                self.observed = None;
+            } else {
+                *remaining = remaining.saturating_sub(1);
+            }
+        }
+    }
+
+    /// Takes in the rate-limit headers of an upstream response, if any.
+    pub(crate) fn observe(&mut self, headers: &HeaderMap, now: Instant) {
+        let header = |name: &str| {
+            headers
+                .get(name)
+                .and_then(|value| value.to_str().ok())
+                .and_then(|value| value.trim().parse::<u64>().ok())
+        };
+        let Some(remaining) = header("x-ratelimit-remaining") else {
+            return;
+        };
+        let refill_in = match header("x-ratelimit-reset") {
+            Some(reset) if reset > RESET_EPOCH_THRESHOLD => {
+                Duration::from_secs(reset.saturating_sub(now_ts()))
+            }
+            Some(reset) => Duration::from_secs(reset),
+            None => RATE_WINDOW,
+        };
+        self.observed = Some((remaining, now + refill_in.min(RATE_WINDOW)));
+    }
+
+    fn recent(&self, now: Instant) -> impl Iterator<Item = Instant> + '_ {
+        self.sent
+            .iter()
+            .copied()
+            .filter(move |sent| *sent + RATE_WINDOW > now)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::header::HeaderValue;
+
+    #[test]
+    fn paces_to_the_configured_rate() {
+        let start = Instant::now();
+        let mut pacer = KeyPacer::default();
+        assert_eq!(pacer.remaining(0, start), None);
+        pacer.record_send(start);
+        pacer.record_send(start + Duration::from_secs(10));
+        assert_eq!(pacer.remaining(3, start + Duration::from_secs(10)), Some(1));
+        assert_eq!(pacer.wait(3, start), Duration::ZERO);
+
+        let now = start + Duration::from_secs(20);
+        assert_eq!(pacer.wait(2, now), Duration::from_secs(40));
+        assert_eq!(pacer.wait(0, now), Duration::ZERO);
+        assert_eq!(
+            pacer.wait(2, start + Duration::from_secs(61)),
+            Duration::ZERO
+        );
+    }
+
+    #[test]
+    fn observed_headers_tighten_the_budget() {
+        let now = Instant::now();
+        let mut pacer = KeyPacer::default();
+        let mut headers = HeaderMap::new();
+        headers.insert("x-ratelimit-remaining", HeaderValue::from_static("1"));
+        headers.insert("x-ratelimit-reset", HeaderValue::from_static("30"));
+        pacer.observe(&headers, now);
+        assert_eq!(pacer.remaining(100, now), Some(1));
+        assert_eq!(pacer.wait(100, now), Duration::ZERO);
+
+        pacer.record_send(now);
+        assert_eq!(pacer.remaining(0, now), Some(0));
+        assert_eq!(pacer.wait(100, now), Duration::from_secs(30));
+        assert_eq!(
+            pacer.wait(100, now + Duration::from_secs(30)),
+            Duration::ZERO
+        );
+
+        pacer.observe(&HeaderMap::new(), now);
+        assert_eq!(pacer.remaining(0, now), Some(0));
+    }
+}
@@ -0,0 +1,244 @@
+//! Named configuration profiles. The active profile is always the regular
+//! `proxy-config.json`; the others are stored as-is (sealed secrets included)
+//! in `profiles/<name>.json`, and activating one swaps the two files.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::Serialize;
+
+use crate::config_schema::{render_plain_config, write_atomic};
+use crate::ProxyConfig;
+
+pub(crate) const DEFAULT_PROFILE: &str = "default";
+const PROFILES_DIR_NAME: &str = "profiles";
+const ACTIVE_MARKER_FILE: &str = "active-profile";
+const MAX_PROFILE_NAME_LEN: usize = 40;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ProfileSummary {
+    pub(crate) name: String,
+    pub(crate) active: bool,
+}
+
+/// Profile names become file names, so only letters, digits, spaces, `-`
+/// and `_` are allowed.
+pub(crate) fn validate_profile_name(name: &str) -> Result<(), String> {
+    let valid_chars = name
+        .chars()
+        .all(|c| c.is_alphanumeric() || matches!(c, ' ' | '-' | '_'));
+    if name.trim().is_empty() || name.trim() != name {
+        return Err("Profile name cannot be empty or start/end with spaces".to_string());
+    }
+    if !valid_chars || name.chars().count() > MAX_PROFILE_NAME_LEN {
+        return Err(format!(
+            "Profile name may use up to {} letters, digits, spaces, '-' and '_'",
+            MAX_PROFILE_NAME_LEN
+        ));
+    }
+    Ok(())
+}
+
+pub(crate) fn active_profile(config_path: &Path) -> String {
+    fs::read_to_string(profiles_dir(config_path).join(ACTIVE_MARKER_FILE))
+        .ok()
+        .map(|name| name.trim().to_string())
+        .filter(|name| validate_profile_name(name).is_ok())
+        .unwrap_or_else(|| DEFAULT_PROFILE.to_string())
+}
+
+/// The active profile followed by the stored ones, sorted by name.
+pub(crate) fn list_profiles(config_path: &Path) -> Result<Vec<ProfileSummary>, String> {
+    let active = active_profile(config_path);
+    let mut names: Vec<String> = match fs::read_dir(profiles_dir(config_path)) {
+        Ok(entries) => entries
+            .filter_map(Result::ok)
+            .filter_map(|entry| {
+                let name = entry.file_name().to_string_lossy().to_string();
+                let stem = name.strip_suffix(".json")?.to_string();
+                (stem != active && validate_profile_name(&stem).is_ok()).then_some(stem)
+            })
+            .collect(),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Vec::new(),
+        Err(e) => return Err(format!("Failed to read profiles: {}", e)),
+    };
+    names.sort();
+
+    let mut profiles = vec![ProfileSummary {
+        name: active,
+        active: true,
+    }];
+    profiles.extend(names.into_iter().map(|name| ProfileSummary {
+        name,
+        active: false,
+    }));
+    Ok(profiles)
+}
+
+/// Stores a new profile with the default settings.
+pub(crate) fn create_profile(config_path: &Path, name: &str) -> Result<(), String> {
+    let text = render_plain_config(&ProxyConfig::default().normalized())?;
+    write_new_profile(config_path, name, &text)
+}
+
+/// Stores a copy of `source` (active or not) under `name`.
+pub(crate) fn clone_profile(config_path: &Path, source: &str, name: &str) -> Result<(), String> {
+    let text = fs::read_to_string(stored_path(config_path, source)?)
+        .map_err(|e| format!("Failed to read profile '{}': {}", source, e))?;
+    write_new_profile(config_path, name, &text)
+}
+
+pub(crate) fn rename_profile(config_path: &Path, name: &str, new_name: &str) -> Result<(), String> {
+    validate_profile_name(new_name)?;
+    ensure_unused(config_path, new_name)?;
+    if name == active_profile(config_path) {
+        return write_active_marker(config_path, new_name);
+    }
+    fs::rename(
+        stored_path(config_path, name)?,
+        profile_file(config_path, new_name),
+    )
+    .map_err(|e| format!("Failed to rename profile: {}", e))
+}
+
+pub(crate) fn delete_profile(config_path: &Path, name: &str) -> Result<(), String> {
+    if name == active_profile(config_path) {
+        return Err("The active profile cannot be deleted".to_string());
+    }
+    fs::remove_file(stored_path(config_path, name)?)
+        .map_err(|e| format!("Failed to delete profile: {}", e))
+}
+
+/// Makes `name` the active profile: the current config is stored under the
+/// active name and the profile's file takes its place.
+pub(crate) fn activate_profile(config_path: &Path, name: &str) -> Result<(), String> {
+    let active = active_profile(config_path);
+    if name == active {
+        return Ok(());
+    }
+    let target = stored_path(config_path, name)?;
+    let incoming = fs::read_to_string(&target)
+        .map_err(|e| format!("Failed to read profile '{}': {}", name, e))?;
+    let outgoing =
+        fs::read_to_string(config_path).map_err(|e| format!("Failed to read config: {}", e))?;
+
+    write_atomic(&profile_file(config_path, &active), &outgoing)
+        .map_err(|e| format!("Failed to store profile '{}': {}", active, e))?;
+    write_atomic(config_path, &incoming).map_err(|e| format!("Failed to write config: {}", e))?;
+    write_active_marker(config_path, name)?;
+    fs::remove_file(&target).map_err(|e| format!("Failed to tidy profile files: {}", e))
+}
+
+fn write_new_profile(config_path: &Path, name: &str, text: &str) -> Result<(), String> {
+    validate_profile_name(name)?;
+    ensure_unused(config_path, name)?;
+    fs::create_dir_all(profiles_dir(config_path))
+        .map_err(|e| format!("Failed to create profiles dir: {}", e))?;
+    write_atomic(&profile_file(config_path, name), text)
+        .map_err(|e| format!("Failed to write profile: {}", e))
+}
+
+fn ensure_unused(config_path: &Path, name: &str) -> Result<(), String> {
+    if list_profiles(config_path)?.iter().any(|p| p.name == name) {
+        return Err(format!("A profile named '{}' already exists", name));
+    }
+    Ok(())
+}
+
+/// Where the settings of `name` currently live.
+fn stored_path(config_path: &Path, name: &str) -> Result<PathBuf, String> {
+    validate_profile_name(name)?;
+    if name == active_profile(config_path) {
+        return Ok(config_path.to_path_buf());
+    }
+    let path = profile_file(config_path, name);
+    if !path.is_file() {
+        return Err(format!("Profile '{}' not found", name));
+    }
+    Ok(path)
+}
+
+fn write_active_marker(config_path: &Path, name: &str) -> Result<(), String> {
+    let dir = profiles_dir(config_path);
+    fs::create_dir_all(&dir).map_err(|e| format!("Failed to create profiles dir: {}", e))?;
+    write_atomic(&dir.join(ACTIVE_MARKER_FILE), name)
+        .map_err(|e| format!("Failed to record active profile: {}", e))
+}
+
+fn profiles_dir(config_path: &Path) -> PathBuf {
+    config_path.with_file_name(PROFILES_DIR_NAME)
+}
+
+fn profile_file(config_path: &Path, name: &str) -> PathBuf {
+    profiles_dir(config_path).join(format!("{}.json", name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config_schema::{load_config_file, write_config_file};
+    use crate::keys::key_values;
+    use uuid::Uuid;
+
+    #[test]
+    fn profile_names_must_be_safe_file_names() {
+        assert!(validate_profile_name("team paid").is_ok());
+        assert!(validate_profile_name("个人-free_1").is_ok());
+        for bad in [
+            "",
+            " padded",
+            "../escape",
+            "a/b",
+            "dots.json",
+            &"x".repeat(41),
+        ] {
+            assert!(validate_profile_name(bad).is_err(), "{:?} accepted", bad);
+        }
+    }
+
+    #[test]
+    fn activating_swaps_configs_and_keeps_the_previous_profile() {
+        let dir = std::env::temp_dir().join(format!("balance-proxy-profiles-{}", Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("proxy-config.json");
+        let personal = ProxyConfig {
+            tavily_api_keys: vec!["tvly-free".into()],
+            ..ProxyConfig::default()
+        };
+        write_config_file(&path, &personal).unwrap();
+        let port = |path: &Path| load_config_file(path).unwrap().config().unwrap().port;
+
+        create_profile(&path, "team").unwrap();
+        assert!(create_profile(&path, "team").is_err());
+        assert!(create_profile(&path, DEFAULT_PROFILE).is_err());
+        rename_profile(&path, DEFAULT_PROFILE, "personal").unwrap();
+
+        activate_profile(&path, "team").unwrap();
+        assert_eq!(active_profile(&path), "team");
+        assert!(load_config_file(&path)
+            .unwrap()
+            .config()
+            .unwrap()
+            .tavily_api_keys
+            .is_empty());
+        let stored = load_config_file(&profile_file(&path, "personal")).unwrap();
+        let stored_keys = stored.config().unwrap().tavily_api_keys;
+        assert_eq!(key_values(&stored_keys), vec!["tvly-free"]);
+        assert_eq!(port(&path), 8787);
+
+        clone_profile(&path, "personal", "personal copy").unwrap();
+        assert!(delete_profile(&path, "team").is_err());
+        delete_profile(&path, "personal copy").unwrap();
+        let names: Vec<(String, bool)> = list_profiles(&path)
+            .unwrap()
+            .into_iter()
+            .map(|p| (p.name, p.active))
+            .collect();
+        assert_eq!(
+            names,
+            vec![("team".to_string(), true), ("personal".to_string(), false)]
+        );
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,376 @@
+//! Encryption at rest for the secret config fields (the proxy token and the
+//! upstream keys). Each secret is sealed separately with ChaCha20-Poly1305 so
+//! the file keeps its shape; the key comes either from a random key file next
+//! to the config or from a passphrase run through Argon2id.
+
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use argon2::Argon2;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use crate::{key_fingerprint, ProxyConfig};
+
+pub(crate) const ENCRYPTION_MODES: [&str; 3] = ["off", "keyfile", "passphrase"];
+pub(crate) const PASSPHRASE_ENV_VAR: &str = "BALANCE_PROXY_PASSPHRASE";
+const KEY_FILE_NAME: &str = "secret.key";
+const SECRET_FIELDS: [&str; 3] = ["proxyToken", "firecrawlApiKeys", "tavilyApiKeys"];
+const ENCRYPTED_PREFIX: &str = "enc:v1:";
+const NONCE_LEN: usize = 12;
+const SALT_LEN: usize = 16;
+
+/// Passphrase entered in the app, which takes precedence over the env var.
+static SESSION_PASSPHRASE: Mutex<Option<String>> = Mutex::new(None);
+
+/// Stored in the config file next to the sealed fields so a reader knows how
+/// to derive the key.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "scheme", rename_all = "camelCase")]
+pub(crate) enum SecretsHeader {
+    KeyFile,
+    Passphrase { salt: String },
+}
+
+pub(crate) struct SecretCipher(ChaCha20Poly1305);
+
+impl SecretCipher {
+    fn from_key(key: &[u8; 32]) -> Self {
+        Self(ChaCha20Poly1305::new(Key::from_slice(key)))
+    }
+
+    fn seal(&self, plaintext: &str) -> Result<String, String> {
+        let nonce = ChaCha20Poly1305::generate_nonce(&mut OsRng);
+        let ciphertext = self
+            .0
+            .encrypt(&nonce, plaintext.as_bytes())
+            .map_err(|_| "Failed to encrypt secret".to_string())?;
+        let mut sealed = nonce.to_vec();
+        sealed.extend_from_slice(&ciphertext);
+        Ok(format!("{}{}", ENCRYPTED_PREFIX, BASE64.encode(sealed)))
+    }
+
+    fn open(&self, value: &str) -> Result<String, String> {
+        let Some(encoded) = value.strip_prefix(ENCRYPTED_PREFIX) else {
+            // Written before encryption was turned on.
+            return Ok(value.to_string());
+        };
+        let sealed = BASE64
+            .decode(encoded)
+            .map_err(|_| "Encrypted secret is not valid base64".to_string())?;
+        if sealed.len() < NONCE_LEN {
+            return Err("Encrypted secret is truncated".to_string());
+        }
+        let (nonce, ciphertext) = sealed.split_at(NONCE_LEN);
+        let plaintext = self
+            .0
+            .decrypt(Nonce::from_slice(nonce), ciphertext)
+            .map_err(|_| {
+                "Failed to decrypt config secrets: wrong passphrase or key file".to_string()
+            })?;
+        String::from_utf8(plaintext).map_err(|_| "Decrypted secret is not UTF-8".to_string())
+    }
+}
+
+pub(crate) fn validate_mode(mode: &str) -> Result<(), String> {
+    if ENCRYPTION_MODES.contains(&mode) {
+        Ok(())
+    } else {
+        Err(format!(
+            "SECRETS_ENCRYPTION must be one of: {}",
+            ENCRYPTION_MODES.join(", ")
+        ))
+    }
+}
+
+pub(crate) fn set_session_passphrase(passphrase: Option<String>) {
+    if let Ok(mut session) = SESSION_PASSPHRASE.lock() {
+        *session = passphrase.filter(|p| !p.is_empty());
+    }
+}
+
+fn passphrase() -> Option<String> {
+    let session = SESSION_PASSPHRASE.lock().ok().and_then(|s| s.clone());
+    session.or_else(|| {
+        std::env::var(PASSPHRASE_ENV_VAR)
+            .ok()
+            .filter(|p| !p.is_empty())
+    })
+}
+
+/// Creates the header and cipher for writing a config in `mode`, generating
+/// the key file on first use.
+pub(crate) fn new_cipher(
+    mode: &str,
+    config_path: &Path,
+) -> Result<(SecretsHeader, SecretCipher), String> {
+    match mode {
+        "keyfile" => {
+            let key = read_or_create_key_file(&key_file_path(config_path))?;
+            Ok((SecretsHeader::KeyFile, SecretCipher::from_key(&key)))
+        }
+        "passphrase" => {
+            let passphrase = passphrase().ok_or_else(|| {
+                format!(
+                    "A passphrase is required to encrypt secrets; enter one or set {}",
+                    PASSPHRASE_ENV_VAR
+                )
+            })?;
+            let salt: [u8; SALT_LEN] = rand_bytes();
+            let key = derive_key(&passphrase, &salt)?;
+            let header = SecretsHeader::Passphrase {
+                salt: BASE64.encode(salt),
+            };
+            Ok((header, SecretCipher::from_key(&key)))
+        }
+        other => Err(format!("Unknown secrets encryption mode '{}'", other)),
+    }
+}
+
+/// Cipher for reading a file sealed under `header`; `None` when it needs a
+/// passphrase nobody has provided yet.
+pub(crate) fn open_cipher(
+    header: &SecretsHeader,
+    config_path: &Path,
+) -> Result<Option<SecretCipher>, String> {
+    match header {
+        SecretsHeader::KeyFile => {
+            let path = key_file_path(config_path);
+            let text = fs::read_to_string(&path).map_err(|e| {
+                format!("Failed to read key file {}: {}", path.to_string_lossy(), e)
+            })?;
+            Ok(Some(SecretCipher::from_key(&decode_key(&text)?)))
+        }
+        SecretsHeader::Passphrase { salt } => {
+            let Some(passphrase) = passphrase() else {
+                return Ok(None);
+            };
+            let salt = BASE64
+                .decode(salt)
+                .map_err(|_| "Config passphrase salt is not valid base64".to_string())?;
+            Ok(Some(SecretCipher::from_key(&derive_key(
+                &passphrase,
+                &salt,
+            )?)))
+        }
+    }
+}
+
+pub(crate) fn seal_fields(
+    fields: &mut Map<String, Value>,
+    cipher: &SecretCipher,
+) -> Result<(), String> {
+    map_secret_strings(fields, |value| cipher.seal(value))
+}
+
+pub(crate) fn open_fields(
+    fields: &mut Map<String, Value>,
+    cipher: &SecretCipher,
+) -> Result<(), String> {
+    map_secret_strings(fields, |value| cipher.open(value))
+}
+
+/// Blanks the secret fields of a file that cannot be decrypted yet, so the
+/// rest of the config is usable but no provider is considered configured.
+pub(crate) fn clear_fields(fields: &mut Map<String, Value>) {
+    for field in SECRET_FIELDS {
+        if let Some(value) = fields.get_mut(field) {
+            *value = match value {
+                Value::Array(_) => Value::Array(Vec::new()),
+                _ => Value::String(String::new()),
+            };
+        }
+    }
+}
+
+fn map_secret_strings(
+    fields: &mut Map<String, Value>,
+    transform: impl Fn(&str) -> Result<String, String>,
+) -> Result<(), String> {
+    for field in SECRET_FIELDS {
+        match fields.get_mut(field) {
+            Some(Value::String(text)) => *text = transform(text)?,
+            Some(Value::Array(items)) => {
+                for item in items.iter_mut() {
+                    // Key lists hold plain strings or records with a `value`.
+                    let text = match item {
+                        Value::String(text) => text,
+                        Value::Object(record) => match record.get_mut("value") {
+                            Some(Value::String(text)) => text,
+                            _ => continue,
+                        },
+                        _ => continue,
+                    };
+                    *text = transform(text)?;
+                }
+            }
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+/// Displayed in place of a secret: a short prefix and the key fingerprint.
+/// `unmask_secrets` maps it back, so a form can round-trip masked values.
+pub(crate) fn mask(secret: &str) -> String {
+    if secret.is_empty() {
+        return String::new();
+    }
+    let prefix: String = secret.chars().take(4).collect();
+    format!("{}•••• [{}]", prefix, key_fingerprint(secret))
+}
+
+/// Copy of `config` that is safe to hand to the UI.
+pub(crate) fn mask_secrets(config: &ProxyConfig) -> ProxyConfig {
+    let mut masked = config.clone();
+    masked.proxy_token = mask(&config.proxy_token);
+    for key in masked
+        .firecrawl_api_keys
+        .iter_mut()
+        .chain(masked.tavily_api_keys.iter_mut())
+    {
+        key.value = mask(&key.value);
+    }
+    masked
+}
+
+/// Replaces masked values in `incoming` with the secrets they stand for in
+/// `current`; anything else is taken as a newly entered secret.
+pub(crate) fn unmask_secrets(mut incoming: ProxyConfig, current: &ProxyConfig) -> ProxyConfig {
+    let known: Vec<(String, &String)> = current
+        .firecrawl_api_keys
+        .iter()
+        .chain(&current.tavily_api_keys)
+        .map(|key| &key.value)
+        .chain(std::iter::once(&current.proxy_token))
+        .map(|secret| (mask(secret), secret))
+        .collect();
+    let unmask = |value: &mut String| {
+        if let Some((_, secret)) = known.iter().find(|(masked, _)| masked == value) {
+            *value = (*secret).clone();
+        }
+    };
+    unmask(&mut incoming.proxy_token);
+    incoming
+        .firecrawl_api_keys
+        .iter_mut()
+        .chain(incoming.tavily_api_keys.iter_mut())
+        .for_each(|key| unmask(&mut key.value));
+    incoming
+}
+
+fn key_file_path(config_path: &Path) -> PathBuf {
+    config_path.with_file_name(KEY_FILE_NAME)
+}
+
+fn read_or_create_key_file(path: &Path) -> Result<[u8; 32], String> {
+    match fs::read_to_string(path) {
+        Ok(text) => return decode_key(&text),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+        Err(e) => return Err(format!("Failed to read key file: {}", e)),
+    }
+    let key: [u8; 32] = rand_bytes();
+    let mut options = OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let mut file = options
+        .open(path)
+        .map_err(|e| format!("Failed to create key file: {}", e))?;
+    file.write_all(BASE64.encode(key).as_bytes())
+        .and_then(|_| file.sync_all())
+        .map_err(|e| format!("Failed to write key file: {}", e))?;
+    Ok(key)
+}
+
+fn decode_key(text: &str) -> Result<[u8; 32], String> {
+    BASE64
+        .decode(text.trim())
+        .ok()
+        .and_then(|bytes| bytes.try_into().ok())
+        .ok_or_else(|| "Key file does not hold a 32-byte base64 key".to_string())
+}
+
+fn derive_key(passphrase: &str, salt: &[u8]) -> Result<[u8; 32], String> {
+    let mut key = [0u8; 32];
+    Argon2::default()
+        .hash_password_into(passphrase.as_bytes(), salt, &mut key)
+        .map_err(|e| format!("Failed to derive key from passphrase: {}", e))?;
+    Ok(key)
+}
+
+fn rand_bytes<const N: usize>() -> [u8; N] {
+    use chacha20poly1305::aead::rand_core::RngCore;
+    let mut bytes = [0u8; N];
+    OsRng.fill_bytes(&mut bytes);
+    bytes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::keys::key_values;
+
+    fn secret_fields(config: &ProxyConfig) -> Map<String, Value> {
+        match serde_json::to_value(config).unwrap() {
+            Value::Object(fields) => fields,
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn sealed_fields_open_only_with_the_same_key() {
+        let config = ProxyConfig {
+            proxy_token: "local-token".to_string(),
+            tavily_api_keys: vec!["tvly-1".into(), "tvly-2".into()],
+            ..ProxyConfig::default()
+        };
+        let cipher = SecretCipher::from_key(&[7; 32]);
+        let mut fields = secret_fields(&config);
+        seal_fields(&mut fields, &cipher).unwrap();
+        let sealed = fields["tavilyApiKeys"][0]["value"]
+            .as_str()
+            .unwrap()
+            .to_string();
+        assert!(sealed.starts_with(ENCRYPTED_PREFIX));
+        assert!(!serde_json::to_string(&fields).unwrap().contains("tvly-1"));
+        assert_eq!(fields["host"], "127.0.0.1");
+
+        let mut wrong = fields.clone();
+        assert!(open_fields(&mut wrong, &SecretCipher::from_key(&[8; 32])).is_err());
+        open_fields(&mut fields, &cipher).unwrap();
+        assert_eq!(fields, secret_fields(&config));
+
+        // Plaintext values from before encryption was enabled pass through.
+        assert_eq!(cipher.open("tvly-plain").unwrap(), "tvly-plain");
+    }
+
+    #[test]
+    fn masked_values_round_trip_and_new_secrets_pass_through() {
+        let current = ProxyConfig {
+            proxy_token: "local-token".to_string(),
+            firecrawl_api_keys: vec!["fc-aaaaaaaaaaaaaaaa".into()],
+            ..ProxyConfig::default()
+        };
+        let mut edited = mask_secrets(&current);
+        assert!(!edited.firecrawl_api_keys[0].value.contains("aaaaaaaaaaaa"));
+        assert!(!edited.proxy_token.contains("token"));
+        edited.firecrawl_api_keys.push("fc-new".into());
+
+        let saved = unmask_secrets(edited, &current);
+        assert_eq!(saved.proxy_token, "local-token");
+        assert_eq!(
+            key_values(&saved.firecrawl_api_keys),
+            vec!["fc-aaaaaaaaaaaaaaaa", "fc-new"]
+        );
+    }
+}
@@ -0,0 +1,175 @@
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::atomic::AtomicBool;
+use std::sync::Arc;
+use std::time::Duration;
+
+use tracing::{error, info, warn};
+
+use crate::{provider_display_name, start_single_provider, AppState};
+
+const INITIAL_BACKOFF: Duration = Duration::from_secs(1);
+const MAX_BACKOFF: Duration = Duration::from_secs(60);
+/// Consecutive failed restarts after which the supervisor gives up until the
+/// provider is started again by hand.
+const MAX_CONSECUTIVE_FAILURES: u32 = 10;
+/// A listener that ran at least this long before exiting counts as healthy;
+/// its crash restarts the backoff from the beginning.
+const STABLE_UPTIME: Duration = Duration::from_secs(60);
+
+/// Per-provider crash bookkeeping kept in `ProxyRuntime`.
+#[derive(Debug, Default)]
+pub(crate) struct SupervisorState {
+    pub(crate) crash_count: u32,
+    consecutive_failures: u32,
+    restart_pending: bool,
+}
+
+impl SupervisorState {
+    /// Records an unexpected exit and returns the delay before the next
+    /// restart, or `None` once the failure budget is spent.
+    fn record_crash(&mut self, uptime: Duration) -> Option<Duration> {
+        self.crash_count += 1;
+        if uptime >= STABLE_UPTIME {
+            self.consecutive_failures = 0;
+        }
+        self.record_failed_restart()
+    }
+
+    fn record_failed_restart(&mut self) -> Option<Duration> {
+        self.consecutive_failures += 1;
+        self.restart_pending = self.consecutive_failures <= MAX_CONSECUTIVE_FAILURES;
+        self.restart_pending
+            .then(|| backoff_delay(self.consecutive_failures))
+    }
+
+    /// Cancels a scheduled restart, e.g. because the provider was stopped.
+    pub(crate) fn cancel_restart(&mut self) {
+        self.restart_pending = false;
+    }
+}
+
+fn backoff_delay(consecutive_failures: u32) -> Duration {
+    let exponent = consecutive_failures.saturating_sub(1).min(16);
+    (INITIAL_BACKOFF * 2u32.pow(exponent)).min(MAX_BACKOFF)
+}
+
+/// Called by a listener task that exited without being asked to. Removes the
+/// dead handle and restarts the provider with exponential backoff.
+///
+/// Returns a boxed `Send` future: restarting spawns a new listener task that
+/// may call back into this function, and the compiler cannot prove that
+/// recursive cycle `Send` without a concrete type in between.
+pub(crate) fn handle_listener_exit(
+    state: AppState,
+    provider: &'static str,
+    stop_flag: Arc<AtomicBool>,
+) -> Pin<Box<dyn Future<Output = ()> + Send>> {
+    Box::pin(restart_after_crash(state, provider, stop_flag))
+}
+
+async fn restart_after_crash(state: AppState, provider: &'static str, stop_flag: Arc<AtomicBool>) {
+    let name = provider_display_name(provider);
+    let mut delay = {
+        let mut runtime = state.runtime.lock().await;
+        let Some(handle) = runtime
+            .handle_mut(provider)
+            .take_if(|h| Arc::ptr_eq(&h.stop_requested, &stop_flag))
+        else {
+            return;
+        };
+        match runtime
+            .supervisor_mut(provider)
+            .record_crash(handle.started_at.elapsed())
+        {
+            Some(delay) => delay,
+            None => {
+                error!(
+                    provider,
+                    "{} proxy keeps crashing; giving up on restarts", name
+                );
+                return;
+            }
+        }
+    };
+    *state.active_key_managers.lock().await.slot_mut(provider) = None;
+
+    loop {
+        warn!(
+            provider,
+            delay_ms = delay.as_millis() as u64,
+            "{} proxy restart scheduled",
+            name
+        );
+        tokio::time::sleep(delay).await;
+        if !state
+            .runtime
+            .lock()
+            .await
+            .supervisor_mut(provider)
+            .restart_pending
+        {
+            return;
+        }
+
+        let result = start_single_provider(&state, provider).await;
+        let mut runtime = state.runtime.lock().await;
+        let supervisor = runtime.supervisor_mut(provider);
+        match result {
+            Ok(_) => {
+                supervisor.cancel_restart();
+                info!(
+                    provider,
+                    crashes = supervisor.crash_count,
+                    "{} proxy restarted",
+                    name
+                );
+                return;
+            }
+            Err(err) => match supervisor.record_failed_restart() {
+                Some(next) => {
+                    warn!(provider, error = %err, "{} proxy restart failed", name);
+                    delay = next;
+                }
+                None => {
+                    error!(provider, error = %err, "{} proxy restart failed; giving up", name);
+                    return;
+                }
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn backoff_doubles_up_to_the_cap() {
+        assert_eq!(backoff_delay(1), Duration::from_secs(1));
+        assert_eq!(backoff_delay(2), Duration::from_secs(2));
+        assert_eq!(backoff_delay(4), Duration::from_secs(8));
+        assert_eq!(backoff_delay(30), MAX_BACKOFF);
+    }
+
+    #[test]
+    fn supervisor_gives_up_after_repeated_failures_and_resets_when_stable() {
+        let mut supervisor = SupervisorState::default();
+        assert_eq!(
+            supervisor.record_crash(Duration::from_secs(1)),
+            Some(Duration::from_secs(1))
+        );
+        for _ in 1..MAX_CONSECUTIVE_FAILURES {
+            assert!(supervisor.record_failed_restart().is_some());
+        }
+        assert_eq!(supervisor.record_failed_restart(), None);
+        assert!(!supervisor.restart_pending);
+
+        // A listener that stayed up long enough starts a fresh budget.
+        assert_eq!(
+            supervisor.record_crash(STABLE_UPTIME),
+            Some(Duration::from_secs(1))
+        );
+        assert_eq!(supervisor.crash_count, 2);
+    }
+}
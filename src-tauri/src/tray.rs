@@ -0,0 +1,341 @@
+use std::time::Duration;
+
+use tauri::image::Image;
+use tauri::menu::{Menu, MenuBuilder, MenuItemBuilder, SubmenuBuilder};
+use tauri::tray::TrayIconBuilder;
+use tauri::{AppHandle, Manager, Wry};
+use tauri_plugin_clipboard_manager::ClipboardExt;
+use tokio::sync::broadcast;
+use tracing::warn;
+
+use crate::desktop::{app_data_dir, config_path, show_main_window, switch_profile};
+use crate::events::ProxyEvent;
+use crate::profiles::{self, ProfileSummary};
+use crate::{
+    current_proxy_status, parse_provider, provider_display_name, render_mcp_config,
+    restart_single_provider, start_single_provider, stop_provider, AppState, ProxyStatus,
+    PROVIDERS,
+};
+
+pub(crate) const TRAY_ID: &str = "main-tray";
+const TRAY_REFRESH_DEBOUNCE: Duration = Duration::from_millis(250);
+
+const STATUS_RUNNING: [u8; 4] = [0x22, 0xC5, 0x5E, 0xFF];
+const STATUS_DEGRADED: [u8; 4] = [0xF5, 0x9E, 0x0B, 0xFF];
+const STATUS_STOPPED: [u8; 4] = [0xEF, 0x44, 0x44, 0xFF];
+
+struct ProviderTrayView {
+    provider: &'static str,
+    configured: bool,
+    running: bool,
+    healthy_keys: Option<usize>,
+    total_keys: usize,
+}
+
+impl ProviderTrayView {
+    fn label(&self) -> String {
+        let name = provider_display_name(self.provider);
+        match (
+            self.configured || self.running,
+            self.running,
+            self.healthy_keys,
+        ) {
+            (false, _, _) => format!("{}: not configured", name),
+            (_, true, Some(healthy)) => format!(
+                "{}: running · {}/{} keys healthy",
+                name, healthy, self.total_keys
+            ),
+            (_, true, None) => format!("{}: running", name),
+            (_, false, _) => format!("{}: stopped · {} keys", name, self.total_keys),
+        }
+    }
+}
+
+pub(crate) fn create_tray(app: &AppHandle) -> Result<(), String> {
+    let menu = MenuBuilder::new(app)
+        .text("tray_show", "Show Window")
+        .separator()
+        .text("tray_quit", "Quit")
+        .build()
+        .map_err(|e| format!("Failed to build tray menu: {}", e))?;
+
+    let mut tray = TrayIconBuilder::with_id(TRAY_ID)
+        .menu(&menu)
+        .tooltip("Balance Proxy")
+        .show_menu_on_left_click(true);
+
+    if let Some(icon) = app.default_window_icon().cloned() {
+        tray = tray.icon(icon);
+    }
+
+    tray.build(app)
+        .map_err(|e| format!("Failed to create tray icon: {}", e))?;
+    Ok(())
+}
+
+/// Rebuilds the tray whenever a provider starts/stops or a key changes state.
+/// Bursts of events (e.g. several keys cooling at once) collapse into one
+/// rebuild.
+pub(crate) fn watch_tray_status(app: AppHandle, mut rx: broadcast::Receiver<ProxyEvent>) {
+    tauri::async_runtime::spawn(async move {
+        refresh_tray(&app).await;
+        loop {
+            match rx.recv().await {
+                Ok(ProxyEvent::Lifecycle(_)) | Ok(ProxyEvent::KeyState(_)) => {}
+                Ok(_) | Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                Err(broadcast::error::RecvError::Closed) => break,
+            }
+            tokio::time::sleep(TRAY_REFRESH_DEBOUNCE).await;
+            while let Ok(_) | Err(broadcast::error::TryRecvError::Lagged(_)) = rx.try_recv() {}
+            refresh_tray(&app).await;
+        }
+    });
+}
+
+pub(crate) async fn refresh_tray(app: &AppHandle) {
+    let Some(state) = app.try_state::<AppState>() else {
+        return;
+    };
+    let state = state.inner().clone();
+    let status = current_proxy_status(&state).await;
+    let views = provider_views(&state, &status).await;
+    let profiles = config_path(app)
+        .and_then(|path| profiles::list_profiles(&path))
+        .unwrap_or_default();
+
+    let Some(tray) = app.tray_by_id(TRAY_ID) else {
+        return;
+    };
+    match build_menu(app, &views, &profiles) {
+        Ok(menu) => {
+            let _ = tray.set_menu(Some(menu));
+        }
+        Err(err) => warn!(error = %err, "Failed to rebuild tray menu"),
+    }
+
+    let (label, color) = if status.running {
+        ("Running", STATUS_RUNNING)
+    } else if status.degraded {
+        ("Degraded", STATUS_DEGRADED)
+    } else {
+        ("Stopped", STATUS_STOPPED)
+    };
+    let _ = tray.set_tooltip(Some(format!("Balance Proxy — {}", label)));
+    if let Some(base) = app.default_window_icon() {
+        let rgba = with_status_dot(base.rgba(), base.width(), base.height(), color);
+        let _ = tray.set_icon(Some(Image::new_owned(rgba, base.width(), base.height())));
+    }
+}
+
+async fn provider_views(state: &AppState, status: &ProxyStatus) -> Vec<ProviderTrayView> {
+    let config = state.config.read().await.clone();
+    let mut views = Vec::with_capacity(PROVIDERS.len());
+    for provider in PROVIDERS {
+        let manager = state
+            .active_key_managers
+            .lock()
+            .await
+            .slot_mut(provider)
+            .clone();
+        let (healthy_keys, total_keys) = match manager {
+            Some(manager) => {
+                let manager = manager.lock().await;
+                (Some(manager.healthy_count()), manager.key_count())
+            }
+            None => (None, config.enabled_keys(provider).len()),
+        };
+        views.push(ProviderTrayView {
+            provider,
+            configured: config.provider_enabled(provider),
+            running: if provider == "tavily" {
+                status.tavily_running
+            } else {
+                status.firecrawl_running
+            },
+            healthy_keys,
+            total_keys,
+        });
+    }
+    views
+}
+
+fn build_menu(
+    app: &AppHandle,
+    views: &[ProviderTrayView],
+    profiles: &[ProfileSummary],
+) -> tauri::Result<Menu<Wry>> {
+    let mut menu = MenuBuilder::new(app);
+    if let Some(active) = profiles.iter().find(|p| p.active) {
+        let profile_item =
+            MenuItemBuilder::with_id("tray_status:profile", format!("Profile: {}", active.name))
+                .enabled(false)
+                .build(app)?;
+        menu = menu.item(&profile_item);
+    }
+    for view in views {
+        let status_item =
+            MenuItemBuilder::with_id(format!("tray_status:{}", view.provider), view.label())
+                .enabled(false)
+                .build(app)?;
+        menu = menu.item(&status_item);
+    }
+    menu = menu.separator();
+
+    for view in views {
+        let controls = SubmenuBuilder::new(app, provider_display_name(view.provider))
+            .item(
+                &MenuItemBuilder::with_id(format!("tray_start:{}", view.provider), "Start")
+                    .enabled(view.configured && !view.running)
+                    .build(app)?,
+            )
+            .item(
+                &MenuItemBuilder::with_id(format!("tray_stop:{}", view.provider), "Stop")
+                    .enabled(view.running)
+                    .build(app)?,
+            )
+            .item(
+                &MenuItemBuilder::with_id(format!("tray_restart:{}", view.provider), "Restart")
+                    .enabled(view.configured)
+                    .build(app)?,
+            )
+            .build()?;
+        menu = menu.item(&controls);
+    }
+
+    let copy = SubmenuBuilder::new(app, "Copy MCP config")
+        .text("tray_copy_mcp:firecrawl", "Firecrawl")
+        .text("tray_copy_mcp:tavily", "Tavily")
+        .text("tray_copy_mcp:both", "Both")
+        .build()?;
+
+    let mut switch = SubmenuBuilder::new(app, "Switch profile");
+    for profile in profiles {
+        switch = switch.item(
+            &MenuItemBuilder::with_id(format!("tray_profile:{}", profile.name), &profile.name)
+                .enabled(!profile.active)
+                .build(app)?,
+        );
+    }
+    let switch = switch.enabled(profiles.len() > 1).build()?;
+
+    menu.separator()
+        .item(&switch)
+        .item(&copy)
+        .separator()
+        .text("tray_show", "Show Window")
+        .separator()
+        .text("tray_quit", "Quit")
+        .build()
+}
+
+pub(crate) fn handle_menu_event(app: &AppHandle, id: &str) {
+    match id {
+        "tray_show" => show_main_window(app),
+        "tray_quit" => {
+            if let Some(state) = app.try_state::<AppState>() {
+                state.log_handle.shutdown();
+            }
+            app.exit(0);
+        }
+        _ => {
+            let Some((action, target)) = id.split_once(':') else {
+                return;
+            };
+            let Some(state) = app.try_state::<AppState>() else {
+                return;
+            };
+            let state = state.inner().clone();
+            let app = app.clone();
+            let action = action.to_string();
+            let target = target.to_string();
+            tauri::async_runtime::spawn(async move {
+                if let Err(err) = run_menu_action(&app, &state, &action, &target).await {
+                    warn!(action = %action, target = %target, error = %err, "Tray action failed");
+                }
+            });
+        }
+    }
+}
+
+async fn run_menu_action(
+    app: &AppHandle,
+    state: &AppState,
+    action: &str,
+    target: &str,
+) -> Result<(), String> {
+    if action == "tray_copy_mcp" {
+        let config = state.config.read().await.clone();
+        let text = render_mcp_config(&app_data_dir(app)?, &config, target)?;
+        return app
+            .clipboard()
+            .write_text(text)
+            .map_err(|e| format!("Failed to copy MCP config: {}", e));
+    }
+    if action == "tray_profile" {
+        return switch_profile(app, state, target).await.map(|_| ());
+    }
+
+    let provider = parse_provider(target)?;
+    match action {
+        "tray_start" => start_single_provider(state, provider).await.map(|_| ()),
+        "tray_stop" => {
+            stop_provider(state, provider).await;
+            Ok(())
+        }
+        "tray_restart" => restart_single_provider(state, provider).await.map(|_| ()),
+        _ => Ok(()),
+    }
+}
+
+/// Paints a filled status dot into the bottom-right corner of an RGBA icon.
+fn with_status_dot(rgba: &[u8], width: u32, height: u32, color: [u8; 4]) -> Vec<u8> {
+    let mut out = rgba.to_vec();
+    let radius = (width.min(height) as f32) * 0.22;
+    let cx = width as f32 - radius - 1.0;
+    let cy = height as f32 - radius - 1.0;
+    for y in 0..height {
+        for x in 0..width {
+            let dx = x as f32 + 0.5 - cx;
+            let dy = y as f32 + 0.5 - cy;
+            if dx * dx + dy * dy <= radius * radius {
+                let offset = ((y * width + x) * 4) as usize;
+                if let Some(pixel) = out.get_mut(offset..offset + 4) {
+                    pixel.copy_from_slice(&color);
+                }
+            }
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn status_dot_only_touches_bottom_right_corner() {
+        let rgba = vec![0u8; 16 * 16 * 4];
+        let out = with_status_dot(&rgba, 16, 16, STATUS_RUNNING);
+        let pixel = |x: usize, y: usize| &out[(y * 16 + x) * 4..(y * 16 + x) * 4 + 4];
+        assert_eq!(pixel(12, 12), STATUS_RUNNING);
+        assert_eq!(pixel(1, 1), [0, 0, 0, 0]);
+        assert_eq!(out.len(), rgba.len());
+    }
+
+    #[test]
+    fn provider_labels_reflect_state() {
+        let mut view = ProviderTrayView {
+            provider: "tavily",
+            configured: true,
+            running: true,
+            healthy_keys: Some(2),
+            total_keys: 3,
+        };
+        assert_eq!(view.label(), "Tavily: running · 2/3 keys healthy");
+        view.running = false;
+        view.healthy_keys = None;
+        assert_eq!(view.label(), "Tavily: stopped · 3 keys");
+        view.configured = false;
+        assert_eq!(view.label(), "Tavily: not configured");
+    }
+}
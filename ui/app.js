@@ -2,6 +2,7 @@
 // 1. TAURI BRIDGE & UTILITIES
 // ============================================
 const invoke = window.__TAURI__?.core?.invoke;
+const listen = window.__TAURI__?.event?.listen;
 
 if (!invoke) {
   document.body.innerHTML = '<div style="padding:40px;text-align:center;color:#666">Tauri runtime not found. Please run inside the desktop app.</div>';
@@ -11,9 +12,12 @@ const contentEl = document.getElementById("content");
 const toastContainer = document.getElementById("toastContainer");
 const sidebarBadge = document.getElementById("sidebarBadge");
 const sidebarUrl = document.getElementById("sidebarUrl");
+const sidebarProfile = document.getElementById("sidebarProfile");
 const sidebarNav = document.getElementById("sidebarNav");
 const langToggle = document.getElementById("langToggle");
 
+const PROVIDERS = ["firecrawl", "tavily"];
+
 // ============================================
 // 2. I18N
 // ============================================
@@ -25,6 +29,7 @@ const I18N = {
     "nav.keys": "API Keys",
     "nav.mcp": "MCP 配置",
     "nav.logs": "日志",
+    "nav.debug": "调试抓包",
 
     // Sidebar status
     "status.running": "运行中",
@@ -62,17 +67,97 @@ const I18N = {
     "cfg.tavilyPort": "Tavily 端口",
     "cfg.requestTimeout": "请求超时",
     "cfg.keyCooldown": "Key 冷却时间",
+    "cfg.drainTimeout": "停止前等待请求完成",
     "cfg.launchOnLogin": "开机自启",
     "cfg.launchOnLoginHint": "系统登录后自动启动本应用",
+    "cfg.autoStart": "启动时自动运行代理",
+    "cfg.autoStartHint": "应用启动后自动启动已配置的 provider；监听异常退出时会自动重启",
+    "cfg.logLevel": "日志级别",
+    "cfg.logJson": "JSON 日志输出",
+    "cfg.logJsonHint": "标准输出使用 JSON 格式（重启应用后生效）",
+    "cfg.logFile": "写入日志文件",
+    "cfg.logFileHint": "日志文件按大小或按天轮转，以下设置重启应用后生效",
+    "cfg.logFileMaxSize": "单文件大小上限",
+    "cfg.logFileRetention": "保留文件数",
+    "cfg.logFileCompress": "压缩历史日志 (gzip)",
+    "cfg.history": "记录请求历史",
+    "cfg.historyHint": "在本地 SQLite 中记录每次代理请求的元数据（重启应用后生效）",
+    "cfg.historyRetentionDays": "保留天数",
+    "cfg.historyMaxRows": "最大记录数",
+    "cfg.otel": "导出 OpenTelemetry 链路追踪",
+    "cfg.otelHint": "通过 OTLP/HTTP 将每次请求与上游尝试的 span 发送到采集器，并向上游透传 traceparent（重启应用后生效）",
+    "cfg.otelEndpoint": "OTLP 端点",
+    "cfg.otelServiceName": "服务名",
+    "cfg.notifications": "桌面通知",
+    "cfg.notificationsHint": "Key 池耗尽、Key 失效、健康 Key 数低于阈值或代理意外退出时发送系统通知",
+    "cfg.notifyMinHealthyKeys": "健康 Key 阈值",
+    "cfg.notifyCooldown": "同类通知间隔",
     "cfg.apiKeysSection": "API Keys",
     "cfg.firecrawlApiKeysHint": "Firecrawl Keys：每行一个，或逗号分隔",
     "cfg.tavilyApiKeysHint": "Tavily Keys：每行一个，或逗号分隔",
+    "cfg.keyWatchDir": "Key 投放目录",
+    "cfg.keyWatchDirPlaceholder": "留空则不监视，例如 firecrawl_bot 目录",
+    "cfg.keyWatchInterval": "检查间隔",
+    "cfg.firecrawlKeyTiers": "Firecrawl Key 层级顺序",
+    "cfg.tavilyKeyTiers": "Tavily Key 层级顺序",
+    "cfg.keyTiersPlaceholder": "例如 free, paid",
+    "cfg.keyTiersHint": "按顺序使用各层级的 Key：前一层级的 Key 全部冷却或额度耗尽时才溢出到下一层级；未列出的层级与未分层的 Key 排在最后。重启服务后生效",
+    "cfg.firecrawlKeyRoutes": "Firecrawl 路由 Key 池",
+    "cfg.tavilyKeyRoutes": "Tavily 路由 Key 池",
+    "cfg.keyRoutesHint": "每行一条「路径模式=Key 池」，例如 /v1/crawl*=heavy（* 匹配任意字符）。路径匹配的请求只使用该池的 Key，其余请求使用未设置 Key 池的 Key；池中没有 Key 时回退到默认池。重启服务后生效",
+    "cfg.firecrawlKeyRpm": "Firecrawl 单 Key 每分钟请求数",
+    "cfg.tavilyKeyRpm": "Tavily 单 Key 每分钟请求数",
+    "cfg.keyRpmHint": "按服务商的已知限额主动控制每个 Key 的请求速率：Key 即将超限时换用其他 Key，全部超限时短暂等待，仍无额度则返回 429，绝不超额发送，避免上游冷却。0 表示只根据上游返回的 X-RateLimit-Remaining 控速。重启服务后生效",
+    "cfg.keyWatchHint": "定期读取该目录（或单个文件）中新增或追加的 .txt / .csv / .json Key 文件，把从未出现过的 Key 加入对应服务；手动删除过的 Key 不会被重新加入",
     "cfg.save": "保存配置",
     "cfg.unsaved": "有未保存的更改",
     "cfg.saved": "配置已保存",
     "cfg.saveFailed": "保存失败: ",
     "cfg.loadFailed": "加载配置失败: ",
     "cfg.launchOnLoginFailed": "设置开机自启失败: ",
+    "cfg.importEnv": "导入 .env",
+    "cfg.exportEnv": "导出 .env",
+    "cfg.imported": "已导入 {n} 项，请检查后保存",
+    "cfg.importIgnored": "已忽略未知变量: ",
+    "cfg.importFailed": "导入失败: ",
+    "cfg.exported": "已导出到 ",
+    "cfg.exportFailed": "导出失败: ",
+    "cfg.envOverridden": "以下配置由环境变量覆盖，在此修改不会生效: ",
+    "cfg.envBadge": "环境变量",
+    "cfg.secretsEncryption": "密钥加密存储",
+    "cfg.secretsOff": "不加密",
+    "cfg.secretsKeyfile": "随机主密钥（secret.key 文件）",
+    "cfg.secretsPassphrase": "口令",
+    "cfg.secretsHint": "加密配置文件中的 Token 与 API Key；口令模式下每次启动需解锁，也可通过 BALANCE_PROXY_PASSPHRASE 环境变量提供",
+    "cfg.passphrase": "加密口令",
+    "cfg.passphrasePlaceholder": "留空则沿用当前口令",
+    "cfg.revealSecrets": "显示完整密钥",
+    "cfg.revealFailed": "读取密钥失败: ",
+    "cfg.locked": "配置中的密钥已用口令加密，请输入口令解锁：",
+    "cfg.unlock": "解锁",
+    "cfg.unlocked": "已解锁",
+    "cfg.unlockFailed": "解锁失败: ",
+    "cfg.backups": "配置备份",
+    "cfg.backupsHint": "每次保存前会自动备份上一份配置，保留最近 10 份",
+    "cfg.noBackups": "暂无备份",
+    "cfg.backupInvalid": "（无法读取）",
+    "cfg.restore": "恢复",
+    "cfg.restoreConfirm": "用所选备份替换当前配置？当前配置会先被备份。",
+    "cfg.restored": "已恢复备份，重启代理后生效",
+    "cfg.restoreFailed": "恢复失败: ",
+    "cfg.recovered": "配置文件损坏，已自动恢复最近的有效备份 {backup}。损坏的文件已移至: ",
+    "cfg.profiles": "配置方案",
+    "cfg.profilesHint": "每个方案是一份完整的配置；切换后正在运行的代理会用新方案重启",
+    "cfg.profileName": "新方案名称",
+    "cfg.activateProfile": "切换",
+    "cfg.newProfile": "新建",
+    "cfg.cloneProfile": "复制",
+    "cfg.renameProfile": "重命名",
+    "cfg.deleteProfile": "删除",
+    "cfg.deleteProfileConfirm": "删除方案 {name}？此操作无法撤销。",
+    "cfg.profileActivated": "已切换到方案 {name}",
+    "cfg.profileFailed": "方案操作失败: ",
+    "cfg.activeProfileTag": "（当前）",
 
     // Keys
     "keys.title": "API Keys",
@@ -83,9 +168,63 @@ const I18N = {
     "keys.active": "活跃",
     "keys.cooldown": "冷却中",
     "keys.idle": "空闲",
+    "keys.dead": "已失效",
     "keys.failures": "次失败",
-    "keys.editNote": "在 <a id=\"keysGoConfig\">配置页面</a> 编辑 Keys。",
+    "keys.editNote": "也可以在 <a id=\"keysGoConfig\">配置页面</a> 批量编辑 Keys。",
+    "keys.manage": "管理 Keys",
+    "keys.manageHint": "修改立即保存，并在代理运行时直接生效；权重为该 Key 每轮连续承担的请求数",
+    "keys.keyValue": "API Key",
+    "keys.keyLabel": "标签",
+    "keys.keyWeight": "权重",
+    "keys.add": "添加",
+    "keys.moveUp": "上移",
+    "keys.moveDown": "下移",
+    "keys.notes": "备注",
+    "keys.notesPrompt": "备注内容：",
+    "keys.remove": "删除",
+    "keys.removeConfirm": "删除 Key {key}？",
+    "keys.enabled": "启用",
+    "keys.addedAt": "添加于 ",
+    "keys.sourceManual": "手动添加",
+    "keys.sourceImport": "批量导入",
+    "keys.import": "从文件导入…",
+    "keys.importHint": "支持注册脚本输出的 Key 列表与账号文件（email:password:key、email|password|key）、CSV 和 JSON；按 fc- / tvly- 前缀识别服务",
+    "keys.importAuto": "自动识别服务",
+    "keys.importDone": "导入完成：新增 {added}，跳过 {skipped}，无效 {invalid}",
+    "keys.importSkipped": "跳过",
+    "keys.importInvalid": "无效",
+    "keys.importFailed": "导入失败: ",
+    "keys.validate": "验证 Keys",
+    "keys.expired": "已过期",
+    "keys.exhausted": "额度耗尽",
+    "keys.resetsIn": "{n} 天后重置额度",
+    "keys.expiresIn": "{n} 天后过期",
+    "keys.resetDay": "重置日",
+    "keys.resetDayHint": "每月额度重置日（UTC，1-31）；额度耗尽的 Key 会停用到该日",
+    "keys.expiresOn": "过期日期（UTC），当天起不再使用",
+    "keys.tier": "层级",
+    "keys.tierHint": "Key 层级，例如 free 或 paid；顺序在设置中配置",
+    "keys.pool": "Key 池",
+    "keys.poolHint": "Key 池名称；留空则属于默认池，路由在设置中配置",
+    "keys.maxConcurrency": "并发",
+    "keys.maxConcurrencyHint": "该 Key 同时进行中的请求上限；达到上限时轮询跳过它。留空不限制",
+    "keys.inFlight": "进行中",
+    "keys.rateRemaining": "本分钟剩余",
+    "keys.removeInvalid": "验证时从配置中删除无效 Key",
+    "keys.validateDone": "验证完成：有效 {valid}，无效 {invalid}，额度耗尽 {exhausted}，限流 {limited}，已删除 {removed}",
+    "keys.validateFailed": "验证失败: ",
+    "keys.probe.valid": "有效",
+    "keys.probe.invalid": "无效",
+    "keys.probe.exhausted": "额度耗尽",
+    "keys.probe.rateLimited": "限流",
+    "keys.probe.error": "请求失败",
+    "keys.editFailed": "修改 Key 失败: ",
     "keys.loadFailed": "加载 Keys 失败",
+    "keys.start": "启动",
+    "keys.stop": "停止",
+    "keys.restart": "重启",
+    "keys.actionFailed": "操作失败: ",
+    "keys.draining": "· 正在等待 {n} 个请求完成",
 
     // MCP
     "mcp.title": "MCP 配置",
@@ -110,7 +249,33 @@ const I18N = {
     "logs.search": "搜索日志...",
     "logs.refresh": "刷新",
     "logs.showing": "显示 {0} / {1} 条",
-    "logs.autoRefresh": "自动刷新: 2s",
+    "logs.autoRefresh": "实时推送",
+    "logs.export": "导出",
+    "logs.rangeHour": "最近 1 小时",
+    "logs.rangeDay": "最近 24 小时",
+    "logs.rangeWeek": "最近 7 天",
+    "logs.rangeAll": "全部",
+    "logs.exported": "日志已导出: ",
+    "logs.exportFailed": "导出失败: ",
+
+    // Debug capture
+    "debug.title": "调试抓包",
+    "debug.desc": "临时记录请求与响应内容（已截断，API Key 与代理 Token 会被脱敏），用于排查 MCP 工具问题。",
+    "debug.maxRequests": "最多请求数",
+    "debug.maxMinutes": "最长时间",
+    "debug.maxBodyKb": "Body 截断上限",
+    "debug.start": "开始抓包",
+    "debug.stop": "停止抓包",
+    "debug.clear": "清空记录",
+    "debug.active": "抓包中：剩余 {0} 条，{1} 到期",
+    "debug.inactive": "未开启",
+    "debug.empty": "暂无抓包记录",
+    "debug.request": "请求",
+    "debug.response": "响应",
+    "debug.replay": "重放",
+    "debug.replayResult": "重放结果",
+    "debug.truncated": "（已截断，共 {0} 字节）",
+    "debug.failed": "操作失败: ",
 
     // Common
     "copied": "已复制到剪贴板",
@@ -122,6 +287,7 @@ const I18N = {
     "nav.keys": "API Keys",
     "nav.mcp": "MCP Config",
     "nav.logs": "Logs",
+    "nav.debug": "Debug Capture",
 
     "status.running": "Running",
     "status.degraded": "Degraded",
@@ -156,17 +322,97 @@ const I18N = {
     "cfg.tavilyPort": "Tavily Port",
     "cfg.requestTimeout": "Request Timeout",
     "cfg.keyCooldown": "Key Cooldown",
+    "cfg.drainTimeout": "Drain Timeout",
     "cfg.launchOnLogin": "Launch on login",
     "cfg.launchOnLoginHint": "Automatically start this app after user login",
+    "cfg.autoStart": "Start proxy on launch",
+    "cfg.autoStartHint": "Start configured providers when the app opens; crashed listeners are restarted automatically",
+    "cfg.logLevel": "Log level",
+    "cfg.logJson": "JSON log output",
+    "cfg.logJsonHint": "Write stdout logs as JSON (takes effect after app restart)",
+    "cfg.logFile": "Write log files",
+    "cfg.logFileHint": "Log files rotate by size or by day; these settings apply after app restart",
+    "cfg.logFileMaxSize": "Max file size",
+    "cfg.logFileRetention": "Files to keep",
+    "cfg.logFileCompress": "Compress rotated logs (gzip)",
+    "cfg.history": "Record request history",
+    "cfg.historyHint": "Store metadata for every proxied request in a local SQLite journal (applies after app restart)",
+    "cfg.historyRetentionDays": "Retention days",
+    "cfg.historyMaxRows": "Max records",
+    "cfg.otel": "Export OpenTelemetry traces",
+    "cfg.otelHint": "Send spans for each request and upstream attempt to a collector over OTLP/HTTP, and propagate traceparent upstream (applies after app restart)",
+    "cfg.otelEndpoint": "OTLP endpoint",
+    "cfg.otelServiceName": "Service name",
+    "cfg.notifications": "Desktop notifications",
+    "cfg.notificationsHint": "Notify when a key pool is exhausted, a key is invalid, healthy keys drop below the threshold, or a proxy exits unexpectedly",
+    "cfg.notifyMinHealthyKeys": "Healthy key threshold",
+    "cfg.notifyCooldown": "Repeat interval",
     "cfg.apiKeysSection": "API Keys",
     "cfg.firecrawlApiKeysHint": "Firecrawl keys: one per line, or comma-separated",
     "cfg.tavilyApiKeysHint": "Tavily keys: one per line, or comma-separated",
+    "cfg.keyWatchDir": "Key Drop Folder",
+    "cfg.keyWatchDirPlaceholder": "Empty to disable, e.g. the firecrawl_bot folder",
+    "cfg.keyWatchInterval": "Check Interval",
+    "cfg.firecrawlKeyTiers": "Firecrawl Key Tier Order",
+    "cfg.tavilyKeyTiers": "Tavily Key Tier Order",
+    "cfg.keyTiersPlaceholder": "e.g. free, paid",
+    "cfg.keyTiersHint": "Tiers are used in this order: keys of a later tier only serve when every key of the earlier ones is cooling down or exhausted. Unlisted tiers and untiered keys come last. Applies after a restart",
+    "cfg.firecrawlKeyRoutes": "Firecrawl Key Pool Routes",
+    "cfg.tavilyKeyRoutes": "Tavily Key Pool Routes",
+    "cfg.keyRoutesHint": "One pattern=pool per line, e.g. /v1/crawl*=heavy (* matches anything). Requests whose path matches only use that pool's keys; all others use the keys without a pool. A pool without keys falls back to the default pool. Applies after a restart",
+    "cfg.firecrawlKeyRpm": "Firecrawl Requests / Min per Key",
+    "cfg.tavilyKeyRpm": "Tavily Requests / Min per Key",
+    "cfg.keyRpmHint": "Paces each key to the provider's known limit: a key about to exceed it hands over to another, and when all would, the request waits briefly and is answered with 429 if none frees up, so no key is sent past its limit. 0 paces only by the X-RateLimit-Remaining headers upstream sends. Applies after a restart",
+    "cfg.keyWatchHint": "New or appended .txt / .csv / .json key files in this folder (or a single file) are read periodically and keys never seen before join their provider; keys you removed are never added back",
     "cfg.save": "Save Configuration",
     "cfg.unsaved": "Unsaved changes",
     "cfg.saved": "Configuration saved",
     "cfg.saveFailed": "Save failed: ",
     "cfg.loadFailed": "Failed to load config: ",
     "cfg.launchOnLoginFailed": "Failed to update launch-on-login: ",
+    "cfg.importEnv": "Import .env",
+    "cfg.exportEnv": "Export .env",
+    "cfg.imported": "Imported {n} settings; review and save",
+    "cfg.importIgnored": "Ignored unknown variables: ",
+    "cfg.importFailed": "Import failed: ",
+    "cfg.exported": "Exported to ",
+    "cfg.exportFailed": "Export failed: ",
+    "cfg.envOverridden": "These settings are overridden by environment variables and cannot be changed here: ",
+    "cfg.envBadge": "env",
+    "cfg.secretsEncryption": "Encrypt secrets at rest",
+    "cfg.secretsOff": "Off",
+    "cfg.secretsKeyfile": "Random master key (secret.key file)",
+    "cfg.secretsPassphrase": "Passphrase",
+    "cfg.secretsHint": "Encrypts the token and API keys in the config file; passphrase mode needs unlocking on every launch, or the BALANCE_PROXY_PASSPHRASE environment variable",
+    "cfg.passphrase": "Passphrase",
+    "cfg.passphrasePlaceholder": "Leave empty to keep the current passphrase",
+    "cfg.revealSecrets": "Show full keys",
+    "cfg.revealFailed": "Failed to read secrets: ",
+    "cfg.locked": "The keys in this config are encrypted with a passphrase. Enter it to unlock:",
+    "cfg.unlock": "Unlock",
+    "cfg.unlocked": "Unlocked",
+    "cfg.unlockFailed": "Unlock failed: ",
+    "cfg.backups": "Config Backups",
+    "cfg.backupsHint": "The previous config is backed up before every save; the last 10 are kept",
+    "cfg.noBackups": "No backups yet",
+    "cfg.backupInvalid": " (unreadable)",
+    "cfg.restore": "Restore",
+    "cfg.restoreConfirm": "Replace the current config with the selected backup? The current config is backed up first.",
+    "cfg.restored": "Backup restored; restart the proxy to apply it",
+    "cfg.restoreFailed": "Restore failed: ",
+    "cfg.recovered": "The config file was corrupt, so the latest valid backup {backup} was restored. The broken file was moved to: ",
+    "cfg.profiles": "Profiles",
+    "cfg.profilesHint": "Each profile is a complete config; switching restarts running proxies with the new profile",
+    "cfg.profileName": "New profile name",
+    "cfg.activateProfile": "Activate",
+    "cfg.newProfile": "New",
+    "cfg.cloneProfile": "Clone",
+    "cfg.renameProfile": "Rename",
+    "cfg.deleteProfile": "Delete",
+    "cfg.deleteProfileConfirm": "Delete profile {name}? This cannot be undone.",
+    "cfg.profileActivated": "Switched to profile {name}",
+    "cfg.profileFailed": "Profile action failed: ",
+    "cfg.activeProfileTag": " (active)",
 
     "keys.title": "API Keys",
     "keys.desc": "Monitor the health and status of Firecrawl and Tavily API keys.",
@@ -176,9 +422,63 @@ const I18N = {
     "keys.active": "Active",
     "keys.cooldown": "Cooldown",
     "keys.idle": "Idle",
+    "keys.dead": "Dead",
     "keys.failures": " failures",
-    "keys.editNote": "Edit keys on the <a id=\"keysGoConfig\">Configuration page</a>.",
+    "keys.editNote": "Keys can also be edited in bulk on the <a id=\"keysGoConfig\">Configuration page</a>.",
+    "keys.manage": "Manage Keys",
+    "keys.manageHint": "Changes are saved at once and apply to running proxies; weight is how many requests in a row a key serves per turn",
+    "keys.keyValue": "API key",
+    "keys.keyLabel": "Label",
+    "keys.keyWeight": "Weight",
+    "keys.add": "Add",
+    "keys.moveUp": "Move up",
+    "keys.moveDown": "Move down",
+    "keys.notes": "Notes",
+    "keys.notesPrompt": "Notes:",
+    "keys.remove": "Remove",
+    "keys.removeConfirm": "Remove key {key}?",
+    "keys.enabled": "Enabled",
+    "keys.addedAt": "Added ",
+    "keys.sourceManual": "added manually",
+    "keys.sourceImport": "Imported",
+    "keys.import": "Import from files…",
+    "keys.importHint": "Reads the key lists and account files the signup scripts write (email:password:key, email|password|key), CSV and JSON; fc- / tvly- prefixes pick the provider",
+    "keys.importAuto": "Detect provider",
+    "keys.importDone": "Import finished: {added} added, {skipped} skipped, {invalid} invalid",
+    "keys.importSkipped": "Skipped",
+    "keys.importInvalid": "Invalid",
+    "keys.importFailed": "Import failed: ",
+    "keys.validate": "Validate Keys",
+    "keys.expired": "Expired",
+    "keys.exhausted": "Exhausted",
+    "keys.resetsIn": "quota resets in {n}d",
+    "keys.expiresIn": "expires in {n}d",
+    "keys.resetDay": "Reset",
+    "keys.resetDayHint": "Day of the month (UTC, 1-31) the quota resets; an exhausted key rests until then",
+    "keys.expiresOn": "Expiry date (UTC); the key is not used from this day on",
+    "keys.tier": "Tier",
+    "keys.tierHint": "Key tier, e.g. free or paid; the order is set in Settings",
+    "keys.pool": "Pool",
+    "keys.poolHint": "Key pool name; empty for the default pool. Routes are set in Settings",
+    "keys.maxConcurrency": "Conc.",
+    "keys.maxConcurrencyHint": "Requests the key may have in flight at once; rotation skips it while at the limit. Empty for no limit",
+    "keys.inFlight": "In flight",
+    "keys.rateRemaining": "Left this minute",
+    "keys.removeInvalid": "Remove invalid keys from the config when validating",
+    "keys.validateDone": "Validation finished: {valid} valid, {invalid} invalid, {exhausted} exhausted, {limited} rate-limited, {removed} removed",
+    "keys.validateFailed": "Validation failed: ",
+    "keys.probe.valid": "Valid",
+    "keys.probe.invalid": "Invalid",
+    "keys.probe.exhausted": "Exhausted",
+    "keys.probe.rateLimited": "Rate-limited",
+    "keys.probe.error": "Probe failed",
+    "keys.editFailed": "Failed to update key: ",
     "keys.loadFailed": "Failed to load keys.",
+    "keys.start": "Start",
+    "keys.stop": "Stop",
+    "keys.restart": "Restart",
+    "keys.actionFailed": "Action failed: ",
+    "keys.draining": "· draining {n} in-flight requests",
 
     "mcp.title": "MCP Configuration",
     "mcp.desc": "Choose a scope and copy the generated JSON to your MCP client settings file.",
@@ -201,7 +501,32 @@ const I18N = {
     "logs.search": "Search logs...",
     "logs.refresh": "Refresh",
     "logs.showing": "Showing {0} of {1} lines",
-    "logs.autoRefresh": "Auto-refresh: 2s",
+    "logs.autoRefresh": "Live",
+    "logs.export": "Export",
+    "logs.rangeHour": "Last hour",
+    "logs.rangeDay": "Last 24 hours",
+    "logs.rangeWeek": "Last 7 days",
+    "logs.rangeAll": "All",
+    "logs.exported": "Logs exported: ",
+    "logs.exportFailed": "Export failed: ",
+
+    "debug.title": "Debug Capture",
+    "debug.desc": "Temporarily record request and response bodies (truncated, with API keys and the proxy token redacted) to debug MCP tools.",
+    "debug.maxRequests": "Max requests",
+    "debug.maxMinutes": "Max duration",
+    "debug.maxBodyKb": "Body size limit",
+    "debug.start": "Start capture",
+    "debug.stop": "Stop capture",
+    "debug.clear": "Clear captures",
+    "debug.active": "Capturing: {0} requests left, ends at {1}",
+    "debug.inactive": "Inactive",
+    "debug.empty": "No captures yet.",
+    "debug.request": "Request",
+    "debug.response": "Response",
+    "debug.replay": "Replay",
+    "debug.replayResult": "Replay result",
+    "debug.truncated": " (truncated, {0} bytes total)",
+    "debug.failed": "Operation failed: ",
 
     "copied": "Copied to clipboard",
     "copyFailed": "Copy failed: ",
@@ -228,7 +553,7 @@ function setLang(lang) {
   });
 
   // Update sidebar nav labels
-  const navLabels = { dashboard: "nav.dashboard", config: "nav.config", keys: "nav.keys", mcp: "nav.mcp", logs: "nav.logs" };
+  const navLabels = { dashboard: "nav.dashboard", config: "nav.config", keys: "nav.keys", mcp: "nav.mcp", logs: "nav.logs", debug: "nav.debug" };
   sidebarNav.querySelectorAll(".nav-item").forEach((btn) => {
     const key = navLabels[btn.dataset.page];
     if (key) btn.querySelector("span").textContent = t(key);
@@ -363,14 +688,31 @@ function parseKeys(text) {
   return text.split(/[\n,]/g).map((v) => v.trim()).filter(Boolean);
 }
 
+// Key lists hold records ({ value, label, enabled, ... }); older data may
+// still be plain strings.
+function keyValue(key) {
+  return typeof key === "string" ? key : key?.value || "";
+}
+
+function enabledKeys(keys) {
+  return (keys || []).filter((key) => typeof key === "string" || key.enabled !== false);
+}
+
 function normalizeKeysText(arr) {
-  return (arr || []).join("\n");
+  return (arr || []).map(keyValue).join("\n");
+}
+
+// Turns edited key text back into records, keeping the label, weight and
+// other details of keys that were already configured.
+function mergeKeyRecords(values, records) {
+  return values.map((value) => (records || []).find((r) => keyValue(r) === value && typeof r === "object") || { value });
 }
 
 function idleStatusesFromKeys(keys) {
-  return (keys || []).map((key, index) => ({
+  return enabledKeys(keys).map((key, index) => ({
     index,
-    keyPreview: truncateKey(key),
+    keyPreview: truncateKey(keyValue(key)),
+    label: key.label || "",
     isCoolingDown: false,
     cooldownRemainingSecs: 0,
     failCount: 0,
@@ -379,10 +721,10 @@ function idleStatusesFromKeys(keys) {
 
 function isProviderConfigured(config, provider) {
   if (provider === "firecrawl") {
-    return !!((config?.firecrawlApiKeys || []).length && (config?.upstreamBaseUrl || "").trim());
+    return !!(enabledKeys(config?.firecrawlApiKeys).length && (config?.upstreamBaseUrl || "").trim());
   }
   if (provider === "tavily") {
-    return !!((config?.tavilyApiKeys || []).length && (config?.tavilyUpstreamBaseUrl || "").trim());
+    return !!(enabledKeys(config?.tavilyApiKeys).length && (config?.tavilyUpstreamBaseUrl || "").trim());
   }
   return false;
 }
@@ -433,11 +775,31 @@ function highlightJSON(json) {
 // ============================================
 let currentPage = null;
 let currentPageId = null;
-let globalTimer = null;
+let pageEventGeneration = 0;
+let pageUnlisteners = [];
 
 const pages = {};
 
+// Subscribes to a backend event until the current page is left.
+function onPageEvent(name, handler) {
+  if (!listen) return;
+  const generation = pageEventGeneration;
+  listen(name, (event) => handler(event.payload))
+    .then((unlisten) => {
+      if (generation === pageEventGeneration) pageUnlisteners.push(unlisten);
+      else unlisten();
+    })
+    .catch(() => {});
+}
+
+function releasePageEvents() {
+  pageEventGeneration += 1;
+  pageUnlisteners.forEach((unlisten) => unlisten());
+  pageUnlisteners = [];
+}
+
 function navigate(pageId) {
+  releasePageEvents();
   if (currentPage && currentPage.destroy) currentPage.destroy();
   currentPageId = pageId;
   currentPage = pages[pageId];
@@ -461,7 +823,7 @@ sidebarNav.addEventListener("click", (e) => {
   navigate(btn.dataset.page);
 });
 
-// ---- Global status polling ----
+// ---- Global status ----
 function isAnyProxyRunning(status) {
   return !!(status && (status.anyRunning || status.running || status.firecrawlRunning || status.tavilyRunning));
 }
@@ -476,6 +838,7 @@ function formatProxyUrls(status) {
 async function updateSidebarStatus() {
   try {
     const status = await invoke("get_proxy_status");
+    sidebarProfile.textContent = status?.activeProfile || "";
     if (status?.running) {
       sidebarBadge.className = "badge badge-success";
       sidebarBadge.textContent = t("status.running");
@@ -499,9 +862,9 @@ async function updateSidebarStatus() {
 // 4. PAGE: Dashboard
 // ============================================
 pages.dashboard = {
-  _timer: null,
   _proxyStartTime: null,
   _uptimeTimer: null,
+  _recentLogs: [],
 
   template() {
     return `
@@ -595,8 +958,14 @@ pages.dashboard = {
       copyWithFeedback(text, copyBtn);
     });
 
+    onPageEvent("log-line", (line) => {
+      this._recentLogs = [...this._recentLogs.slice(-9), line];
+      this._renderLogs();
+    });
+    onPageEvent("proxy-lifecycle", () => this._refresh());
+    onPageEvent("key-state", () => this._refreshKeyStats());
+
     await this._refresh();
-    this._timer = setInterval(() => this._refresh(), 2000);
     this._uptimeTimer = setInterval(() => this._updateUptime(), 1000);
   },
 
@@ -638,26 +1007,11 @@ pages.dashboard = {
         }
       }
 
-      const mergedKeyStatuses = mergeConfiguredKeys(keySnapshot);
-      const totalKeys = mergedKeyStatuses.length;
-      const activeCount = mergedKeyStatuses.filter((k) => !k.isCoolingDown).length;
-      const cooldownCount = mergedKeyStatuses.filter((k) => k.isCoolingDown).length;
-
-      const totalEl = document.getElementById("statTotalKeys");
-      const activeEl = document.getElementById("statActive");
-      const cooldownEl = document.getElementById("statCooldown");
-      if (totalEl) totalEl.textContent = String(totalKeys);
-      if (activeEl) activeEl.textContent = String(activeCount);
-      if (cooldownEl) cooldownEl.textContent = String(cooldownCount);
-
+      this._renderKeyStats(keySnapshot);
       this._updateUptime();
 
-      const logsEl = document.getElementById("dashLogs");
-      if (logsEl) {
-        const recent = logs.slice(-10);
-        logsEl.textContent = recent.join("\n") || t("dash.noLogs");
-        logsEl.scrollTop = logsEl.scrollHeight;
-      }
+      this._recentLogs = logs.slice(-10);
+      this._renderLogs();
 
       const mcpEl = document.getElementById("dashMcp");
       if (mcpEl) mcpEl.innerHTML = highlightJSON(mcpText);
@@ -666,6 +1020,34 @@ pages.dashboard = {
     }
   },
 
+  async _refreshKeyStats() {
+    try {
+      const snapshot = await invoke("get_key_status_snapshot");
+      this._renderKeyStats(snapshot);
+    } catch {}
+  },
+
+  _renderKeyStats(keySnapshot) {
+    const mergedKeyStatuses = mergeConfiguredKeys(keySnapshot);
+    const totalKeys = mergedKeyStatuses.length;
+    const activeCount = mergedKeyStatuses.filter((k) => !k.isCoolingDown && !k.isDead).length;
+    const cooldownCount = mergedKeyStatuses.filter((k) => k.isCoolingDown).length;
+
+    const totalEl = document.getElementById("statTotalKeys");
+    const activeEl = document.getElementById("statActive");
+    const cooldownEl = document.getElementById("statCooldown");
+    if (totalEl) totalEl.textContent = String(totalKeys);
+    if (activeEl) activeEl.textContent = String(activeCount);
+    if (cooldownEl) cooldownEl.textContent = String(cooldownCount);
+  },
+
+  _renderLogs() {
+    const logsEl = document.getElementById("dashLogs");
+    if (!logsEl) return;
+    logsEl.textContent = this._recentLogs.join("\n") || t("dash.noLogs");
+    logsEl.scrollTop = logsEl.scrollHeight;
+  },
+
   _updateUptime() {
     const el = document.getElementById("statUptime");
     if (!el) return;
@@ -681,7 +1063,6 @@ pages.dashboard = {
   },
 
   destroy() {
-    if (this._timer) { clearInterval(this._timer); this._timer = null; }
     if (this._uptimeTimer) { clearInterval(this._uptimeTimer); this._uptimeTimer = null; }
   },
 };
@@ -689,6 +1070,48 @@ pages.dashboard = {
 // ============================================
 // 5. PAGE: Configuration
 // ============================================
+const DEFAULT_OTEL_ENDPOINT = "http://127.0.0.1:4318/v1/traces";
+
+// Config variable name -> form control, for marking environment overrides.
+const ENV_VAR_INPUTS = {
+  PROXY_TOKEN: "cfgProxyToken",
+  UPSTREAM_BASE_URL: "cfgUpstreamUrl",
+  TAVILY_UPSTREAM_BASE_URL: "cfgTavilyUpstreamUrl",
+  HOST: "cfgHost",
+  PORT: "cfgPort",
+  TAVILY_PORT: "cfgTavilyPort",
+  REQUEST_TIMEOUT_MS: "cfgTimeout",
+  KEY_COOLDOWN_SECONDS: "cfgCooldown",
+  DRAIN_TIMEOUT_SECONDS: "cfgDrainTimeout",
+  KEY_WATCH_DIR: "cfgKeyWatchDir",
+  KEY_WATCH_INTERVAL_SECONDS: "cfgKeyWatchInterval",
+  FIRECRAWL_KEY_TIERS: "cfgFirecrawlKeyTiers",
+  TAVILY_KEY_TIERS: "cfgTavilyKeyTiers",
+  FIRECRAWL_KEY_ROUTES: "cfgFirecrawlKeyRoutes",
+  TAVILY_KEY_ROUTES: "cfgTavilyKeyRoutes",
+  FIRECRAWL_KEY_RPM: "cfgFirecrawlKeyRpm",
+  TAVILY_KEY_RPM: "cfgTavilyKeyRpm",
+  FIRECRAWL_API_KEYS: "cfgApiKeys",
+  TAVILY_API_KEYS: "cfgTavilyApiKeys",
+  AUTO_START_PROXY: "cfgAutoStart",
+  LOG_LEVEL: "cfgLogLevel",
+  LOG_JSON: "cfgLogJson",
+  LOG_FILE_ENABLED: "cfgLogFile",
+  LOG_FILE_MAX_SIZE_MB: "cfgLogFileMaxSize",
+  LOG_FILE_RETENTION: "cfgLogFileRetention",
+  LOG_FILE_COMPRESS: "cfgLogFileCompress",
+  HISTORY_ENABLED: "cfgHistory",
+  HISTORY_RETENTION_DAYS: "cfgHistoryRetentionDays",
+  HISTORY_MAX_ROWS: "cfgHistoryMaxRows",
+  OTEL_ENABLED: "cfgOtel",
+  OTEL_ENDPOINT: "cfgOtelEndpoint",
+  OTEL_SERVICE_NAME: "cfgOtelServiceName",
+  NOTIFICATIONS_ENABLED: "cfgNotify",
+  NOTIFY_MIN_HEALTHY_KEYS: "cfgNotifyMinHealthy",
+  NOTIFY_COOLDOWN_MINUTES: "cfgNotifyCooldown",
+  SECRETS_ENCRYPTION: "cfgSecretsEncryption",
+};
+
 pages.config = {
   _savedConfig: null,
   _savedLaunchOnLogin: false,
@@ -697,6 +1120,32 @@ pages.config = {
     return `
       <h1>${t("cfg.title")}</h1>
 
+      <div id="cfgRecoveryBanner" class="config-banner"></div>
+      <div id="cfgEnvBanner" class="config-banner"></div>
+      <div id="cfgLockedBanner" class="config-banner">
+        <span>${t("cfg.locked")}</span>
+        <div class="backup-row">
+          <input id="cfgUnlockPassphrase" class="form-input" type="password" autocomplete="off" />
+          <button id="cfgUnlockBtn" class="btn btn-sm btn-primary">${t("cfg.unlock")}</button>
+        </div>
+      </div>
+
+      <div class="card">
+        <div class="card-header">${t("cfg.profiles")}</div>
+        <div class="backup-row">
+          <select id="cfgProfileSelect" class="form-input"></select>
+          <button id="cfgActivateProfileBtn" class="btn btn-sm btn-primary">${t("cfg.activateProfile")}</button>
+          <button id="cfgDeleteProfileBtn" class="btn btn-sm">${t("cfg.deleteProfile")}</button>
+        </div>
+        <div class="backup-row profile-name-row">
+          <input id="cfgProfileName" class="form-input" type="text" maxlength="40" placeholder="${t("cfg.profileName")}" />
+          <button id="cfgNewProfileBtn" class="btn btn-sm">${t("cfg.newProfile")}</button>
+          <button id="cfgCloneProfileBtn" class="btn btn-sm">${t("cfg.cloneProfile")}</button>
+          <button id="cfgRenameProfileBtn" class="btn btn-sm">${t("cfg.renameProfile")}</button>
+        </div>
+        <p class="form-note">${t("cfg.profilesHint")}</p>
+      </div>
+
       <div class="card">
         <div class="card-header">${t("cfg.proxySettings")}</div>
         <div class="form-group">
@@ -745,6 +1194,13 @@ pages.config = {
             </div>
           </div>
         </div>
+        <div class="form-group">
+          <label class="form-label">${t("cfg.drainTimeout")} <span class="form-hint">DRAIN_TIMEOUT_SECONDS</span></label>
+          <div class="input-with-suffix">
+            <input id="cfgDrainTimeout" class="form-input" type="number" min="0" max="600" />
+            <span class="input-suffix">sec</span>
+          </div>
+        </div>
       </div>
 
       <div class="card">
@@ -757,6 +1213,67 @@ pages.config = {
           <label class="form-label">${t("cfg.tavilyApiKeysHint")}</label>
           <textarea id="cfgTavilyApiKeys" class="form-textarea" rows="5" placeholder="tvly-key-1&#10;tvly-key-2&#10;tvly-key-3"></textarea>
         </div>
+        <div class="form-row">
+          <div class="form-group">
+            <label class="form-label">${t("cfg.keyWatchDir")} <span class="form-hint">KEY_WATCH_DIR</span></label>
+            <input id="cfgKeyWatchDir" class="form-input" type="text" placeholder="${t("cfg.keyWatchDirPlaceholder")}" />
+          </div>
+          <div class="form-group">
+            <label class="form-label">${t("cfg.keyWatchInterval")} <span class="form-hint">KEY_WATCH_INTERVAL_SECONDS</span></label>
+            <div class="input-with-suffix">
+              <input id="cfgKeyWatchInterval" class="form-input" type="number" min="1" />
+              <span class="input-suffix">sec</span>
+            </div>
+          </div>
+        </div>
+        <p class="form-note">${t("cfg.keyWatchHint")}</p>
+        <div class="form-row">
+          <div class="form-group">
+            <label class="form-label">${t("cfg.firecrawlKeyTiers")} <span class="form-hint">FIRECRAWL_KEY_TIERS</span></label>
+            <input id="cfgFirecrawlKeyTiers" class="form-input" type="text" placeholder="${t("cfg.keyTiersPlaceholder")}" />
+          </div>
+          <div class="form-group">
+            <label class="form-label">${t("cfg.tavilyKeyTiers")} <span class="form-hint">TAVILY_KEY_TIERS</span></label>
+            <input id="cfgTavilyKeyTiers" class="form-input" type="text" placeholder="${t("cfg.keyTiersPlaceholder")}" />
+          </div>
+        </div>
+        <p class="form-note">${t("cfg.keyTiersHint")}</p>
+        <div class="form-row">
+          <div class="form-group">
+            <label class="form-label">${t("cfg.firecrawlKeyRoutes")} <span class="form-hint">FIRECRAWL_KEY_ROUTES</span></label>
+            <textarea id="cfgFirecrawlKeyRoutes" class="form-textarea" rows="3" placeholder="/v1/crawl*=heavy&#10;/v2/extract*=heavy"></textarea>
+          </div>
+          <div class="form-group">
+            <label class="form-label">${t("cfg.tavilyKeyRoutes")} <span class="form-hint">TAVILY_KEY_ROUTES</span></label>
+            <textarea id="cfgTavilyKeyRoutes" class="form-textarea" rows="3" placeholder="/extract=heavy"></textarea>
+          </div>
+        </div>
+        <p class="form-note">${t("cfg.keyRoutesHint")}</p>
+        <div class="form-row">
+          <div class="form-group">
+            <label class="form-label">${t("cfg.firecrawlKeyRpm")} <span class="form-hint">FIRECRAWL_KEY_RPM</span></label>
+            <input id="cfgFirecrawlKeyRpm" class="form-input" type="number" min="0" />
+          </div>
+          <div class="form-group">
+            <label class="form-label">${t("cfg.tavilyKeyRpm")} <span class="form-hint">TAVILY_KEY_RPM</span></label>
+            <input id="cfgTavilyKeyRpm" class="form-input" type="number" min="0" />
+          </div>
+        </div>
+        <p class="form-note">${t("cfg.keyRpmHint")}</p>
+        <button id="cfgRevealBtn" class="btn btn-sm">${t("cfg.revealSecrets")}</button>
+        <div class="form-group secrets-mode">
+          <label class="form-label">${t("cfg.secretsEncryption")} <span class="form-hint">SECRETS_ENCRYPTION</span></label>
+          <select id="cfgSecretsEncryption" class="form-input">
+            <option value="off">${t("cfg.secretsOff")}</option>
+            <option value="keyfile">${t("cfg.secretsKeyfile")}</option>
+            <option value="passphrase">${t("cfg.secretsPassphrase")}</option>
+          </select>
+        </div>
+        <div id="cfgPassphraseGroup" class="form-group">
+          <label class="form-label">${t("cfg.passphrase")}</label>
+          <input id="cfgPassphrase" class="form-input" type="password" autocomplete="new-password" placeholder="${t("cfg.passphrasePlaceholder")}" />
+        </div>
+        <p class="form-note">${t("cfg.secretsHint")}</p>
       </div>
 
       <div class="card">
@@ -766,24 +1283,131 @@ pages.config = {
           <input id="cfgLaunchOnLogin" type="checkbox" />
         </label>
         <p class="form-note">${t("cfg.launchOnLoginHint")}</p>
+        <label class="toggle-row" for="cfgAutoStart">
+          <span>${t("cfg.autoStart")}</span>
+          <input id="cfgAutoStart" type="checkbox" />
+        </label>
+        <p class="form-note">${t("cfg.autoStartHint")}</p>
+        <div class="form-group">
+          <label class="form-label">${t("cfg.logLevel")} <span class="form-hint">LOG_LEVEL</span></label>
+          <select id="cfgLogLevel" class="form-input">
+            <option value="trace">TRACE</option>
+            <option value="debug">DEBUG</option>
+            <option value="info">INFO</option>
+            <option value="warn">WARN</option>
+            <option value="error">ERROR</option>
+          </select>
+        </div>
+        <label class="toggle-row" for="cfgLogJson">
+          <span>${t("cfg.logJson")}</span>
+          <input id="cfgLogJson" type="checkbox" />
+        </label>
+        <p class="form-note">${t("cfg.logJsonHint")}</p>
+        <label class="toggle-row" for="cfgLogFile">
+          <span>${t("cfg.logFile")}</span>
+          <input id="cfgLogFile" type="checkbox" />
+        </label>
+        <p class="form-note">${t("cfg.logFileHint")}</p>
+        <div class="form-row">
+          <div class="form-group">
+            <label class="form-label">${t("cfg.logFileMaxSize")} <span class="form-hint">LOG_FILE_MAX_SIZE_MB</span></label>
+            <div class="input-with-suffix">
+              <input id="cfgLogFileMaxSize" class="form-input" type="number" min="1" />
+              <span class="input-suffix">MB</span>
+            </div>
+          </div>
+          <div class="form-group">
+            <label class="form-label">${t("cfg.logFileRetention")} <span class="form-hint">LOG_FILE_RETENTION</span></label>
+            <input id="cfgLogFileRetention" class="form-input" type="number" min="1" />
+          </div>
+        </div>
+        <label class="toggle-row" for="cfgLogFileCompress">
+          <span>${t("cfg.logFileCompress")}</span>
+          <input id="cfgLogFileCompress" type="checkbox" />
+        </label>
+        <label class="toggle-row" for="cfgHistory">
+          <span>${t("cfg.history")}</span>
+          <input id="cfgHistory" type="checkbox" />
+        </label>
+        <p class="form-note">${t("cfg.historyHint")}</p>
+        <div class="form-row">
+          <div class="form-group">
+            <label class="form-label">${t("cfg.historyRetentionDays")} <span class="form-hint">HISTORY_RETENTION_DAYS</span></label>
+            <input id="cfgHistoryRetentionDays" class="form-input" type="number" min="1" />
+          </div>
+          <div class="form-group">
+            <label class="form-label">${t("cfg.historyMaxRows")} <span class="form-hint">HISTORY_MAX_ROWS</span></label>
+            <input id="cfgHistoryMaxRows" class="form-input" type="number" min="1" />
+          </div>
+        </div>
+        <label class="toggle-row" for="cfgOtel">
+          <span>${t("cfg.otel")}</span>
+          <input id="cfgOtel" type="checkbox" />
+        </label>
+        <p class="form-note">${t("cfg.otelHint")}</p>
+        <div class="form-row">
+          <div class="form-group">
+            <label class="form-label">${t("cfg.otelEndpoint")} <span class="form-hint">OTEL_ENDPOINT</span></label>
+            <input id="cfgOtelEndpoint" class="form-input" type="text" />
+          </div>
+          <div class="form-group">
+            <label class="form-label">${t("cfg.otelServiceName")} <span class="form-hint">OTEL_SERVICE_NAME</span></label>
+            <input id="cfgOtelServiceName" class="form-input" type="text" />
+          </div>
+        </div>
+        <label class="toggle-row" for="cfgNotify">
+          <span>${t("cfg.notifications")}</span>
+          <input id="cfgNotify" type="checkbox" />
+        </label>
+        <p class="form-note">${t("cfg.notificationsHint")}</p>
+        <div class="form-row">
+          <div class="form-group">
+            <label class="form-label">${t("cfg.notifyMinHealthyKeys")} <span class="form-hint">NOTIFY_MIN_HEALTHY_KEYS</span></label>
+            <input id="cfgNotifyMinHealthy" class="form-input" type="number" min="0" />
+          </div>
+          <div class="form-group">
+            <label class="form-label">${t("cfg.notifyCooldown")} <span class="form-hint">NOTIFY_COOLDOWN_MINUTES</span></label>
+            <div class="input-with-suffix">
+              <input id="cfgNotifyCooldown" class="form-input" type="number" min="1" />
+              <span class="input-suffix">min</span>
+            </div>
+          </div>
+        </div>
+      </div>
+
+      <div class="card">
+        <div class="card-header">${t("cfg.backups")}</div>
+        <div class="backup-row">
+          <select id="cfgBackupSelect" class="form-input"></select>
+          <button id="cfgRestoreBtn" class="btn btn-sm">${t("cfg.restore")}</button>
+        </div>
+        <p class="form-note">${t("cfg.backupsHint")}</p>
       </div>
 
       <div class="config-actions">
         <button id="cfgSaveBtn" class="btn btn-primary">${t("cfg.save")}</button>
         <span id="cfgDirtyBadge" class="dirty-badge">${t("cfg.unsaved")}</span>
+        <span class="config-actions-spacer"></span>
+        <button id="cfgImportEnvBtn" class="btn btn-sm">${t("cfg.importEnv")}</button>
+        <button id="cfgExportEnvBtn" class="btn btn-sm">${t("cfg.exportEnv")}</button>
+        <input id="cfgImportEnvFile" type="file" accept=".env,text/plain" hidden />
       </div>
     `;
   },
 
   async init() {
     try {
-      const [config, launchOnLogin] = await Promise.all([
+      const [config, launchOnLogin, sources] = await Promise.all([
         invoke("load_proxy_config"),
         invoke("get_launch_on_login_enabled").catch(() => false),
+        invoke("get_config_sources").catch(() => ({})),
       ]);
       this._savedConfig = config;
       this._savedLaunchOnLogin = !!launchOnLogin;
       this._writeForm({ ...config, launchOnLogin: this._savedLaunchOnLogin });
+      this._markEnvOverrides(sources);
+      this._showRecovery(await invoke("get_config_recovery").catch(() => null));
+      this._showLocked(await invoke("get_secrets_locked").catch(() => false));
     } catch (e) {
       showToast(t("cfg.loadFailed") + e, "error");
     }
@@ -796,24 +1420,54 @@ pages.config = {
       "cfgPort",
       "cfgTavilyPort",
       "cfgTimeout",
+      "cfgDrainTimeout",
+      "cfgKeyWatchDir",
+      "cfgKeyWatchInterval",
+      "cfgFirecrawlKeyTiers",
+      "cfgTavilyKeyTiers",
+      "cfgFirecrawlKeyRoutes",
+      "cfgTavilyKeyRoutes",
+      "cfgFirecrawlKeyRpm",
+      "cfgTavilyKeyRpm",
       "cfgCooldown",
       "cfgApiKeys",
       "cfgTavilyApiKeys",
+      "cfgLogFileMaxSize",
+      "cfgLogFileRetention",
+      "cfgHistoryRetentionDays",
+      "cfgHistoryMaxRows",
+      "cfgOtelEndpoint",
+      "cfgOtelServiceName",
+      "cfgNotifyMinHealthy",
+      "cfgNotifyCooldown",
+      "cfgPassphrase",
     ];
     inputs.forEach((id) => {
       const el = document.getElementById(id);
       if (el) el.addEventListener("input", () => this._checkDirty());
     });
-    const launchOnLoginEl = document.getElementById("cfgLaunchOnLogin");
-    if (launchOnLoginEl) launchOnLoginEl.addEventListener("change", () => this._checkDirty());
+    ["cfgLaunchOnLogin", "cfgAutoStart", "cfgSecretsEncryption", "cfgLogLevel", "cfgLogJson", "cfgLogFile", "cfgLogFileCompress", "cfgHistory", "cfgOtel", "cfgNotify"].forEach((id) => {
+      const el = document.getElementById(id);
+      if (el) el.addEventListener("change", () => this._checkDirty());
+    });
 
     document.getElementById("cfgSaveBtn").addEventListener("click", async () => {
       const btn = document.getElementById("cfgSaveBtn");
       setLoading(btn, true);
       try {
         const form = this._readForm();
-        const { launchOnLogin, ...config } = form;
-        await invoke("save_proxy_config", { config });
+        const { launchOnLogin, ...formConfig } = form;
+        // Keep fields the form does not edit instead of resetting them to defaults.
+        const config = {
+          ...(this._savedConfig || {}),
+          ...formConfig,
+          firecrawlApiKeys: mergeKeyRecords(formConfig.firecrawlApiKeys, this._savedConfig?.firecrawlApiKeys),
+          tavilyApiKeys: mergeKeyRecords(formConfig.tavilyApiKeys, this._savedConfig?.tavilyApiKeys),
+        };
+        const passphraseInput = document.getElementById("cfgPassphrase");
+        const passphrase = config.secretsEncryption === "passphrase" && passphraseInput.value ? passphraseInput.value : null;
+        await invoke("save_proxy_config", { config, passphrase });
+        passphraseInput.value = "";
         try {
           const actual = await invoke("set_launch_on_login_enabled", { enabled: launchOnLogin });
           this._savedLaunchOnLogin = !!actual;
@@ -822,12 +1476,231 @@ pages.config = {
         }
         this._savedConfig = config;
         this._checkDirty();
+        this._loadBackups();
         showToast(t("cfg.saved"), "success");
       } catch (e) {
         showToast(t("cfg.saveFailed") + e, "error");
       }
       setLoading(btn, false);
     });
+
+    const fileInput = document.getElementById("cfgImportEnvFile");
+    document.getElementById("cfgImportEnvBtn").addEventListener("click", () => fileInput.click());
+    fileInput.addEventListener("change", async () => {
+      const file = fileInput.files?.[0];
+      fileInput.value = "";
+      if (!file) return;
+      try {
+        const { launchOnLogin, ...formConfig } = this._readForm();
+        const base = { ...(this._savedConfig || {}), ...formConfig };
+        const result = await invoke("import_env_config", { base, text: await file.text() });
+        this._writeForm({ ...result.config, launchOnLogin });
+        this._checkDirty();
+        showToast(t("cfg.imported").replace("{n}", result.applied.length), "success");
+        if (result.ignored.length) {
+          showToast(t("cfg.importIgnored") + result.ignored.join(", "), "info");
+        }
+      } catch (e) {
+        showToast(t("cfg.importFailed") + e, "error");
+      }
+    });
+
+    document.getElementById("cfgSecretsEncryption").addEventListener("change", () => this._togglePassphrase());
+
+    document.getElementById("cfgRevealBtn").addEventListener("click", async () => {
+      try {
+        const secrets = await invoke("reveal_secrets");
+        const revealed = {
+          proxyToken: secrets.proxyToken,
+          firecrawlApiKeys: secrets.firecrawlApiKeys,
+          tavilyApiKeys: secrets.tavilyApiKeys,
+        };
+        // Unsaved edits elsewhere in the form are kept.
+        this._savedConfig = { ...this._savedConfig, ...revealed };
+        this._writeForm({ ...this._readForm(), ...revealed });
+        this._checkDirty();
+      } catch (e) {
+        showToast(t("cfg.revealFailed") + e, "error");
+      }
+    });
+
+    const unlockBtn = document.getElementById("cfgUnlockBtn");
+    unlockBtn.addEventListener("click", async () => {
+      const input = document.getElementById("cfgUnlockPassphrase");
+      setLoading(unlockBtn, true);
+      try {
+        const config = await invoke("unlock_secrets", { passphrase: input.value });
+        input.value = "";
+        this._savedConfig = config;
+        this._writeForm({ ...config, launchOnLogin: this._savedLaunchOnLogin });
+        this._checkDirty();
+        this._showLocked(false);
+        showToast(t("cfg.unlocked"), "success");
+        updateSidebarStatus().catch(() => {});
+      } catch (e) {
+        showToast(t("cfg.unlockFailed") + e, "error");
+      }
+      setLoading(unlockBtn, false);
+    });
+
+    this._loadProfiles();
+    const profileSelect = document.getElementById("cfgProfileSelect");
+    const profileName = document.getElementById("cfgProfileName");
+    const profileAction = async (btn, run) => {
+      setLoading(btn, true);
+      try {
+        await run();
+        profileName.value = "";
+      } catch (e) {
+        showToast(t("cfg.profileFailed") + e, "error");
+      }
+      setLoading(btn, false);
+      this._loadProfiles();
+    };
+    const activateBtn = document.getElementById("cfgActivateProfileBtn");
+    activateBtn.addEventListener("click", () =>
+      profileAction(activateBtn, async () => {
+        const name = profileSelect.value;
+        await invoke("activate_profile", { name });
+        const [config, sources] = await Promise.all([
+          invoke("load_proxy_config"),
+          invoke("get_config_sources").catch(() => ({})),
+        ]);
+        this._savedConfig = config;
+        this._writeForm({ ...config, launchOnLogin: this._savedLaunchOnLogin });
+        this._markEnvOverrides(sources);
+        this._showLocked(await invoke("get_secrets_locked").catch(() => false));
+        this._checkDirty();
+        this._loadBackups();
+        updateSidebarStatus().catch(() => {});
+        showToast(t("cfg.profileActivated").replace("{name}", name), "success");
+      })
+    );
+    const newBtn = document.getElementById("cfgNewProfileBtn");
+    newBtn.addEventListener("click", () =>
+      profileAction(newBtn, () => invoke("create_profile", { name: profileName.value.trim() }))
+    );
+    const cloneBtn = document.getElementById("cfgCloneProfileBtn");
+    cloneBtn.addEventListener("click", () =>
+      profileAction(cloneBtn, () =>
+        invoke("clone_profile", { source: profileSelect.value, name: profileName.value.trim() })
+      )
+    );
+    const renameBtn = document.getElementById("cfgRenameProfileBtn");
+    renameBtn.addEventListener("click", () =>
+      profileAction(renameBtn, async () => {
+        await invoke("rename_profile", { name: profileSelect.value, newName: profileName.value.trim() });
+        updateSidebarStatus().catch(() => {});
+      })
+    );
+    const deleteBtn = document.getElementById("cfgDeleteProfileBtn");
+    deleteBtn.addEventListener("click", () => {
+      const name = profileSelect.value;
+      if (!name || !window.confirm(t("cfg.deleteProfileConfirm").replace("{name}", name))) return;
+      profileAction(deleteBtn, () => invoke("delete_profile", { name }));
+    });
+
+    this._loadBackups();
+    const restoreBtn = document.getElementById("cfgRestoreBtn");
+    restoreBtn.addEventListener("click", async () => {
+      const name = document.getElementById("cfgBackupSelect").value;
+      if (!name || !window.confirm(t("cfg.restoreConfirm"))) return;
+      setLoading(restoreBtn, true);
+      try {
+        const config = await invoke("restore_config_backup", { name });
+        this._savedConfig = config;
+        this._writeForm({ ...config, launchOnLogin: this._readForm().launchOnLogin });
+        this._checkDirty();
+        showToast(t("cfg.restored"), "success");
+      } catch (e) {
+        showToast(t("cfg.restoreFailed") + e, "error");
+      }
+      setLoading(restoreBtn, false);
+      this._loadBackups();
+    });
+
+    const exportBtn = document.getElementById("cfgExportEnvBtn");
+    exportBtn.addEventListener("click", async () => {
+      setLoading(exportBtn, true);
+      try {
+        const path = await invoke("export_env_config");
+        showToast(t("cfg.exported") + path, "success");
+      } catch (e) {
+        showToast(t("cfg.exportFailed") + e, "error");
+      }
+      setLoading(exportBtn, false);
+    });
+  },
+
+  async _loadProfiles() {
+    const select = document.getElementById("cfgProfileSelect");
+    if (!select) return;
+    let profiles = [];
+    try {
+      profiles = await invoke("list_profiles");
+    } catch {}
+    select.innerHTML = profiles
+      .map((p) => {
+        const label = p.name + (p.active ? t("cfg.activeProfileTag") : "");
+        return `<option value="${escapeHtml(p.name)}"${p.active ? " selected" : ""}>${escapeHtml(label)}</option>`;
+      })
+      .join("");
+    const syncButtons = () => {
+      const active = profiles.find((p) => p.name === select.value)?.active ?? true;
+      document.getElementById("cfgActivateProfileBtn").disabled = active;
+      document.getElementById("cfgDeleteProfileBtn").disabled = active;
+    };
+    select.onchange = syncButtons;
+    syncButtons();
+  },
+
+  async _loadBackups() {
+    const select = document.getElementById("cfgBackupSelect");
+    if (!select) return;
+    let backups = [];
+    try {
+      backups = await invoke("list_config_backups");
+    } catch {}
+    select.innerHTML = backups.length
+      ? backups
+          .map((b) => {
+            const label = new Date(b.createdAtMs).toLocaleString() + (b.valid ? "" : t("cfg.backupInvalid"));
+            return `<option value="${escapeHtml(b.name)}"${b.valid ? "" : " disabled"}>${escapeHtml(label)}</option>`;
+          })
+          .join("")
+      : `<option value="">${t("cfg.noBackups")}</option>`;
+    document.getElementById("cfgRestoreBtn").disabled = !backups.some((b) => b.valid);
+  },
+
+  _showLocked(locked) {
+    document.getElementById("cfgLockedBanner")?.classList.toggle("visible", !!locked);
+  },
+
+  _togglePassphrase() {
+    const mode = document.getElementById("cfgSecretsEncryption").value;
+    document.getElementById("cfgPassphraseGroup").style.display = mode === "passphrase" ? "" : "none";
+  },
+
+  _showRecovery(recovery) {
+    const banner = document.getElementById("cfgRecoveryBanner");
+    if (!banner || !recovery) return;
+    banner.textContent = t("cfg.recovered").replace("{backup}", recovery.backup) + recovery.corruptCopy;
+    banner.classList.add("visible");
+  },
+
+  _markEnvOverrides(sources) {
+    const overridden = Object.keys(sources || {}).filter((name) => sources[name] === "env");
+    const banner = document.getElementById("cfgEnvBanner");
+    banner.textContent = overridden.length ? t("cfg.envOverridden") + overridden.join(", ") : "";
+    banner.classList.toggle("visible", overridden.length > 0);
+    overridden.forEach((name) => {
+      const input = document.getElementById(ENV_VAR_INPUTS[name]);
+      if (!input) return;
+      input.disabled = true;
+      input.title = t("cfg.envBadge") + ": " + name;
+      const hint = input.closest(".form-group")?.querySelector(".form-hint");
+      if (hint) hint.classList.add("form-hint-env");
+    });
   },
 
   _readForm() {
@@ -838,10 +1711,36 @@ pages.config = {
       tavilyApiKeys: parseKeys(document.getElementById("cfgTavilyApiKeys").value),
       tavilyUpstreamBaseUrl: document.getElementById("cfgTavilyUpstreamUrl").value.trim(),
       requestTimeoutMs: Number(document.getElementById("cfgTimeout").value),
+      drainTimeoutSeconds: Number(document.getElementById("cfgDrainTimeout").value),
+      keyWatchDir: document.getElementById("cfgKeyWatchDir").value.trim(),
+      keyWatchIntervalSeconds: Number(document.getElementById("cfgKeyWatchInterval").value),
+      firecrawlKeyTiers: parseKeys(document.getElementById("cfgFirecrawlKeyTiers").value),
+      tavilyKeyTiers: parseKeys(document.getElementById("cfgTavilyKeyTiers").value),
+      firecrawlKeyRoutes: parseKeys(document.getElementById("cfgFirecrawlKeyRoutes").value),
+      tavilyKeyRoutes: parseKeys(document.getElementById("cfgTavilyKeyRoutes").value),
+      firecrawlKeyRpm: Number(document.getElementById("cfgFirecrawlKeyRpm").value),
+      tavilyKeyRpm: Number(document.getElementById("cfgTavilyKeyRpm").value),
       keyCooldownSeconds: Number(document.getElementById("cfgCooldown").value),
       host: document.getElementById("cfgHost").value.trim(),
       port: Number(document.getElementById("cfgPort").value),
       tavilyPort: Number(document.getElementById("cfgTavilyPort").value),
+      logLevel: document.getElementById("cfgLogLevel").value,
+      logJson: !!document.getElementById("cfgLogJson").checked,
+      logFileEnabled: !!document.getElementById("cfgLogFile").checked,
+      logFileMaxSizeMb: Number(document.getElementById("cfgLogFileMaxSize").value),
+      logFileRetention: Number(document.getElementById("cfgLogFileRetention").value),
+      logFileCompress: !!document.getElementById("cfgLogFileCompress").checked,
+      historyEnabled: !!document.getElementById("cfgHistory").checked,
+      historyRetentionDays: Number(document.getElementById("cfgHistoryRetentionDays").value),
+      historyMaxRows: Number(document.getElementById("cfgHistoryMaxRows").value),
+      otelEnabled: !!document.getElementById("cfgOtel").checked,
+      otelEndpoint: document.getElementById("cfgOtelEndpoint").value.trim(),
+      otelServiceName: document.getElementById("cfgOtelServiceName").value.trim(),
+      notificationsEnabled: !!document.getElementById("cfgNotify").checked,
+      notifyMinHealthyKeys: Number(document.getElementById("cfgNotifyMinHealthy").value),
+      notifyCooldownMinutes: Number(document.getElementById("cfgNotifyCooldown").value),
+      autoStartProxy: !!document.getElementById("cfgAutoStart").checked,
+      secretsEncryption: document.getElementById("cfgSecretsEncryption").value,
       launchOnLogin: !!document.getElementById("cfgLaunchOnLogin").checked,
     };
   },
@@ -854,9 +1753,36 @@ pages.config = {
     document.getElementById("cfgPort").value = String(c.port || 8787);
     document.getElementById("cfgTavilyPort").value = String(c.tavilyPort || 8788);
     document.getElementById("cfgTimeout").value = String(c.requestTimeoutMs || 60000);
+    document.getElementById("cfgDrainTimeout").value = String(c.drainTimeoutSeconds ?? 30);
+    document.getElementById("cfgKeyWatchDir").value = c.keyWatchDir || "";
+    document.getElementById("cfgKeyWatchInterval").value = String(c.keyWatchIntervalSeconds || 10);
+    document.getElementById("cfgFirecrawlKeyTiers").value = (c.firecrawlKeyTiers || []).join(", ");
+    document.getElementById("cfgTavilyKeyTiers").value = (c.tavilyKeyTiers || []).join(", ");
+    document.getElementById("cfgFirecrawlKeyRoutes").value = (c.firecrawlKeyRoutes || []).join("\n");
+    document.getElementById("cfgTavilyKeyRoutes").value = (c.tavilyKeyRoutes || []).join("\n");
+    document.getElementById("cfgFirecrawlKeyRpm").value = String(c.firecrawlKeyRpm ?? 0);
+    document.getElementById("cfgTavilyKeyRpm").value = String(c.tavilyKeyRpm ?? 0);
     document.getElementById("cfgCooldown").value = String(c.keyCooldownSeconds || 60);
     document.getElementById("cfgApiKeys").value = normalizeKeysText(c.firecrawlApiKeys);
     document.getElementById("cfgTavilyApiKeys").value = normalizeKeysText(c.tavilyApiKeys);
+    document.getElementById("cfgLogLevel").value = c.logLevel || "info";
+    document.getElementById("cfgLogJson").checked = !!c.logJson;
+    document.getElementById("cfgLogFile").checked = c.logFileEnabled !== false;
+    document.getElementById("cfgLogFileMaxSize").value = String(c.logFileMaxSizeMb || 10);
+    document.getElementById("cfgLogFileRetention").value = String(c.logFileRetention || 7);
+    document.getElementById("cfgLogFileCompress").checked = !!c.logFileCompress;
+    document.getElementById("cfgHistory").checked = c.historyEnabled !== false;
+    document.getElementById("cfgHistoryRetentionDays").value = String(c.historyRetentionDays || 30);
+    document.getElementById("cfgHistoryMaxRows").value = String(c.historyMaxRows || 200000);
+    document.getElementById("cfgOtel").checked = !!c.otelEnabled;
+    document.getElementById("cfgOtelEndpoint").value = c.otelEndpoint || DEFAULT_OTEL_ENDPOINT;
+    document.getElementById("cfgOtelServiceName").value = c.otelServiceName || "balance-proxy";
+    document.getElementById("cfgNotify").checked = c.notificationsEnabled !== false;
+    document.getElementById("cfgNotifyMinHealthy").value = String(c.notifyMinHealthyKeys ?? 1);
+    document.getElementById("cfgNotifyCooldown").value = String(c.notifyCooldownMinutes || 10);
+    document.getElementById("cfgAutoStart").checked = !!c.autoStartProxy;
+    document.getElementById("cfgSecretsEncryption").value = c.secretsEncryption || "off";
+    this._togglePassphrase();
     document.getElementById("cfgLaunchOnLogin").checked = !!c.launchOnLogin;
   },
 
@@ -873,10 +1799,37 @@ pages.config = {
       cur.port !== (saved.port || 8787) ||
       cur.tavilyPort !== (saved.tavilyPort || 8788) ||
       cur.requestTimeoutMs !== (saved.requestTimeoutMs || 60000) ||
+      cur.drainTimeoutSeconds !== (saved.drainTimeoutSeconds ?? 30) ||
+      cur.keyWatchDir !== (saved.keyWatchDir || "") ||
+      cur.keyWatchIntervalSeconds !== (saved.keyWatchIntervalSeconds || 10) ||
+      cur.firecrawlKeyTiers.join(",") !== (saved.firecrawlKeyTiers || []).join(",") ||
+      cur.tavilyKeyTiers.join(",") !== (saved.tavilyKeyTiers || []).join(",") ||
+      cur.firecrawlKeyRoutes.join(",") !== (saved.firecrawlKeyRoutes || []).join(",") ||
+      cur.tavilyKeyRoutes.join(",") !== (saved.tavilyKeyRoutes || []).join(",") ||
+      cur.firecrawlKeyRpm !== (saved.firecrawlKeyRpm ?? 0) ||
+      cur.tavilyKeyRpm !== (saved.tavilyKeyRpm ?? 0) ||
       cur.keyCooldownSeconds !== (saved.keyCooldownSeconds || 60) ||
-      JSON.stringify(cur.firecrawlApiKeys) !== JSON.stringify(saved.firecrawlApiKeys || []) ||
-      JSON.stringify(cur.tavilyApiKeys) !== JSON.stringify(saved.tavilyApiKeys || []) ||
-      cur.launchOnLogin !== this._savedLaunchOnLogin;
+      cur.logLevel !== (saved.logLevel || "info") ||
+      cur.logJson !== !!saved.logJson ||
+      cur.logFileEnabled !== (saved.logFileEnabled !== false) ||
+      cur.logFileMaxSizeMb !== (saved.logFileMaxSizeMb || 10) ||
+      cur.logFileRetention !== (saved.logFileRetention || 7) ||
+      cur.logFileCompress !== !!saved.logFileCompress ||
+      cur.historyEnabled !== (saved.historyEnabled !== false) ||
+      cur.historyRetentionDays !== (saved.historyRetentionDays || 30) ||
+      cur.historyMaxRows !== (saved.historyMaxRows || 200000) ||
+      cur.otelEnabled !== !!saved.otelEnabled ||
+      cur.otelEndpoint !== (saved.otelEndpoint || DEFAULT_OTEL_ENDPOINT) ||
+      cur.otelServiceName !== (saved.otelServiceName || "balance-proxy") ||
+      cur.notificationsEnabled !== (saved.notificationsEnabled !== false) ||
+      cur.notifyMinHealthyKeys !== (saved.notifyMinHealthyKeys ?? 1) ||
+      cur.notifyCooldownMinutes !== (saved.notifyCooldownMinutes || 10) ||
+      cur.autoStartProxy !== !!saved.autoStartProxy ||
+      cur.secretsEncryption !== (saved.secretsEncryption || "off") ||
+      JSON.stringify(cur.firecrawlApiKeys) !== JSON.stringify((saved.firecrawlApiKeys || []).map(keyValue)) ||
+      JSON.stringify(cur.tavilyApiKeys) !== JSON.stringify((saved.tavilyApiKeys || []).map(keyValue)) ||
+      cur.launchOnLogin !== this._savedLaunchOnLogin ||
+      !!document.getElementById("cfgPassphrase").value;
     badge.classList.toggle("visible", dirty);
   },
 
@@ -888,6 +1841,8 @@ pages.config = {
 // ============================================
 pages.keys = {
   _timer: null,
+  _snapshot: null,
+  _fetchedAt: 0,
 
   template() {
     return `
@@ -896,24 +1851,303 @@ pages.keys = {
         ${t("keys.desc")}
       </p>
       <div id="keyListContainer" class="card">
-        <div class="card-header">${t("keys.firecrawl")}</div>
+        <div class="card-header provider-header">
+          <span>${t("keys.firecrawl")} <span class="provider-drain" id="drainFirecrawl"></span></span>
+          ${this._controlsTemplate("firecrawl")}
+        </div>
         <div class="key-list" id="keyListFirecrawl"></div>
-        <div class="card-header" style="margin-top:16px">${t("keys.tavily")}</div>
+        <div class="card-header provider-header" style="margin-top:16px">
+          <span>${t("keys.tavily")} <span class="provider-drain" id="drainTavily"></span></span>
+          ${this._controlsTemplate("tavily")}
+        </div>
         <div class="key-list" id="keyListTavily"></div>
       </div>
+      <div class="card">
+        <div class="card-header">${t("keys.manage")}</div>
+        <p class="form-note">${t("keys.manageHint")}</p>
+        <div class="backup-row key-import-row">
+          <select class="form-input" id="keyImportProvider">
+            <option value="">${t("keys.importAuto")}</option>
+            ${PROVIDERS.map((provider) => `<option value="${provider}">${t(`keys.${provider}`)}</option>`).join("")}
+          </select>
+          <button class="btn btn-sm" id="keyImportBtn">${t("keys.import")}</button>
+          <input type="file" id="keyImportFiles" multiple accept=".txt,.csv,.json,text/plain" hidden />
+        </div>
+        <p class="form-note">${t("keys.importHint")}</p>
+        <label class="toggle-row key-remove-invalid" for="keyRemoveInvalid">
+          <span>${t("keys.removeInvalid")}</span>
+          <input type="checkbox" id="keyRemoveInvalid" />
+        </label>
+        <div class="key-import-result" id="keyImportResult"></div>
+        ${PROVIDERS.map((provider) => `
+          <div class="key-manage-title">
+            <span>${t(`keys.${provider}`)}</span>
+            <button class="btn btn-sm key-validate-btn" data-provider="${provider}">${t("keys.validate")}</button>
+          </div>
+          <div class="key-import-result" id="keyProbe-${provider}"></div>
+          <div class="key-list" id="keyManage-${provider}"></div>
+          <div class="backup-row key-add-row" data-provider="${provider}">
+            <input class="form-input key-add-value" type="password" autocomplete="off" placeholder="${t("keys.keyValue")}" />
+            <input class="form-input key-add-label" type="text" placeholder="${t("keys.keyLabel")}" />
+            <input class="form-input key-weight" type="number" min="1" max="100" value="1" title="${t("keys.keyWeight")}" />
+            <button class="btn btn-sm btn-primary key-add-btn">${t("keys.add")}</button>
+          </div>
+        `).join("")}
+      </div>
       <div class="keys-legend">
         <span class="keys-legend-item"><span class="legend-dot green"></span> ${t("keys.active")}</span>
         <span class="keys-legend-item"><span class="legend-dot amber"></span> ${t("keys.cooldown")}</span>
         <span class="keys-legend-item"><span class="legend-dot gray"></span> ${t("keys.idle")}</span>
+        <span class="keys-legend-item"><span class="legend-dot red"></span> ${t("keys.dead")}</span>
       </div>
       <p class="keys-note">${t("keys.editNote")}</p>
     `;
   },
 
+  _controlsTemplate(provider) {
+    return `
+      <span class="provider-controls">
+        <button class="btn btn-sm" data-provider="${provider}" data-action="start">${t("keys.start")}</button>
+        <button class="btn btn-sm" data-provider="${provider}" data-action="stop">${t("keys.stop")}</button>
+        <button class="btn btn-sm" data-provider="${provider}" data-action="restart">${t("keys.restart")}</button>
+      </span>
+    `;
+  },
+
+  async _runProviderAction(button) {
+    const { provider, action } = button.dataset;
+    button.classList.add("btn-loading");
+    try {
+      await invoke(`${action}_provider_proxy`, { provider });
+    } catch (e) {
+      showToast(t("keys.actionFailed") + e, "error");
+    } finally {
+      button.classList.remove("btn-loading");
+      await this._refresh();
+    }
+  },
+
   async init() {
     document.getElementById("keysGoConfig").addEventListener("click", () => navigate("config"));
+    document.querySelectorAll(".provider-controls button").forEach((button) => {
+      button.addEventListener("click", () => this._runProviderAction(button));
+    });
+    onPageEvent("key-state", () => this._refresh());
+    onPageEvent("proxy-lifecycle", () => this._refresh());
+    document.querySelectorAll(".key-add-row").forEach((row) => {
+      row.querySelector(".key-add-btn").addEventListener("click", async () => {
+        const value = row.querySelector(".key-add-value");
+        const label = row.querySelector(".key-add-label");
+        const weight = row.querySelector(".key-weight");
+        const ok = await this._editKey("add_key", row.dataset.provider, {
+          value: value.value,
+          details: { label: label.value.trim(), notes: "", weight: Number(weight.value) || 1 },
+        });
+        if (ok) {
+          value.value = "";
+          label.value = "";
+          weight.value = "1";
+        }
+      });
+    });
+    const importFiles = document.getElementById("keyImportFiles");
+    document.getElementById("keyImportBtn").addEventListener("click", () => importFiles.click());
+    importFiles.addEventListener("change", async () => {
+      const files = await Promise.all(
+        Array.from(importFiles.files).map(async (file) => ({ name: file.name, text: await file.text() }))
+      );
+      importFiles.value = "";
+      if (files.length) await this._importKeys(files, document.getElementById("keyImportProvider").value || null);
+    });
+    document.querySelectorAll(".key-validate-btn").forEach((button) => {
+      button.addEventListener("click", () => this._validateKeys(button));
+    });
+    PROVIDERS.forEach((provider) => this._loadManagedKeys(provider));
+
+    await this._refresh();
+    // Cooldown countdowns tick locally; state changes arrive as events. The
+    // in-flight count of a draining listener has no event, so poll it.
+    this._timer = setInterval(() => {
+      if (this._status?.firecrawlDraining || this._status?.tavilyDraining) this._refresh();
+      else this._render();
+    }, 1000);
+  },
+
+  async _loadManagedKeys(provider) {
+    const listEl = document.getElementById(`keyManage-${provider}`);
+    if (!listEl) return;
+    try {
+      this._renderManagedKeys(provider, await invoke("list_keys", { provider }));
+    } catch {
+      listEl.innerHTML = `<div style="padding:12px;color:var(--text-muted)">${t("keys.loadFailed")}</div>`;
+    }
+  },
+
+  _renderManagedKeys(provider, keys) {
+    const listEl = document.getElementById(`keyManage-${provider}`);
+    if (!listEl) return;
+    if (!keys.length) {
+      listEl.innerHTML = `<div style="padding:12px;color:var(--text-muted)">${t("keys.notConfigured")}</div>`;
+      return;
+    }
+    listEl.innerHTML = keys.map((key, index) => {
+      const meta = [
+        key.addedAt ? t("keys.addedAt") + new Date(key.addedAt * 1000).toLocaleDateString() : "",
+        key.source === "manual" ? t("keys.sourceManual") : "",
+        key.source === "import" ? t("keys.sourceImport") : "",
+      ].filter(Boolean).join(" · ");
+      return `
+        <div class="key-row key-manage-row${key.enabled ? "" : " key-disabled"}" data-fingerprint="${escapeHtml(key.fingerprint)}">
+          <label class="key-enabled" title="${t("keys.enabled")}"><input type="checkbox" data-action="toggle"${key.enabled ? " checked" : ""} /></label>
+          <span>
+            <span class="key-preview">${escapeHtml(key.value)}</span>
+            <span class="key-meta">${escapeHtml(meta)}</span>
+          </span>
+          <input class="form-input key-label-input" data-action="label" type="text" value="${escapeHtml(key.label)}" placeholder="${t("keys.keyLabel")}" />
+          <input class="form-input key-weight" data-action="weight" type="number" min="1" max="100" value="${key.weight}" title="${t("keys.keyWeight")}" />
+          <input class="form-input key-weight" data-action="resetDay" type="number" min="1" max="31" value="${key.quotaResetDay ?? ""}" placeholder="${t("keys.resetDay")}" title="${t("keys.resetDayHint")}" />
+          <input class="form-input key-tier" data-action="tier" type="text" value="${escapeHtml(key.tier || "")}" placeholder="${t("keys.tier")}" title="${t("keys.tierHint")}" />
+          <input class="form-input key-tier" data-action="pool" type="text" value="${escapeHtml(key.pool || "")}" placeholder="${t("keys.pool")}" title="${t("keys.poolHint")}" />
+          <input class="form-input key-weight" data-action="maxConcurrency" type="number" min="1" max="1000" value="${key.maxConcurrency ?? ""}" placeholder="${t("keys.maxConcurrency")}" title="${t("keys.maxConcurrencyHint")}" />
+          <input class="form-input key-expiry" data-action="expiresOn" type="date" value="${escapeHtml(key.expiresOn || "")}" title="${t("keys.expiresOn")}" />
+          <span class="key-manage-actions">
+            <button class="btn btn-sm" data-action="up" title="${t("keys.moveUp")}"${index === 0 ? " disabled" : ""}>↑</button>
+            <button class="btn btn-sm" data-action="down" title="${t("keys.moveDown")}"${index === keys.length - 1 ? " disabled" : ""}>↓</button>
+            <button class="btn btn-sm" data-action="notes" title="${escapeHtml(key.notes || t("keys.notes"))}">${t("keys.notes")}</button>
+            <button class="btn btn-sm" data-action="remove">${t("keys.remove")}</button>
+          </span>
+        </div>
+      `;
+    }).join("");
+
+    listEl.querySelectorAll(".key-manage-row").forEach((row, index) => {
+      const key = keys[index];
+      const fingerprint = key.fingerprint;
+      const details = (changes) => ({
+        label: key.label,
+        notes: key.notes,
+        weight: key.weight,
+        quotaResetDay: key.quotaResetDay ?? null,
+        expiresOn: key.expiresOn ?? null,
+        tier: key.tier || "",
+        pool: key.pool || "",
+        maxConcurrency: key.maxConcurrency ?? null,
+        ...changes,
+      });
+      row.querySelector('[data-action="toggle"]').addEventListener("change", (e) =>
+        this._editKey("set_key_enabled", provider, { fingerprint, enabled: e.target.checked })
+      );
+      row.querySelector('[data-action="label"]').addEventListener("change", (e) =>
+        this._editKey("update_key", provider, { fingerprint, details: details({ label: e.target.value.trim() }) })
+      );
+      row.querySelector('[data-action="weight"]').addEventListener("change", (e) =>
+        this._editKey("update_key", provider, { fingerprint, details: details({ weight: Number(e.target.value) }) })
+      );
+      row.querySelector('[data-action="resetDay"]').addEventListener("change", (e) =>
+        this._editKey("update_key", provider, { fingerprint, details: details({ quotaResetDay: e.target.value ? Number(e.target.value) : null }) })
+      );
+      row.querySelector('[data-action="tier"]').addEventListener("change", (e) =>
+        this._editKey("update_key", provider, { fingerprint, details: details({ tier: e.target.value.trim() }) })
+      );
+      row.querySelector('[data-action="pool"]').addEventListener("change", (e) =>
+        this._editKey("update_key", provider, { fingerprint, details: details({ pool: e.target.value.trim() }) })
+      );
+      row.querySelector('[data-action="maxConcurrency"]').addEventListener("change", (e) =>
+        this._editKey("update_key", provider, { fingerprint, details: details({ maxConcurrency: e.target.value ? Number(e.target.value) : null }) })
+      );
+      row.querySelector('[data-action="expiresOn"]').addEventListener("change", (e) =>
+        this._editKey("update_key", provider, { fingerprint, details: details({ expiresOn: e.target.value || null }) })
+      );
+      row.querySelector('[data-action="up"]').addEventListener("click", () =>
+        this._editKey("move_key", provider, { fingerprint, position: index - 1 })
+      );
+      row.querySelector('[data-action="down"]').addEventListener("click", () =>
+        this._editKey("move_key", provider, { fingerprint, position: index + 1 })
+      );
+      row.querySelector('[data-action="notes"]').addEventListener("click", () => {
+        const notes = window.prompt(t("keys.notesPrompt"), key.notes || "");
+        if (notes !== null) this._editKey("update_key", provider, { fingerprint, details: details({ notes }) });
+      });
+      row.querySelector('[data-action="remove"]').addEventListener("click", () => {
+        if (window.confirm(t("keys.removeConfirm").replace("{key}", key.label || key.value))) {
+          this._editKey("remove_key", provider, { fingerprint });
+        }
+      });
+    });
+  },
+
+  async _editKey(command, provider, args) {
+    try {
+      this._renderManagedKeys(provider, await invoke(command, { provider, ...args }));
+      await this._refresh();
+      return true;
+    } catch (e) {
+      showToast(t("keys.editFailed") + e, "error");
+      this._loadManagedKeys(provider);
+      return false;
+    }
+  },
+
+  async _validateKeys(button) {
+    const { provider } = button.dataset;
+    const resultEl = document.getElementById(`keyProbe-${provider}`);
+    button.classList.add("btn-loading");
+    try {
+      const report = await invoke("validate_keys", {
+        provider,
+        removeInvalid: !!document.getElementById("keyRemoveInvalid").checked,
+      });
+      const counts = {};
+      report.results.forEach((result) => { counts[result.outcome] = (counts[result.outcome] || 0) + 1; });
+      showToast(
+        t("keys.validateDone")
+          .replace("{valid}", counts.valid || 0)
+          .replace("{invalid}", counts.invalid || 0)
+          .replace("{exhausted}", counts.exhausted || 0)
+          .replace("{limited}", counts.rateLimited || 0)
+          .replace("{removed}", report.removed),
+        counts.invalid || counts.exhausted ? "info" : "success"
+      );
+      resultEl.innerHTML = report.results
+        .filter((result) => result.outcome !== "valid")
+        .map((result) => `
+          <div class="key-import-entry">${escapeHtml(t(`keys.probe.${result.outcome}`))} ·
+            <span class="key-preview">${escapeHtml(result.key)}</span>${result.label ? ` ${escapeHtml(result.label)}` : ""}
+            ${result.detail ? ` — ${escapeHtml(result.detail)}` : ""}</div>
+        `).join("");
+    } catch (e) {
+      showToast(t("keys.validateFailed") + e, "error");
+    } finally {
+      button.classList.remove("btn-loading");
+    }
+    this._loadManagedKeys(provider);
+    await this._refresh();
+  },
+
+  async _importKeys(files, provider) {
+    const resultEl = document.getElementById("keyImportResult");
+    try {
+      const report = await invoke("import_keys", { files, provider });
+      showToast(
+        t("keys.importDone")
+          .replace("{added}", report.added.length)
+          .replace("{skipped}", report.skipped.length)
+          .replace("{invalid}", report.invalid.length),
+        report.added.length ? "success" : "info"
+      );
+      const rows = [
+        ...report.skipped.map((entry) => [t("keys.importSkipped"), entry]),
+        ...report.invalid.map((entry) => [t("keys.importInvalid"), entry]),
+      ];
+      resultEl.innerHTML = rows.map(([kind, entry]) => `
+        <div class="key-import-entry">${escapeHtml(kind)} · ${escapeHtml(entry.file)}${entry.line ? `:${entry.line}` : ""}
+          ${entry.key ? `<span class="key-preview">${escapeHtml(entry.key)}</span>` : ""} — ${escapeHtml(entry.reason)}</div>
+      `).join("");
+    } catch (e) {
+      showToast(t("keys.importFailed") + e, "error");
+    }
+    PROVIDERS.forEach((p) => this._loadManagedKeys(p));
     await this._refresh();
-    this._timer = setInterval(() => this._refresh(), 2000);
   },
 
   _renderProviderRows(listEl, providerSnapshot) {
@@ -931,10 +2165,22 @@ pages.keys = {
     }
 
     const providerRunning = !!providerSnapshot.running;
-    listEl.innerHTML = statuses.map((k) => {
+    const elapsedSecs = Math.floor((Date.now() - this._fetchedAt) / 1000);
+    listEl.innerHTML = statuses.map((snapshotKey) => {
+      const remaining = Math.max(0, snapshotKey.cooldownRemainingSecs - elapsedSecs);
+      const k = { ...snapshotKey, isCoolingDown: snapshotKey.isCoolingDown && remaining > 0, cooldownRemainingSecs: remaining };
       let badgeClass = "badge-muted";
       let badgeText = t("keys.idle");
-      if (k.isCoolingDown) {
+      if (k.isDead) {
+        badgeClass = "badge-danger";
+        badgeText = t("keys.dead");
+      } else if (k.isExpired) {
+        badgeClass = "badge-danger";
+        badgeText = t("keys.expired");
+      } else if (k.isExhausted && k.isCoolingDown) {
+        badgeClass = "badge-warning";
+        badgeText = t("keys.exhausted");
+      } else if (k.isCoolingDown) {
         badgeClass = "badge-warning";
         badgeText = t("keys.cooldown");
       } else if (providerRunning) {
@@ -942,13 +2188,23 @@ pages.keys = {
         badgeText = t("keys.active");
       }
 
-      const cooldownHtml = k.cooldownRemainingSecs > 0
+      const cooldownHtml = k.cooldownRemainingSecs > 0 && !k.isExhausted
         ? `<span class="key-cooldown-timer">${k.cooldownRemainingSecs}s</span>`
         : "";
+      const schedule = [
+        k.tier ? `${t("keys.tier")}: ${k.tier}` : "",
+        k.pool ? `${t("keys.pool")}: ${k.pool}` : "",
+        k.inFlight > 0 || k.maxConcurrency != null
+          ? `${t("keys.inFlight")}: ${k.inFlight || 0}${k.maxConcurrency != null ? "/" + k.maxConcurrency : ""}`
+          : "",
+        k.rateRemaining != null ? `${t("keys.rateRemaining")}: ${k.rateRemaining}` : "",
+        k.daysUntilReset != null ? t("keys.resetsIn").replace("{n}", k.daysUntilReset) : "",
+        k.daysUntilExpiry != null && !k.isExpired ? t("keys.expiresIn").replace("{n}", k.daysUntilExpiry) : "",
+      ].filter(Boolean).join(" · ");
 
       return `
         <div class="key-row">
-          <span class="key-preview" title="${k.keyPreview}">${k.keyPreview}</span>
+          <span class="key-preview" title="${k.keyPreview}">${k.keyPreview}${k.label ? ` <span class="key-label">${escapeHtml(k.label)}</span>` : ""}${schedule ? `<span class="key-meta">${escapeHtml(schedule)}</span>` : ""}</span>
           <span class="badge ${badgeClass}">${badgeText}</span>
           <span class="key-fail-count">${k.failCount > 0 ? k.failCount + t("keys.failures") : cooldownHtml}</span>
         </div>
@@ -967,15 +2223,40 @@ pages.keys = {
         invoke("get_proxy_status").catch(() => ({})),
         invoke("get_key_status_snapshot").catch(() => null),
       ]);
-      const snapshot = snapshotRaw || buildFallbackKeySnapshot(config, status);
-      this._renderProviderRows(firecrawlListEl, snapshot.firecrawl);
-      this._renderProviderRows(tavilyListEl, snapshot.tavily);
+      this._snapshot = snapshotRaw || buildFallbackKeySnapshot(config, status);
+      this._status = status;
+      this._fetchedAt = Date.now();
+      this._render();
     } catch {
       firecrawlListEl.innerHTML = `<div style="padding:12px;color:var(--text-muted)">${t("keys.loadFailed")}</div>`;
       tavilyListEl.innerHTML = `<div style="padding:12px;color:var(--text-muted)">${t("keys.loadFailed")}</div>`;
     }
   },
 
+  _render() {
+    if (!this._snapshot) return;
+    this._renderProviderRows(document.getElementById("keyListFirecrawl"), this._snapshot.firecrawl);
+    this._renderProviderRows(document.getElementById("keyListTavily"), this._snapshot.tavily);
+    this._renderControls("firecrawl", "drainFirecrawl");
+    this._renderControls("tavily", "drainTavily");
+  },
+
+  _renderControls(provider, drainElId) {
+    const status = this._status || {};
+    const running = !!status[`${provider}Running`];
+    const configured = !!this._snapshot?.[provider]?.configured;
+    document.querySelectorAll(`.provider-controls button[data-provider="${provider}"]`).forEach((button) => {
+      const { action } = button.dataset;
+      button.disabled = action === "stop" ? !running : !configured || (action === "start" && running);
+    });
+    const drainEl = document.getElementById(drainElId);
+    if (drainEl) {
+      drainEl.textContent = status[`${provider}Draining`]
+        ? t("keys.draining").replace("{n}", status[`${provider}InFlight`] || 0)
+        : "";
+    }
+  },
+
   destroy() {
     if (this._timer) { clearInterval(this._timer); this._timer = null; }
   },
@@ -1164,8 +2445,10 @@ pages.mcp = {
 // ============================================
 // 8. PAGE: Logs
 // ============================================
+const MAX_UI_LOG_LINES = 500;
+
 pages.logs = {
-  _timer: null,
+  _renderPending: false,
   _allLogs: [],
   _activeFilter: "all",
   _searchText: "",
@@ -1177,16 +2460,25 @@ pages.logs = {
       <div class="logs-toolbar">
         <div class="filter-group">
           <button class="filter-btn active" data-filter="all">${t("logs.all")}</button>
+          <button class="filter-btn" data-filter="DEBUG">DEBUG</button>
           <button class="filter-btn" data-filter="INFO">INFO</button>
           <button class="filter-btn" data-filter="WARN">WARN</button>
           <button class="filter-btn" data-filter="ERROR">ERROR</button>
         </div>
         <input id="logsSearch" class="logs-search" type="text" placeholder="${t("logs.search")}" />
         <button id="logsRefreshBtn" class="btn btn-sm">${t("logs.refresh")}</button>
+        <select id="logsExportRange" class="logs-search logs-range">
+          <option value="3600">${t("logs.rangeHour")}</option>
+          <option value="86400" selected>${t("logs.rangeDay")}</option>
+          <option value="604800">${t("logs.rangeWeek")}</option>
+          <option value="all">${t("logs.rangeAll")}</option>
+        </select>
+        <button id="logsExportBtn" class="btn btn-sm">${t("logs.export")}</button>
       </div>
       <div id="logsViewer" class="logs-viewer"></div>
       <div class="logs-footer">
         <span id="logsCount">-</span>
+        <span id="logsDir" class="logs-dir"></span>
         <span>${t("logs.autoRefresh")}</span>
       </div>
     `;
@@ -1224,8 +2516,45 @@ pages.logs = {
 
     document.getElementById("logsRefreshBtn").addEventListener("click", () => this._fetchLogs());
 
+    const exportBtn = document.getElementById("logsExportBtn");
+    exportBtn.addEventListener("click", async () => {
+      const range = document.getElementById("logsExportRange").value;
+      const endTs = Math.floor(Date.now() / 1000);
+      const startTs = range === "all" ? null : endTs - Number(range);
+      setLoading(exportBtn, true);
+      try {
+        const path = await invoke("export_logs", { startTs, endTs });
+        showToast(t("logs.exported") + path, "success");
+      } catch (e) {
+        showToast(t("logs.exportFailed") + e, "error");
+      }
+      setLoading(exportBtn, false);
+    });
+
+    invoke("get_log_directory")
+      .then((dir) => {
+        const dirEl = document.getElementById("logsDir");
+        if (dirEl) dirEl.textContent = dir;
+      })
+      .catch(() => {});
+
+    onPageEvent("log-line", (line) => {
+      this._allLogs.push(line);
+      if (this._allLogs.length > MAX_UI_LOG_LINES) this._allLogs.shift();
+      this._scheduleRender();
+    });
+
     await this._fetchLogs();
-    this._timer = setInterval(() => this._fetchLogs(), 2000);
+  },
+
+  // Coalesces bursts of log events into one render per frame.
+  _scheduleRender() {
+    if (this._renderPending) return;
+    this._renderPending = true;
+    requestAnimationFrame(() => {
+      this._renderPending = false;
+      this._renderLogs();
+    });
   },
 
   async _fetchLogs() {
@@ -1258,7 +2587,7 @@ pages.logs = {
       const colored = line
         .replace(/&/g, "&amp;").replace(/</g, "&lt;").replace(/>/g, "&gt;")
         .replace(/^(\d{4}-\d{2}-\d{2}[T ]\d{2}:\d{2}:\d{2}(?:\.\d+)?)/, '<span class="log-ts">$1</span>')
-        .replace(/\[(INFO|WARN|ERROR)\]/, '<span class="log-level">[$1]</span>');
+        .replace(/\[(TRACE|DEBUG|INFO|WARN|ERROR)\]/, '<span class="log-level">[$1]</span>');
 
       return `<div class="log-line ${cls}">${colored}</div>`;
     }).join("");
@@ -1273,12 +2602,226 @@ pages.logs = {
   },
 
   destroy() {
-    if (this._timer) { clearInterval(this._timer); this._timer = null; }
+    this._renderPending = false;
+  },
+};
+
+// ============================================
+// 9. PAGE: Debug Capture
+// ============================================
+function escapeHtml(text) {
+  return String(text ?? "")
+    .replace(/&/g, "&amp;").replace(/</g, "&lt;").replace(/>/g, "&gt;").replace(/"/g, "&quot;");
+}
+
+function formatCapturedExchange(headers, body) {
+  const headerText = (headers || []).map((h) => `${h.name}: ${h.value}`).join("\n");
+  let bodyText = body?.text || "";
+  try {
+    bodyText = JSON.stringify(JSON.parse(bodyText), null, 2);
+  } catch {}
+  const note = body?.truncated ? t("debug.truncated", body.size) : "";
+  return `${headerText}\n\n${bodyText}${note}`;
+}
+
+pages.debug = {
+  _timer: null,
+  _selectedId: null,
+
+  template() {
+    return `
+      <h1>${t("debug.title")}</h1>
+      <p style="font-size:13px;color:var(--text-secondary);margin-bottom:16px">${t("debug.desc")}</p>
+
+      <div class="card">
+        <div class="form-row">
+          <div class="form-group">
+            <label class="form-label">${t("debug.maxRequests")}</label>
+            <input id="dbgMaxRequests" class="form-input" type="number" min="1" value="50" />
+          </div>
+          <div class="form-group">
+            <label class="form-label">${t("debug.maxMinutes")}</label>
+            <div class="input-with-suffix">
+              <input id="dbgMaxMinutes" class="form-input" type="number" min="1" value="10" />
+              <span class="input-suffix">min</span>
+            </div>
+          </div>
+          <div class="form-group">
+            <label class="form-label">${t("debug.maxBodyKb")}</label>
+            <div class="input-with-suffix">
+              <input id="dbgMaxBodyKb" class="form-input" type="number" min="1" value="64" />
+              <span class="input-suffix">KB</span>
+            </div>
+          </div>
+        </div>
+        <div class="flex-between">
+          <span id="dbgStatus" class="badge badge-muted">${t("debug.inactive")}</span>
+          <div class="status-actions">
+            <button id="dbgStartBtn" class="btn btn-primary btn-sm">${t("debug.start")}</button>
+            <button id="dbgStopBtn" class="btn btn-danger btn-sm">${t("debug.stop")}</button>
+            <button id="dbgClearBtn" class="btn btn-sm">${t("debug.clear")}</button>
+          </div>
+        </div>
+      </div>
+
+      <div class="card">
+        <div class="key-list" id="dbgList"></div>
+      </div>
+
+      <div id="dbgDetail" class="card hidden">
+        <div class="card-head-row">
+          <h2 id="dbgDetailTitle"></h2>
+          <button id="dbgReplayBtn" class="btn btn-sm btn-primary">${t("debug.replay")}</button>
+        </div>
+        <div class="card-header">${t("debug.request")}</div>
+        <div id="dbgRequest" class="code-block"></div>
+        <div class="card-header" style="margin-top:16px">${t("debug.response")}</div>
+        <div id="dbgResponse" class="code-block"></div>
+        <div id="dbgReplayWrap" class="hidden">
+          <div class="card-header" style="margin-top:16px">${t("debug.replayResult")}</div>
+          <div id="dbgReplay" class="code-block"></div>
+        </div>
+      </div>
+    `;
+  },
+
+  async init() {
+    this._selectedId = null;
+    const startBtn = document.getElementById("dbgStartBtn");
+    const stopBtn = document.getElementById("dbgStopBtn");
+    const clearBtn = document.getElementById("dbgClearBtn");
+    const replayBtn = document.getElementById("dbgReplayBtn");
+
+    startBtn.addEventListener("click", async () => {
+      const settings = {
+        maxRequests: Number(document.getElementById("dbgMaxRequests").value),
+        maxMinutes: Number(document.getElementById("dbgMaxMinutes").value),
+        maxBodyBytes: Number(document.getElementById("dbgMaxBodyKb").value) * 1024,
+      };
+      try {
+        this._renderStatus(await invoke("start_debug_capture", { settings }));
+      } catch (e) {
+        showToast(t("debug.failed") + e, "error");
+      }
+    });
+
+    stopBtn.addEventListener("click", async () => {
+      try {
+        this._renderStatus(await invoke("stop_debug_capture"));
+      } catch (e) {
+        showToast(t("debug.failed") + e, "error");
+      }
+    });
+
+    clearBtn.addEventListener("click", async () => {
+      try {
+        await invoke("clear_captures");
+        this._selectedId = null;
+        document.getElementById("dbgDetail").classList.add("hidden");
+        await this._refresh();
+      } catch (e) {
+        showToast(t("debug.failed") + e, "error");
+      }
+    });
+
+    document.getElementById("dbgList").addEventListener("click", (e) => {
+      const row = e.target.closest(".key-row");
+      if (row?.dataset.id) this._showCapture(row.dataset.id);
+    });
+
+    replayBtn.addEventListener("click", async () => {
+      if (!this._selectedId) return;
+      setLoading(replayBtn, true);
+      try {
+        const result = await invoke("replay_capture", { id: this._selectedId });
+        document.getElementById("dbgReplayWrap").classList.remove("hidden");
+        document.getElementById("dbgReplay").textContent =
+          `HTTP ${result.status} · ${result.latencyMs}ms · ${result.url}\n` +
+          formatCapturedExchange(result.headers, result.body);
+      } catch (e) {
+        showToast(t("debug.failed") + e, "error");
+      }
+      setLoading(replayBtn, false);
+    });
+
+    // Captures are written right after a request completes.
+    onPageEvent("request-completed", () => {
+      clearTimeout(this._timer);
+      this._timer = setTimeout(() => this._refresh(), 300);
+    });
+
+    await this._refresh();
+  },
+
+  _renderStatus(status) {
+    const el = document.getElementById("dbgStatus");
+    if (!el) return;
+    if (status?.active) {
+      const ends = status.expiresAt ? new Date(status.expiresAt * 1000).toLocaleTimeString() : "-";
+      el.className = "badge badge-warning";
+      el.textContent = t("debug.active", status.remainingRequests, ends);
+    } else {
+      el.className = "badge badge-muted";
+      el.textContent = t("debug.inactive");
+    }
+  },
+
+  async _refresh() {
+    const listEl = document.getElementById("dbgList");
+    if (!listEl) return;
+    try {
+      const [status, captures] = await Promise.all([
+        invoke("get_debug_capture_status"),
+        invoke("list_captures"),
+      ]);
+      this._renderStatus(status);
+      if (!captures.length) {
+        listEl.innerHTML = `<div style="padding:12px;color:var(--text-muted)">${t("debug.empty")}</div>`;
+        return;
+      }
+      listEl.innerHTML = captures.map((c) => {
+        const badgeClass = c.status >= 400 ? "badge-danger" : "badge-success";
+        const when = new Date(c.ts * 1000).toLocaleTimeString();
+        return `
+          <div class="key-row" data-id="${escapeHtml(c.id)}" style="cursor:pointer">
+            <span class="key-preview">${escapeHtml(c.provider)} ${escapeHtml(c.method)} ${escapeHtml(c.path)}</span>
+            <span class="badge ${badgeClass}">${c.status}</span>
+            <span class="key-fail-count">${when} · ${c.latencyMs}ms</span>
+          </div>
+        `;
+      }).join("");
+    } catch (e) {
+      listEl.innerHTML = `<div style="padding:12px;color:var(--text-muted)">${escapeHtml(t("debug.failed") + e)}</div>`;
+    }
+  },
+
+  async _showCapture(id) {
+    try {
+      const entry = await invoke("get_capture", { id });
+      this._selectedId = id;
+      const query = entry.query ? `?${entry.query}` : "";
+      document.getElementById("dbgDetail").classList.remove("hidden");
+      document.getElementById("dbgReplayWrap").classList.add("hidden");
+      document.getElementById("dbgDetailTitle").textContent =
+        `${entry.method} ${entry.path}${query} → ${entry.status}`;
+      document.getElementById("dbgRequest").textContent =
+        formatCapturedExchange(entry.requestHeaders, entry.requestBody);
+      document.getElementById("dbgResponse").textContent =
+        `key #${entry.keyIndex ?? "-"} · retries ${entry.retries} · ${entry.latencyMs}ms\n` +
+        formatCapturedExchange(entry.responseHeaders, entry.responseBody);
+      document.getElementById("dbgReplayBtn").disabled = !!entry.requestBody?.truncated;
+    } catch (e) {
+      showToast(t("debug.failed") + e, "error");
+    }
+  },
+
+  destroy() {
+    if (this._timer) { clearTimeout(this._timer); this._timer = null; }
   },
 };
 
 // ============================================
-// 9. BOOTSTRAP
+// 10. BOOTSTRAP
 // ============================================
 async function bootstrap() {
   // Apply saved language on startup
@@ -1287,7 +2830,7 @@ async function bootstrap() {
   });
 
   // Set sidebar nav labels to current language
-  const navLabels = { dashboard: "nav.dashboard", config: "nav.config", keys: "nav.keys", mcp: "nav.mcp", logs: "nav.logs" };
+  const navLabels = { dashboard: "nav.dashboard", config: "nav.config", keys: "nav.keys", mcp: "nav.mcp", logs: "nav.logs", debug: "nav.debug" };
   sidebarNav.querySelectorAll(".nav-item").forEach((btn) => {
     const key = navLabels[btn.dataset.page];
     if (key) btn.querySelector("span").textContent = t(key);
@@ -1299,13 +2842,18 @@ async function bootstrap() {
 
   navigate("dashboard");
 
-  globalTimer = setInterval(() => {
-    updateSidebarStatus();
-  }, 3000);
-}
+  if (await invoke("get_secrets_locked").catch(() => false)) {
+    showToast(t("cfg.locked"), "error");
+  }
 
-window.addEventListener("beforeunload", () => {
-  if (globalTimer) clearInterval(globalTimer);
-});
+  const recovery = await invoke("get_config_recovery").catch(() => null);
+  if (recovery) {
+    showToast(t("cfg.recovered").replace("{backup}", recovery.backup) + recovery.corruptCopy, "error");
+  }
+
+  if (listen) {
+    listen("proxy-lifecycle", () => updateSidebarStatus()).catch(() => {});
+  }
+}
 
 bootstrap();